@@ -1,5 +1,10 @@
+use crate::google_calendar::acl::ACL_SCOPE;
+use crate::google_calendar::calendars::{
+    CALENDARS_SCOPE, CALENDAR_LIST_READONLY_SCOPE, CALENDAR_LIST_SCOPE,
+};
+use crate::google_calendar::SendUpdates;
 use anyhow::Result;
-use config::{Config, ConfigError, Environment, File};
+use config::{Config, Environment, File};
 use serde::Deserialize;
 
 #[derive(Debug, Clone, Deserialize)]
@@ -13,6 +18,16 @@ pub struct AppConfig {
     pub security: SecurityConfig,
     #[serde(default)]
     pub proxy: ProxyConfig,
+    #[serde(default)]
+    pub stdio: StdioConfig,
+    #[serde(default)]
+    pub writes: WritesConfig,
+    #[serde(default)]
+    pub acl: AclConfig,
+    #[serde(default)]
+    pub calendars: CalendarsConfig,
+    #[serde(default)]
+    pub conflicts: ConflictsConfig,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -70,7 +85,10 @@ impl OAuthConfig {
     }
 
     fn default_scopes() -> Vec<String> {
-        vec!["https://www.googleapis.com/auth/calendar.events".to_owned()]
+        vec![
+            "https://www.googleapis.com/auth/calendar.events".to_owned(),
+            CALENDAR_LIST_READONLY_SCOPE.to_owned(),
+        ]
     }
 }
 
@@ -80,12 +98,57 @@ pub struct GoogleConfig {
     pub api_base: String,
     #[serde(default)]
     pub calendar_id: Option<String>,
+    #[serde(default = "GoogleConfig::default_request_timeout_secs")]
+    pub request_timeout_secs: u64,
+    #[serde(default = "GoogleConfig::default_connect_timeout_secs")]
+    pub connect_timeout_secs: u64,
+    /// Retries for rate-limited and transient failures; 0 disables retrying.
+    #[serde(default = "GoogleConfig::default_max_retries")]
+    pub max_retries: u32,
+    #[serde(default = "GoogleConfig::default_retry_base_delay_ms")]
+    pub retry_base_delay_ms: u64,
+    /// Upper bound for a single backoff; a longer `Retry-After` is returned to the caller instead.
+    #[serde(default = "GoogleConfig::default_retry_max_delay_ms")]
+    pub retry_max_delay_ms: u64,
+    /// Sustained Google API requests per second allowed per user; 0 disables the limiter.
+    #[serde(default = "GoogleConfig::default_rate_limit_per_second")]
+    pub rate_limit_per_second: f64,
+    #[serde(default = "GoogleConfig::default_rate_limit_burst")]
+    pub rate_limit_burst: u32,
 }
 
 impl GoogleConfig {
     fn default_api_base() -> String {
         "https://www.googleapis.com/calendar/v3".to_owned()
     }
+
+    fn default_request_timeout_secs() -> u64 {
+        30
+    }
+
+    fn default_connect_timeout_secs() -> u64 {
+        10
+    }
+
+    fn default_max_retries() -> u32 {
+        3
+    }
+
+    fn default_retry_base_delay_ms() -> u64 {
+        500
+    }
+
+    fn default_retry_max_delay_ms() -> u64 {
+        30_000
+    }
+
+    fn default_rate_limit_per_second() -> f64 {
+        10.0
+    }
+
+    fn default_rate_limit_burst() -> u32 {
+        20
+    }
 }
 
 impl Default for GoogleConfig {
@@ -93,6 +156,13 @@ impl Default for GoogleConfig {
         Self {
             api_base: Self::default_api_base(),
             calendar_id: None,
+            request_timeout_secs: Self::default_request_timeout_secs(),
+            connect_timeout_secs: Self::default_connect_timeout_secs(),
+            max_retries: Self::default_max_retries(),
+            retry_base_delay_ms: Self::default_retry_base_delay_ms(),
+            retry_max_delay_ms: Self::default_retry_max_delay_ms(),
+            rate_limit_per_second: Self::default_rate_limit_per_second(),
+            rate_limit_burst: Self::default_rate_limit_burst(),
         }
     }
 }
@@ -105,6 +175,9 @@ pub struct SecurityConfig {
     pub encrypt_tokens: bool,
     #[serde(default = "SecurityConfig::default_use_in_memory")]
     pub use_in_memory: bool,
+    /// Per-user records such as idempotency keys. Ignored when `use_in_memory` is set.
+    #[serde(default = "SecurityConfig::default_user_data_path")]
+    pub user_data_path: String,
 }
 
 impl SecurityConfig {
@@ -119,6 +192,10 @@ impl SecurityConfig {
     fn default_use_in_memory() -> bool {
         false
     }
+
+    fn default_user_data_path() -> String {
+        "config/user_data.json".to_owned()
+    }
 }
 
 impl Default for SecurityConfig {
@@ -127,6 +204,7 @@ impl Default for SecurityConfig {
             token_store_path: Self::default_token_store_path(),
             encrypt_tokens: Self::default_encrypt_tokens(),
             use_in_memory: Self::default_use_in_memory(),
+            user_data_path: Self::default_user_data_path(),
         }
     }
 }
@@ -139,6 +217,79 @@ pub struct ProxyConfig {
     pub redirect_path: Option<String>,
 }
 
+#[derive(Debug, Clone, Deserialize, Default)]
+pub struct StdioConfig {
+    /// Fixed user id for `--stdio` mode. When omitted the single stored user is reused,
+    /// falling back to `"local"`.
+    #[serde(default)]
+    pub user_id: Option<String>,
+}
+
+/// When `create_event` / `update_event` must be confirmed by the user before reaching Google.
+#[derive(Debug, Clone, Copy, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfirmationPolicy {
+    #[default]
+    Never,
+    /// Only writes on events with attendees, which may trigger invitation emails.
+    Attendees,
+    Always,
+}
+
+#[derive(Debug, Clone, Deserialize, Default)]
+pub struct WritesConfig {
+    #[serde(default)]
+    pub confirmation: ConfirmationPolicy,
+    /// `sendUpdates` for writes that do not choose one; `none` so guests are only emailed on
+    /// request.
+    #[serde(default)]
+    pub send_updates: SendUpdates,
+}
+
+/// Calendar sharing tools. They change who can see a calendar, so they are off by default.
+#[derive(Debug, Clone, Deserialize, Default)]
+pub struct AclConfig {
+    /// Expose the ACL tools and request the `calendar.acls` OAuth scope.
+    #[serde(default)]
+    pub enabled: bool,
+}
+
+/// Secondary calendar management tools.
+#[derive(Debug, Clone, Deserialize, Default)]
+pub struct CalendarsConfig {
+    /// Expose create/update/subscribe/unsubscribe and request the `calendar.calendars` and
+    /// `calendar.calendarlist` OAuth scopes.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Also expose deleting secondary calendars, which removes their events for good.
+    #[serde(default)]
+    pub allow_destructive: bool,
+}
+
+/// What `create_event` / `update_event` do when the new time overlaps busy events.
+#[derive(Debug, Clone, Copy, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictPolicy {
+    /// Do not look for conflicts.
+    Off,
+    /// Write the event and list the conflicts in the result.
+    #[default]
+    Warn,
+    /// Reject the write unless the call passes `allow_conflicts`.
+    Refuse,
+}
+
+/// Double-booking checks on event writes.
+#[derive(Debug, Clone, Deserialize, Default)]
+pub struct ConflictsConfig {
+    #[serde(default)]
+    pub policy: ConflictPolicy,
+    /// Calendars checked for conflicts; empty means the calendars selected in the user's
+    /// calendar list.
+    #[serde(default)]
+    pub calendars: Vec<String>,
+}
+
 impl AppConfig {
     pub fn load() -> Result<Self> {
         dotenvy::dotenv().ok();
@@ -148,8 +299,30 @@ impl AppConfig {
             .add_source(File::with_name("config/config.local").required(false))
             .add_source(Environment::with_prefix("APP").separator("__"));
 
-        let cfg = builder.build()?;
-        cfg.try_deserialize().map_err(|err: ConfigError| err.into())
+        let mut cfg: AppConfig = builder.build()?.try_deserialize()?;
+        cfg.add_feature_scopes();
+        Ok(cfg)
+    }
+
+    /// Adds the OAuth scopes that enabled optional features need beyond the configured ones.
+    fn add_feature_scopes(&mut self) {
+        let mut required = Vec::new();
+        if self.acl.enabled {
+            required.push(ACL_SCOPE);
+        }
+        if self.calendars.enabled {
+            required.extend([CALENDARS_SCOPE, CALENDAR_LIST_SCOPE]);
+        }
+        for scope in required {
+            if !self
+                .oauth
+                .scopes
+                .iter()
+                .any(|configured| configured == scope)
+            {
+                self.oauth.scopes.push(scope.to_owned());
+            }
+        }
     }
 }
 
@@ -172,16 +345,19 @@ mod tests {
                 redirect_uri: "http://localhost/oauth/callback".into(),
                 scopes: OAuthConfig::default_scopes(),
             },
-            google: GoogleConfig {
-                api_base: GoogleConfig::default_api_base(),
-                calendar_id: None,
-            },
+            google: GoogleConfig::default(),
             security: SecurityConfig {
                 token_store_path: SecurityConfig::default_token_store_path(),
                 encrypt_tokens: SecurityConfig::default_encrypt_tokens(),
                 use_in_memory: SecurityConfig::default_use_in_memory(),
+                user_data_path: SecurityConfig::default_user_data_path(),
             },
             proxy: ProxyConfig::default(),
+            stdio: StdioConfig::default(),
+            writes: WritesConfig::default(),
+            acl: AclConfig::default(),
+            calendars: CalendarsConfig::default(),
+            conflicts: ConflictsConfig::default(),
         };
 
         assert_eq!(cfg.server.bind_address, "127.0.0.1:8080");
@@ -189,8 +365,15 @@ mod tests {
             cfg.google.api_base,
             "https://www.googleapis.com/calendar/v3"
         );
+        assert_eq!(cfg.google.max_retries, 3);
         assert!(!cfg.security.encrypt_tokens);
         assert!(!cfg.security.use_in_memory);
         assert!(!cfg.proxy.enabled);
+        assert!(cfg.stdio.user_id.is_none());
+        assert_eq!(cfg.writes.confirmation, ConfirmationPolicy::Never);
+        assert_eq!(cfg.writes.send_updates, SendUpdates::None);
+        assert!(!cfg.acl.enabled);
+        assert!(!cfg.calendars.enabled && !cfg.calendars.allow_destructive);
+        assert_eq!(cfg.conflicts.policy, ConflictPolicy::Warn);
     }
 }
@@ -0,0 +1,206 @@
+use super::{GoogleCalendarClient, GoogleCalendarError};
+use crate::oauth::UserToken;
+use anyhow::Result;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// OAuth scope needed to read and change calendar sharing; not covered by `calendar.events`.
+pub const ACL_SCOPE: &str = "https://www.googleapis.com/auth/calendar.acls";
+
+/// Access granted by an ACL rule, from least to most.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum AclRole {
+    /// No access; only meaningful for the `default` scope.
+    None,
+    /// Can see free/busy information only.
+    FreeBusyReader,
+    /// Can see event details, except private events.
+    Reader,
+    /// Can create and change events.
+    Writer,
+    /// Can also change sharing.
+    Owner,
+}
+
+/// Who an ACL rule applies to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", content = "value", rename_all = "camelCase")]
+pub enum AclScope {
+    /// Everyone (public sharing).
+    Default,
+    /// A single user, by email.
+    User(String),
+    /// A Google group, by email.
+    Group(String),
+    /// Everyone in a domain, e.g. "example.com".
+    Domain(String),
+}
+
+impl AclScope {
+    fn validate(&self) -> Result<(), GoogleCalendarError> {
+        let (kind, value) = match self {
+            Self::Default => return Ok(()),
+            Self::User(value) => ("user", value),
+            Self::Group(value) => ("group", value),
+            Self::Domain(value) => ("domain", value),
+        };
+        let value = value.trim();
+        let valid = match self {
+            Self::Domain(_) => !value.is_empty() && !value.contains('@'),
+            _ => value.contains('@'),
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(GoogleCalendarError::invalid(format!(
+                "'{value}' is not a valid {kind} for an ACL scope"
+            )))
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AclRule {
+    /// Rule id such as "user:alice@example.com"; omitted when inserting.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub role: AclRole,
+    pub scope: AclScope,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AclRules {
+    #[serde(default)]
+    pub items: Vec<AclRule>,
+    #[serde(rename = "nextPageToken", default, skip_serializing)]
+    pub next_page_token: Option<String>,
+}
+
+impl GoogleCalendarClient {
+    /// All sharing rules of a calendar the user owns.
+    pub async fn list_acl(
+        &self,
+        token: &UserToken,
+        calendar_id: &Option<String>,
+    ) -> Result<AclRules> {
+        let calendar_id = self.resolve_calendar(calendar_id);
+        let url = self.calendar_url(calendar_id, "acl")?;
+        let mut rules = AclRules {
+            items: Vec::new(),
+            next_page_token: None,
+        };
+        let mut page_token = None;
+        loop {
+            let mut request = self.http.get(url.clone()).bearer_auth(token.access_token());
+            if let Some(page_token) = &page_token {
+                request = request.query(&[("pageToken", page_token)]);
+            }
+            let page = self
+                .send(token, request, true)
+                .await?
+                .json::<AclRules>()
+                .await?;
+            rules.items.extend(page.items);
+            page_token = page.next_page_token;
+            if page_token.is_none() {
+                return Ok(rules);
+            }
+        }
+    }
+
+    /// Grants `role` to `scope`; an existing rule for the same scope is replaced by Google.
+    pub async fn insert_acl(
+        &self,
+        token: &UserToken,
+        calendar_id: &Option<String>,
+        scope: AclScope,
+        role: AclRole,
+        send_notifications: bool,
+    ) -> Result<AclRule> {
+        scope.validate()?;
+        let calendar_id = self.resolve_calendar(calendar_id);
+        let url = self.calendar_url(calendar_id, "acl")?;
+        let rule = AclRule {
+            id: None,
+            role,
+            scope,
+        };
+        let request = self
+            .http
+            .post(url)
+            .bearer_auth(token.access_token())
+            .query(&[("sendNotifications", send_notifications.to_string())])
+            .json(&rule);
+        // A repeated insert could email the grantee twice.
+        let response = self.send(token, request, false).await?;
+
+        Ok(response.json::<AclRule>().await?)
+    }
+
+    pub async fn patch_acl(
+        &self,
+        token: &UserToken,
+        calendar_id: &Option<String>,
+        rule_id: &str,
+        role: AclRole,
+    ) -> Result<AclRule> {
+        let calendar_id = self.resolve_calendar(calendar_id);
+        let url = self.calendar_url(
+            calendar_id,
+            &format!("acl/{}", urlencoding::encode(rule_id)),
+        )?;
+        let request = self
+            .http
+            .patch(url)
+            .bearer_auth(token.access_token())
+            .json(&serde_json::json!({ "role": role }));
+        let response = self.send(token, request, true).await?;
+
+        Ok(response.json::<AclRule>().await?)
+    }
+
+    pub async fn delete_acl(
+        &self,
+        token: &UserToken,
+        calendar_id: &Option<String>,
+        rule_id: &str,
+    ) -> Result<()> {
+        let calendar_id = self.resolve_calendar(calendar_id);
+        let url = self.calendar_url(
+            calendar_id,
+            &format!("acl/{}", urlencoding::encode(rule_id)),
+        )?;
+        let request = self.http.delete(url).bearer_auth(token.access_token());
+        self.send(token, request, true).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn acl_scope_uses_google_wire_format() {
+        let rule: AclRule = serde_json::from_str(
+            r#"{"id":"user:alice@example.com","role":"reader","scope":{"type":"user","value":"alice@example.com"}}"#,
+        )
+        .unwrap();
+        assert_eq!(rule.role, AclRole::Reader);
+        assert_eq!(rule.scope, AclScope::User("alice@example.com".into()));
+
+        let public: AclScope = serde_json::from_str(r#"{"type":"default"}"#).unwrap();
+        assert_eq!(public, AclScope::Default);
+        assert_eq!(
+            serde_json::to_value(&public).unwrap(),
+            serde_json::json!({"type": "default"})
+        );
+
+        assert!(AclScope::Domain("example.com".into()).validate().is_ok());
+        assert!(AclScope::Domain("team@example.com".into())
+            .validate()
+            .is_err());
+        assert!(AclScope::Group("oncall".into()).validate().is_err());
+    }
+}
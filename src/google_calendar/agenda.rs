@@ -0,0 +1,509 @@
+use super::analytics::{
+    blocks_time, event_key, self_response, timed_range, EventKey, WorkingHours,
+};
+use super::time_expr::local;
+use super::{CalendarEvent, Transparency};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, Utc};
+use chrono_tz::Tz;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Event fields the agenda needs, in Google partial-response syntax.
+pub const AGENDA_FIELDS: &str = "id,iCalUID,status,summary,location,description,start,end,transparency,hangoutLink,conferenceData(entryPoints(entryPointType,uri)),attendees(email,self,responseStatus)";
+
+/// Default for the shortest free block worth listing.
+pub const DEFAULT_MIN_FREE_MINUTES: i64 = 30;
+
+/// Video meeting hosts whose links are picked out of locations and descriptions.
+const MEETING_HOSTS: &[&str] = &[
+    "meet.google.com",
+    "zoom.us",
+    "teams.microsoft.com",
+    "teams.live.com",
+    "webex.com",
+    "whereby.com",
+    "gotomeeting.com",
+];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AgendaSpan {
+    #[default]
+    Day,
+    /// The ISO week, Monday to Sunday, containing the date.
+    Week,
+}
+
+#[derive(Debug, Clone)]
+pub struct AgendaOptions {
+    pub tz: Tz,
+    pub span: AgendaSpan,
+    /// Day to show, or any day of the week to show.
+    pub date: NaiveDate,
+    pub working_hours: WorkingHours,
+    /// Shortest free block to list.
+    pub min_free: Duration,
+}
+
+impl AgendaOptions {
+    fn first_day(&self) -> NaiveDate {
+        match self.span {
+            AgendaSpan::Day => self.date,
+            AgendaSpan::Week => {
+                self.date - Duration::days(i64::from(self.date.weekday().num_days_from_monday()))
+            }
+        }
+    }
+
+    fn day_count(&self) -> i64 {
+        match self.span {
+            AgendaSpan::Day => 1,
+            AgendaSpan::Week => 7,
+        }
+    }
+
+    /// From midnight of the first day to midnight after the last, in the user's zone.
+    pub fn range(&self) -> (DateTime<Utc>, DateTime<Utc>) {
+        let first = self.first_day();
+        (
+            midnight(first, self.tz),
+            midnight(first + Duration::days(self.day_count()), self.tz),
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct Agenda {
+    pub time_zone: String,
+    pub days: Vec<AgendaDay>,
+    /// Invitations in the range the user has not answered yet.
+    pub pending_rsvps: usize,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct AgendaDay {
+    pub date: NaiveDate,
+    /// Day of the week, e.g. "Monday".
+    pub weekday: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub all_day: Vec<AgendaItem>,
+    pub events: Vec<AgendaItem>,
+    /// Free time within working hours, at least `min_free_minutes` long.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub free_blocks: Vec<FreeBlock>,
+    /// Time taken by busy events, counting overlaps once.
+    pub busy_hours: f64,
+}
+
+/// One event, trimmed to what an agenda needs.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct AgendaItem {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    /// Local "HH:MM", prefixed with the date when it falls on another day. Unset for all-day
+    /// events.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end: Option<String>,
+    /// Calendars the event was found on.
+    pub calendar_ids: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    /// Link to join the call, from the conference data, the location or the description.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub meeting_url: Option<String>,
+    /// The user's RSVP (`needsAction`, `tentative` or `accepted`) when they are a guest.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_status: Option<String>,
+    /// The event shows as free.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub free: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub travel: Option<TravelHint>,
+}
+
+/// Set when the previous event of the day with a place was somewhere else.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct TravelHint {
+    /// Location of the previous event.
+    pub from: String,
+    /// Minutes between the end of the previous event and the start of this one; negative when
+    /// they overlap.
+    pub gap_minutes: i64,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct FreeBlock {
+    /// Local "HH:MM".
+    pub start: String,
+    pub end: String,
+    pub minutes: i64,
+}
+
+fn midnight(date: NaiveDate, tz: Tz) -> DateTime<Utc> {
+    let naive = date.and_time(NaiveTime::MIN);
+    local(naive, tz).unwrap_or_else(|| naive.and_utc())
+}
+
+fn clock(at: DateTime<Utc>, day: NaiveDate, tz: Tz) -> String {
+    let at = at.with_timezone(&tz);
+    if at.date_naive() == day {
+        at.format("%H:%M").to_string()
+    } else {
+        at.format("%Y-%m-%d %H:%M").to_string()
+    }
+}
+
+/// The first link in `text` to a known video meeting host.
+fn find_meeting_link(text: &str) -> Option<&str> {
+    text.match_indices("https://").find_map(|(at, _)| {
+        let url = text[at..]
+            .split(|c: char| c.is_whitespace() || matches!(c, '"' | '\'' | '<' | '>' | ')'))
+            .next()?;
+        let host = url["https://".len()..].split(['/', '?', '#']).next()?;
+        MEETING_HOSTS
+            .iter()
+            .any(|known| host == *known || host.ends_with(&format!(".{known}")))
+            .then_some(url)
+    })
+}
+
+fn meeting_url(event: &CalendarEvent) -> Option<String> {
+    event
+        .conference_data
+        .as_ref()
+        .and_then(|conference| conference.join_url())
+        .or(event.hangout_link.as_deref())
+        .or_else(|| {
+            [event.location.as_deref(), event.description.as_deref()]
+                .into_iter()
+                .flatten()
+                .find_map(find_meeting_link)
+        })
+        .map(str::to_owned)
+}
+
+/// A location that is a place rather than a call link.
+fn place(event: &CalendarEvent) -> Option<&str> {
+    event
+        .location
+        .as_deref()
+        .map(str::trim)
+        .filter(|location| !location.is_empty() && !location.contains("://"))
+}
+
+fn item(calendar_ids: Vec<String>, event: &CalendarEvent, day: NaiveDate, tz: Tz) -> AgendaItem {
+    let range = timed_range(event);
+    AgendaItem {
+        id: event.id.clone(),
+        summary: event.summary.clone(),
+        start: range.map(|(start, _)| clock(start, day, tz)),
+        end: range.map(|(_, end)| clock(end, day, tz)),
+        calendar_ids,
+        location: event.location.clone(),
+        meeting_url: meeting_url(event),
+        response_status: self_response(event).map(str::to_owned),
+        free: event.transparency == Some(Transparency::Transparent),
+        travel: None,
+    }
+}
+
+/// Merges overlapping intervals, sorted by start.
+fn merge(
+    mut intervals: Vec<(DateTime<Utc>, DateTime<Utc>)>,
+) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    intervals.sort();
+    let mut merged: Vec<(DateTime<Utc>, DateTime<Utc>)> = Vec::new();
+    for (start, end) in intervals {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+/// Builds the agenda from `(calendar_id, event)` pairs listed for [`AgendaOptions::range`],
+/// with recurring events expanded into instances.
+///
+/// An event found on several calendars is shown once, listing each calendar. Cancelled events
+/// and invitations the user declined are left out.
+pub fn build_agenda(events: Vec<(String, CalendarEvent)>, options: &AgendaOptions) -> Agenda {
+    let tz = options.tz;
+    let mut merged: Vec<(Vec<String>, CalendarEvent)> = Vec::new();
+    let mut seen: HashMap<EventKey, usize> = HashMap::new();
+    for (calendar_id, event) in events {
+        if event.status.as_deref() == Some("cancelled") || self_response(&event) == Some("declined")
+        {
+            continue;
+        }
+        let key = event_key(&event);
+        if let Some(&index) = key.as_ref().and_then(|key| seen.get(key)) {
+            let calendars: &mut Vec<String> = &mut merged[index].0;
+            if !calendars.contains(&calendar_id) {
+                calendars.push(calendar_id);
+            }
+            continue;
+        }
+        if let Some(key) = key {
+            seen.insert(key, merged.len());
+        }
+        merged.push((vec![calendar_id], event));
+    }
+
+    let first = options.first_day();
+    let last = first + Duration::days(options.day_count() - 1);
+    let mut days: Vec<AgendaDay> = (0..options.day_count())
+        .map(|offset| {
+            let date = first + Duration::days(offset);
+            AgendaDay {
+                date,
+                weekday: date.format("%A").to_string(),
+                all_day: Vec::new(),
+                events: Vec::new(),
+                free_blocks: Vec::new(),
+                busy_hours: 0.0,
+            }
+        })
+        .collect();
+    let mut timed: Vec<Vec<&CalendarEvent>> = vec![Vec::new(); days.len()];
+
+    for (calendar_ids, event) in &merged {
+        let Some(start) = event.start.as_ref() else {
+            continue;
+        };
+        if let Some((start, _)) = timed_range(event) {
+            // Events running over from the day before are shown on the first day.
+            let date = start.with_timezone(&tz).date_naive().max(first);
+            if date <= last {
+                let index = (date - first).num_days() as usize;
+                days[index]
+                    .events
+                    .push(item(calendar_ids.clone(), event, date, tz));
+                timed[index].push(event);
+            }
+        } else if let Some(start) = start.date {
+            let end = event
+                .end
+                .as_ref()
+                .and_then(|end| end.date)
+                .filter(|end| *end > start)
+                .unwrap_or(start + Duration::days(1));
+            let mut date = start.max(first);
+            while date < end && date <= last {
+                let index = (date - first).num_days() as usize;
+                days[index]
+                    .all_day
+                    .push(item(calendar_ids.clone(), event, date, tz));
+                date += Duration::days(1);
+            }
+        }
+    }
+
+    for (day, events) in days.iter_mut().zip(&timed) {
+        let mut order: Vec<usize> = (0..events.len()).collect();
+        order.sort_by_key(|&index| timed_range(events[index]).map(|(start, _)| start));
+        day.events = order
+            .iter()
+            .map(|&index| day.events[index].clone())
+            .collect();
+
+        let mut previous: Option<(&str, DateTime<Utc>)> = None;
+        for (&index, item) in order.iter().zip(&mut day.events) {
+            let event = events[index];
+            let (Some(place), Some((start, end))) = (place(event), timed_range(event)) else {
+                continue;
+            };
+            if let Some((from, previous_end)) = previous {
+                if !from.eq_ignore_ascii_case(place) {
+                    item.travel = Some(TravelHint {
+                        from: from.to_owned(),
+                        gap_minutes: (start - previous_end).num_minutes(),
+                    });
+                }
+            }
+            previous = Some((place, end));
+        }
+
+        let (day_start, day_end) = (
+            midnight(day.date, tz),
+            midnight(day.date + Duration::days(1), tz),
+        );
+        let busy = merge(
+            events
+                .iter()
+                .filter(|event| blocks_time(event))
+                .filter_map(|event| timed_range(event))
+                .map(|(start, end)| (start.max(day_start), end.min(day_end)))
+                .filter(|(start, end)| start < end)
+                .collect(),
+        );
+        let busy_minutes: i64 = busy
+            .iter()
+            .map(|(start, end)| (*end - *start).num_minutes())
+            .sum();
+        day.busy_hours = (busy_minutes as f64 / 60.0 * 100.0).round() / 100.0;
+
+        let hours = &options.working_hours;
+        if !hours.is_working_day(day.date.weekday()) {
+            continue;
+        }
+        let (Some(work_start), Some(work_end)) = (
+            local(day.date.and_time(hours.start), tz),
+            local(day.date.and_time(hours.end), tz),
+        ) else {
+            continue;
+        };
+        let mut cursor = work_start;
+        for (start, end) in busy
+            .into_iter()
+            .chain([(work_end, work_end)])
+            .filter(|(_, end)| *end > work_start)
+        {
+            let start = start.min(work_end);
+            if start - cursor >= options.min_free {
+                day.free_blocks.push(FreeBlock {
+                    start: clock(cursor, day.date, tz),
+                    end: clock(start, day.date, tz),
+                    minutes: (start - cursor).num_minutes(),
+                });
+            }
+            cursor = cursor.max(end);
+            if cursor >= work_end {
+                break;
+            }
+        }
+    }
+
+    Agenda {
+        time_zone: tz.name().to_owned(),
+        days,
+        pending_rsvps: merged
+            .iter()
+            .filter(|(_, event)| self_response(event) == Some("needsAction"))
+            .count(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Monday 2025-10-20: a standup on two calendars, an offsite spanning the day, lunch
+    /// across town and a declined meeting.
+    fn monday() -> Agenda {
+        let standup = json!({
+            "id": "standup",
+            "iCalUID": "standup@google.com",
+            "summary": "Standup",
+            "location": "Office, Room 4",
+            "description": "Remote? <a href=\"https://acme.zoom.us/j/123?pwd=x\">join</a>",
+            "start": {"dateTime": "2025-10-20T09:00:00Z"},
+            "end": {"dateTime": "2025-10-20T09:30:00Z"},
+            "attendees": [{"email": "me@example.com", "self": true, "responseStatus": "needsAction"}]
+        });
+        // (calendar id, event) pairs, as listed across the user's calendars.
+        let events = serde_json::from_value(json!([
+            ["primary", standup],
+            ["team@group.calendar.google.com", standup],
+            ["primary", {
+                "id": "offsite",
+                "summary": "Offsite",
+                "start": {"date": "2025-10-19"},
+                "end": {"date": "2025-10-21"}
+            }],
+            ["primary", {
+                "id": "lunch",
+                "summary": "Lunch with Ana",
+                "location": "Cafe Luna",
+                "start": {"dateTime": "2025-10-20T12:00:00Z"},
+                "end": {"dateTime": "2025-10-20T13:00:00Z"},
+                "hangoutLink": "https://meet.google.com/abc-defg-hij"
+            }],
+            ["primary", {
+                "id": "skipped",
+                "start": {"dateTime": "2025-10-20T14:00:00Z"},
+                "end": {"dateTime": "2025-10-20T15:00:00Z"},
+                "attendees": [{"email": "me@example.com", "self": true, "responseStatus": "declined"}]
+            }]
+        ]))
+        .unwrap();
+        let options = AgendaOptions {
+            tz: chrono_tz::UTC,
+            span: AgendaSpan::Day,
+            date: NaiveDate::from_ymd_opt(2025, 10, 20).unwrap(),
+            working_hours: WorkingHours::default(),
+            min_free: Duration::minutes(DEFAULT_MIN_FREE_MINUTES),
+        };
+        build_agenda(events, &options)
+    }
+
+    #[test]
+    fn events_on_several_calendars_are_merged() {
+        let agenda = monday();
+        let day = &agenda.days[0];
+        assert_eq!(day.weekday, "Monday");
+        let summaries: Vec<_> = day.events.iter().map(|e| e.summary.as_deref()).collect();
+        assert_eq!(summaries, [Some("Standup"), Some("Lunch with Ana")]);
+        assert_eq!(day.events[0].calendar_ids.len(), 2);
+    }
+
+    #[test]
+    fn all_day_events_are_listed_apart() {
+        let agenda = monday();
+        assert_eq!(
+            agenda.days[0].all_day[0].summary.as_deref(),
+            Some("Offsite")
+        );
+    }
+
+    #[test]
+    fn unanswered_invitations_are_counted() {
+        assert_eq!(monday().pending_rsvps, 1);
+    }
+
+    #[test]
+    fn meeting_links_come_from_the_description_or_hangout_link() {
+        let agenda = monday();
+        let urls: Vec<_> = agenda.days[0]
+            .events
+            .iter()
+            .map(|e| e.meeting_url.as_deref())
+            .collect();
+        assert_eq!(
+            urls,
+            [
+                Some("https://acme.zoom.us/j/123?pwd=x"),
+                Some("https://meet.google.com/abc-defg-hij")
+            ]
+        );
+    }
+
+    #[test]
+    fn changing_location_adds_a_travel_hint() {
+        let agenda = monday();
+        let travel = agenda.days[0].events[1].travel.as_ref().unwrap();
+        assert_eq!(
+            (travel.from.as_str(), travel.gap_minutes),
+            ("Office, Room 4", 150)
+        );
+    }
+
+    #[test]
+    fn free_blocks_fill_the_working_hours() {
+        let agenda = monday();
+        let day = &agenda.days[0];
+        assert_eq!(day.busy_hours, 1.5);
+        let free: Vec<_> = day
+            .free_blocks
+            .iter()
+            .map(|block| (block.start.as_str(), block.end.as_str()))
+            .collect();
+        assert_eq!(free, [("09:30", "12:00"), ("13:00", "18:00")]);
+    }
+}
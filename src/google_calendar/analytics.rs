@@ -0,0 +1,520 @@
+use super::{CalendarEvent, EventType, GoogleCalendarError, Transparency};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, Utc, Weekday};
+use chrono_tz::Tz;
+use schemars::JsonSchema;
+use serde::{de, Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Gap between two meetings that still counts as back-to-back.
+const BACK_TO_BACK_GAP_MINUTES: i64 = 5;
+/// Suffix of room and equipment calendars, which are not collaborators.
+const RESOURCE_CALENDAR_SUFFIX: &str = "@resource.calendar.google.com";
+
+/// Event fields the report needs, in Google partial-response syntax.
+pub const REPORT_FIELDS: &str = "id,iCalUID,status,start,end,transparency,eventType,recurringEventId,attendees(email,self,responseStatus,resource)";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum GroupBy {
+    Day,
+    /// ISO weeks, starting on Monday.
+    #[default]
+    Week,
+    Calendar,
+}
+
+/// The user's working day, in their time zone.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct WorkingHours {
+    /// Local start, e.g. "09:00".
+    #[serde(
+        default = "WorkingHours::default_start",
+        deserialize_with = "deserialize_clock"
+    )]
+    pub start: NaiveTime,
+    /// Local end, e.g. "18:00".
+    #[serde(
+        default = "WorkingHours::default_end",
+        deserialize_with = "deserialize_clock"
+    )]
+    pub end: NaiveTime,
+    /// Working days as ISO numbers, 1 = Monday to 7 = Sunday.
+    #[serde(default = "WorkingHours::default_days")]
+    pub days: Vec<u8>,
+}
+
+impl Default for WorkingHours {
+    fn default() -> Self {
+        Self {
+            start: Self::default_start(),
+            end: Self::default_end(),
+            days: Self::default_days(),
+        }
+    }
+}
+
+impl WorkingHours {
+    fn default_start() -> NaiveTime {
+        NaiveTime::from_hms_opt(9, 0, 0).expect("valid time")
+    }
+
+    fn default_end() -> NaiveTime {
+        NaiveTime::from_hms_opt(18, 0, 0).expect("valid time")
+    }
+
+    fn default_days() -> Vec<u8> {
+        vec![1, 2, 3, 4, 5]
+    }
+
+    pub fn validate(&self) -> Result<(), GoogleCalendarError> {
+        if self.start >= self.end {
+            return Err(GoogleCalendarError::invalid(
+                "working hours must start before they end",
+            ));
+        }
+        if self.days.iter().any(|day| !(1..=7).contains(day)) {
+            return Err(GoogleCalendarError::invalid(
+                "working days are numbered 1 (Monday) to 7 (Sunday)",
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn is_working_day(&self, day: Weekday) -> bool {
+        self.days.contains(&(day.number_from_monday() as u8))
+    }
+
+    /// Whether any part of `start..end` falls outside working hours in `tz`.
+    pub fn is_outside(&self, start: DateTime<Utc>, end: DateTime<Utc>, tz: Tz) -> bool {
+        let (start, end) = (start.with_timezone(&tz), end.with_timezone(&tz));
+        if start.date_naive() != end.date_naive() && end.time() != NaiveTime::MIN {
+            return true;
+        }
+        let ends_at = if end.time() == NaiveTime::MIN && end > start {
+            // Ending at midnight is the end of the start day.
+            NaiveTime::from_hms_opt(23, 59, 59).expect("valid time")
+        } else {
+            end.time()
+        };
+        !self.is_working_day(start.weekday()) || start.time() < self.start || ends_at > self.end
+    }
+}
+
+/// Accepts "HH:MM" as well as "HH:MM:SS".
+fn deserialize_clock<'de, D: Deserializer<'de>>(deserializer: D) -> Result<NaiveTime, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    NaiveTime::parse_from_str(value.trim(), "%H:%M")
+        .or_else(|_| NaiveTime::parse_from_str(value.trim(), "%H:%M:%S"))
+        .map_err(|_| de::Error::custom(format!("invalid time of day '{value}', expected HH:MM")))
+}
+
+#[derive(Debug, Clone)]
+pub struct ReportOptions {
+    pub group_by: GroupBy,
+    pub tz: Tz,
+    pub working_hours: WorkingHours,
+    /// How many collaborators to list per period.
+    pub top_collaborators: usize,
+}
+
+/// Time spent with one person across the meetings of a period.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct Collaborator {
+    pub email: String,
+    pub meetings: usize,
+    pub hours: f64,
+}
+
+/// Metrics for one period, or for one calendar with `GroupBy::Calendar`.
+///
+/// A meeting is a timed, non-cancelled, busy event with at least one other guest that the user
+/// has not declined. Hours are summed per event, so overlapping meetings count twice.
+#[derive(Debug, Clone, Default, Serialize, JsonSchema)]
+pub struct PeriodMetrics {
+    /// "2025-10-20" for days, "2025-W43" for weeks, the calendar id for calendars, or "total".
+    pub period: String,
+    pub event_count: usize,
+    pub meeting_count: usize,
+    pub meeting_hours: f64,
+    pub focus_time_hours: f64,
+    /// Instances of recurring meetings.
+    pub recurring_meeting_count: usize,
+    pub recurring_meeting_hours: f64,
+    /// Meetings starting at most 5 minutes after the previous one ended, in runs of two or more.
+    pub back_to_back_streaks: usize,
+    /// Most meetings in a single back-to-back run.
+    pub longest_back_to_back_streak: usize,
+    pub meetings_outside_working_hours: usize,
+    /// Invitations the user declined; not counted as meetings.
+    pub declined: usize,
+    /// Invitations the user has not answered yet.
+    pub no_response: usize,
+    pub top_collaborators: Vec<Collaborator>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct TimeReport {
+    pub time_zone: String,
+    pub group_by: GroupBy,
+    pub total: PeriodMetrics,
+    pub periods: Vec<PeriodMetrics>,
+}
+
+/// The user's own RSVP, if they are on the guest list.
+pub fn self_response(event: &CalendarEvent) -> Option<&str> {
+    event
+        .attendees
+        .as_ref()?
+        .iter()
+        .find(|attendee| attendee.is_self)?
+        .response_status
+        .as_deref()
+}
+
+/// Whether the event takes up the user's time: confirmed or tentative, shown as busy and not
+/// declined by them.
+pub fn blocks_time(event: &CalendarEvent) -> bool {
+    event.status.as_deref() != Some("cancelled")
+        && event.transparency != Some(Transparency::Transparent)
+        && self_response(event) != Some("declined")
+}
+
+/// Start and end of an event with a time of day; `None` for all-day events.
+pub(super) fn timed_range(event: &CalendarEvent) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    Some((
+        event.start.as_ref()?.date_time?,
+        event.end.as_ref()?.date_time?,
+    ))
+}
+
+/// Identifies one occurrence of an event across calendars: its iCalUID (or id) and start.
+pub(super) type EventKey = (String, Option<DateTime<Utc>>, Option<NaiveDate>);
+
+/// The [`EventKey`] of `event`, or `None` when it has neither an iCalUID nor an id.
+pub(super) fn event_key(event: &CalendarEvent) -> Option<EventKey> {
+    let uid = event.extra.get("iCalUID").and_then(Value::as_str);
+    let key = uid.map(str::to_owned).or_else(|| event.id.clone())?;
+    let start = event.start.as_ref();
+    Some((
+        key,
+        start.and_then(|start| start.date_time),
+        start.and_then(|start| start.date),
+    ))
+}
+
+fn hours(duration: Duration) -> f64 {
+    duration.num_minutes() as f64 / 60.0
+}
+
+fn round(hours: f64) -> f64 {
+    (hours * 100.0).round() / 100.0
+}
+
+/// Computes per-period metrics over `events`, given as `(calendar_id, event)` pairs. Expects
+/// recurring events expanded into instances.
+///
+/// An event found on several calendars, e.g. a meeting with a teammate whose calendar is also
+/// reported, is counted once, under the first calendar it was listed for.
+pub fn time_report(events: &[(String, CalendarEvent)], options: &ReportOptions) -> TimeReport {
+    let mut seen = HashSet::new();
+    let events: Vec<&(String, CalendarEvent)> = events
+        .iter()
+        .filter(|(_, event)| event_key(event).is_none_or(|key| seen.insert(key)))
+        .collect();
+
+    let mut groups: BTreeMap<String, Vec<&CalendarEvent>> = BTreeMap::new();
+    for (calendar_id, event) in &events {
+        let Some(start) = event.start.as_ref() else {
+            continue;
+        };
+        let local = match (start.date_time, start.date) {
+            (Some(at), _) => at.with_timezone(&options.tz).date_naive(),
+            (None, Some(date)) => date,
+            (None, None) => continue,
+        };
+        let key = match options.group_by {
+            GroupBy::Day => local.format("%Y-%m-%d").to_string(),
+            GroupBy::Week => {
+                let week = local.iso_week();
+                format!("{}-W{:02}", week.year(), week.week())
+            }
+            GroupBy::Calendar => calendar_id.clone(),
+        };
+        groups.entry(key).or_default().push(event);
+    }
+
+    let all: Vec<&CalendarEvent> = events.iter().map(|(_, event)| event).collect();
+    TimeReport {
+        time_zone: options.tz.name().to_owned(),
+        group_by: options.group_by,
+        total: metrics("total".to_owned(), &all, options),
+        periods: groups
+            .into_iter()
+            .map(|(period, events)| metrics(period, &events, options))
+            .collect(),
+    }
+}
+
+fn metrics(period: String, events: &[&CalendarEvent], options: &ReportOptions) -> PeriodMetrics {
+    let mut metrics = PeriodMetrics {
+        period,
+        event_count: events.len(),
+        ..Default::default()
+    };
+    let mut meetings: Vec<(DateTime<Utc>, DateTime<Utc>)> = Vec::new();
+    let mut collaborators: HashMap<&str, (usize, Duration)> = HashMap::new();
+    let (mut meeting_time, mut focus_time, mut recurring_time) =
+        (Duration::zero(), Duration::zero(), Duration::zero());
+
+    for event in events {
+        if event.status.as_deref() == Some("cancelled") {
+            continue;
+        }
+        match self_response(event) {
+            Some("declined") => {
+                metrics.declined += 1;
+                continue;
+            }
+            Some("needsAction") => metrics.no_response += 1,
+            _ => {}
+        }
+        let Some((start, end)) = timed_range(event) else {
+            continue;
+        };
+        let duration = (end - start).max(Duration::zero());
+        if event.event_type == Some(EventType::FocusTime) {
+            focus_time += duration;
+            continue;
+        }
+
+        let guests: Vec<&str> = event
+            .attendees
+            .iter()
+            .flatten()
+            .filter(|attendee| {
+                !attendee.is_self
+                    && !attendee.email.ends_with(RESOURCE_CALENDAR_SUFFIX)
+                    && attendee.extra.get("resource").and_then(|v| v.as_bool()) != Some(true)
+            })
+            .map(|attendee| attendee.email.as_str())
+            .collect();
+        let is_meeting = !guests.is_empty()
+            && blocks_time(event)
+            && matches!(event.event_type, None | Some(EventType::Default));
+        if !is_meeting {
+            continue;
+        }
+
+        metrics.meeting_count += 1;
+        meeting_time += duration;
+        if event.extra.contains_key("recurringEventId") {
+            metrics.recurring_meeting_count += 1;
+            recurring_time += duration;
+        }
+        if options.working_hours.is_outside(start, end, options.tz) {
+            metrics.meetings_outside_working_hours += 1;
+        }
+        for email in guests {
+            let entry = collaborators.entry(email).or_insert((0, Duration::zero()));
+            entry.0 += 1;
+            entry.1 += duration;
+        }
+        meetings.push((start, end));
+    }
+
+    meetings.sort();
+    let mut streak = 1;
+    for pair in meetings.windows(2) {
+        let gap = pair[1].0 - pair[0].1;
+        if gap >= Duration::zero() && gap <= Duration::minutes(BACK_TO_BACK_GAP_MINUTES) {
+            streak += 1;
+            if streak == 2 {
+                metrics.back_to_back_streaks += 1;
+            }
+        } else {
+            streak = 1;
+        }
+        metrics.longest_back_to_back_streak = metrics.longest_back_to_back_streak.max(streak);
+    }
+
+    let mut top: Vec<Collaborator> = collaborators
+        .into_iter()
+        .map(|(email, (meetings, time))| Collaborator {
+            email: email.to_owned(),
+            meetings,
+            hours: round(hours(time)),
+        })
+        .collect();
+    top.sort_by(|a, b| {
+        b.hours
+            .total_cmp(&a.hours)
+            .then(b.meetings.cmp(&a.meetings))
+            .then_with(|| a.email.cmp(&b.email))
+    });
+    top.truncate(options.top_collaborators);
+
+    metrics.meeting_hours = round(hours(meeting_time));
+    metrics.focus_time_hours = round(hours(focus_time));
+    metrics.recurring_meeting_hours = round(hours(recurring_time));
+    metrics.top_collaborators = top;
+    metrics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Three back-to-back meetings on Monday morning, one in the evening, focus time on
+    /// Tuesday and a declined meeting on Wednesday.
+    fn week() -> Vec<(String, CalendarEvent)> {
+        let events: Vec<CalendarEvent> = serde_json::from_value(json!([
+            {
+                "start": {"dateTime": "2025-10-20T09:00:00Z"},
+                "end": {"dateTime": "2025-10-20T10:00:00Z"},
+                "attendees": [
+                    {"email": "me@example.com", "self": true, "responseStatus": "accepted"},
+                    {"email": "ana@example.com"}
+                ]
+            },
+            {
+                "recurringEventId": "standup",
+                "start": {"dateTime": "2025-10-20T10:05:00Z"},
+                "end": {"dateTime": "2025-10-20T10:35:00Z"},
+                "attendees": [
+                    {"email": "me@example.com", "self": true, "responseStatus": "accepted"},
+                    {"email": "ana@example.com"}
+                ]
+            },
+            {
+                "start": {"dateTime": "2025-10-20T10:35:00Z"},
+                "end": {"dateTime": "2025-10-20T11:00:00Z"},
+                "attendees": [
+                    {"email": "me@example.com", "self": true, "responseStatus": "accepted"},
+                    {"email": "ana@example.com"}
+                ]
+            },
+            {
+                "start": {"dateTime": "2025-10-20T19:00:00Z"},
+                "end": {"dateTime": "2025-10-20T20:00:00Z"},
+                "attendees": [
+                    {"email": "me@example.com", "self": true, "responseStatus": "needsAction"},
+                    {"email": "bo@example.com"}
+                ]
+            },
+            {
+                "eventType": "focusTime",
+                "start": {"dateTime": "2025-10-21T13:00:00Z"},
+                "end": {"dateTime": "2025-10-21T15:00:00Z"}
+            },
+            {
+                "start": {"dateTime": "2025-10-22T09:00:00Z"},
+                "end": {"dateTime": "2025-10-22T10:00:00Z"},
+                "attendees": [
+                    {"email": "me@example.com", "self": true, "responseStatus": "declined"},
+                    {"email": "ana@example.com"}
+                ]
+            }
+        ]))
+        .unwrap();
+        events
+            .into_iter()
+            .map(|event| ("primary".to_owned(), event))
+            .collect()
+    }
+
+    fn report(events: &[(String, CalendarEvent)], group_by: GroupBy) -> TimeReport {
+        let options = ReportOptions {
+            group_by,
+            tz: chrono_tz::UTC,
+            working_hours: WorkingHours::default(),
+            top_collaborators: 1,
+        };
+        time_report(events, &options)
+    }
+
+    #[test]
+    fn accepted_and_pending_meetings_are_counted() {
+        let total = report(&week(), GroupBy::Day).total;
+        assert_eq!(total.meeting_count, 4);
+        assert_eq!(total.meeting_hours, 2.92);
+        assert_eq!(total.recurring_meeting_count, 1);
+    }
+
+    #[test]
+    fn focus_time_is_reported_apart_from_meetings() {
+        let total = report(&week(), GroupBy::Day).total;
+        assert_eq!(total.focus_time_hours, 2.0);
+    }
+
+    #[test]
+    fn meetings_within_five_minutes_form_a_streak() {
+        let total = report(&week(), GroupBy::Day).total;
+        assert_eq!(total.back_to_back_streaks, 1);
+        assert_eq!(total.longest_back_to_back_streak, 3);
+    }
+
+    #[test]
+    fn evening_meetings_are_outside_working_hours() {
+        let total = report(&week(), GroupBy::Day).total;
+        assert_eq!(total.meetings_outside_working_hours, 1);
+    }
+
+    #[test]
+    fn declined_and_unanswered_invitations_are_tallied() {
+        let total = report(&week(), GroupBy::Day).total;
+        assert_eq!((total.declined, total.no_response), (1, 1));
+    }
+
+    #[test]
+    fn top_collaborators_are_ranked_by_shared_meetings() {
+        let total = report(&week(), GroupBy::Day).total;
+        assert_eq!(total.top_collaborators[0].email, "ana@example.com");
+        assert_eq!(total.top_collaborators[0].meetings, 3);
+    }
+
+    #[test]
+    fn periods_follow_the_grouping() {
+        let report = report(&week(), GroupBy::Day);
+        let periods: Vec<&str> = report.periods.iter().map(|p| p.period.as_str()).collect();
+        assert_eq!(periods, ["2025-10-20", "2025-10-21", "2025-10-22"]);
+    }
+
+    #[test]
+    fn working_days_can_be_configured() {
+        let hours: WorkingHours =
+            serde_json::from_value(json!({"start": "08:30", "days": [1, 2, 3, 4]})).unwrap();
+        assert!(hours.validate().is_ok());
+        assert!(hours.is_outside(
+            "2025-10-24T10:00:00Z".parse().unwrap(),
+            "2025-10-24T11:00:00Z".parse().unwrap(),
+            chrono_tz::UTC
+        ));
+    }
+
+    #[test]
+    fn events_on_several_calendars_are_counted_once() {
+        let shared: CalendarEvent = serde_json::from_value(json!({
+            "id": "sync",
+            "iCalUID": "sync@google.com",
+            "start": {"dateTime": "2025-10-20T09:00:00Z"},
+            "end": {"dateTime": "2025-10-20T10:00:00Z"},
+            "attendees": [
+                {"email": "me@example.com", "self": true, "responseStatus": "accepted"},
+                {"email": "ana@example.com"}
+            ]
+        }))
+        .unwrap();
+        let events = vec![
+            ("primary".to_owned(), shared.clone()),
+            ("team@group.calendar.google.com".to_owned(), shared),
+        ];
+
+        let report = report(&events, GroupBy::Calendar);
+        assert_eq!(report.total.event_count, 1);
+        assert_eq!(report.total.meeting_hours, 1.0);
+        let periods: Vec<&str> = report.periods.iter().map(|p| p.period.as_str()).collect();
+        assert_eq!(periods, ["primary"]);
+    }
+}
@@ -0,0 +1,392 @@
+use super::{CalendarEvent, EventPayload, GoogleCalendarClient, GoogleCalendarError};
+use crate::oauth::UserToken;
+use anyhow::{anyhow, Context, Result};
+use reqwest::header::CONTENT_TYPE;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use url::{Position, Url};
+
+/// Google Calendar accepts at most 50 calls per batch request.
+pub const MAX_BATCH_SIZE: usize = 50;
+
+/// One call inside a batch.
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(tag = "operation", rename_all = "snake_case")]
+pub enum BatchOperation {
+    Get {
+        event_id: String,
+        #[serde(default)]
+        calendar_id: Option<String>,
+    },
+    Create {
+        payload: EventPayload,
+    },
+    Update {
+        event_id: String,
+        /// Reject this item if the event changed since the `etag` was read.
+        #[serde(default)]
+        if_match_etag: Option<String>,
+        payload: EventPayload,
+    },
+}
+
+impl BatchOperation {
+    /// Whether repeating the call after an ambiguous failure cannot duplicate anything.
+    fn is_repeatable(&self) -> bool {
+        !matches!(self, Self::Create { .. })
+    }
+}
+
+/// Outcome of one batch item, in the order the operations were given.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct BatchItemResult {
+    pub index: usize,
+    /// HTTP status of the item; 0 when it was rejected before sending.
+    pub status_code: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event: Option<CalendarEvent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<BatchItemError>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct BatchItemError {
+    /// Same values as `data.error` on tool errors, e.g. `not_found`.
+    pub error: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    pub message: String,
+    pub retryable: bool,
+}
+
+impl From<&GoogleCalendarError> for BatchItemError {
+    fn from(err: &GoogleCalendarError) -> Self {
+        Self {
+            error: err.code().to_owned(),
+            reason: err.reason().map(str::to_owned),
+            message: err.message().to_owned(),
+            retryable: err.is_retryable(),
+        }
+    }
+}
+
+impl BatchItemResult {
+    fn failed(index: usize, status_code: u16, err: &GoogleCalendarError) -> Self {
+        Self {
+            index,
+            status_code,
+            event: None,
+            error: Some(err.into()),
+        }
+    }
+}
+
+/// An item serialized as an `application/http` part.
+struct BatchPart {
+    index: usize,
+    method: &'static str,
+    path: String,
+    if_match: Option<String>,
+    body: Option<serde_json::Value>,
+}
+
+impl GoogleCalendarClient {
+    /// Runs up to [`MAX_BATCH_SIZE`] operations in one `multipart/mixed` round trip.
+    ///
+    /// Items fail independently: invalid payloads are rejected locally and API errors are
+    /// reported per item, so the call only fails as a whole if the batch request itself does.
+    pub async fn batch(
+        &self,
+        token: &UserToken,
+        operations: &[BatchOperation],
+    ) -> Result<Vec<BatchItemResult>> {
+        if operations.len() > MAX_BATCH_SIZE {
+            return Err(GoogleCalendarError::invalid(format!(
+                "a batch may contain at most {MAX_BATCH_SIZE} operations"
+            ))
+            .into());
+        }
+
+        let mut results: Vec<Option<BatchItemResult>> = vec![None; operations.len()];
+        let mut parts = Vec::new();
+        for (index, operation) in operations.iter().enumerate() {
+            match self.batch_part(index, operation) {
+                Ok(part) => parts.push(part),
+                Err(err) => {
+                    let err = err
+                        .downcast::<GoogleCalendarError>()
+                        .unwrap_or_else(|err| GoogleCalendarError::invalid(err.to_string()));
+                    results[index] = Some(BatchItemResult::failed(index, 0, &err));
+                }
+            }
+        }
+
+        if !parts.is_empty() {
+            // Every item counts against the quota, not just the batch request.
+            for _ in 1..parts.len() {
+                self.throttle(token).await?;
+            }
+
+            let boundary = format!("batch_{}", uuid::Uuid::new_v4().simple());
+            let request = self
+                .http
+                .post(self.batch_url()?)
+                .bearer_auth(token.access_token())
+                .header(
+                    CONTENT_TYPE,
+                    format!("multipart/mixed; boundary={boundary}"),
+                )
+                .body(render_batch(&boundary, &parts));
+            let repeatable = operations.iter().all(BatchOperation::is_repeatable);
+            let response = self.send(token, request, repeatable).await?;
+
+            let content_type = response
+                .headers()
+                .get(CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+                .unwrap_or_default()
+                .to_owned();
+            let response_boundary = multipart_boundary(&content_type)
+                .ok_or_else(|| anyhow!("batch response is not multipart: {content_type}"))?;
+            let body = response.text().await?;
+
+            let mut responses = parse_batch(&response_boundary, &body);
+            for part in &parts {
+                let result = match responses.remove(&part.index) {
+                    Some((status_code, body)) => item_result(part.index, status_code, &body),
+                    None => BatchItemResult::failed(
+                        part.index,
+                        0,
+                        &GoogleCalendarError::Transient {
+                            message: "no response for this batch item".to_owned(),
+                            status_code: None,
+                        },
+                    ),
+                };
+                results[part.index] = Some(result);
+            }
+        }
+
+        Ok(results.into_iter().flatten().collect())
+    }
+
+    fn batch_part(&self, index: usize, operation: &BatchOperation) -> Result<BatchPart> {
+        let part = match operation {
+            BatchOperation::Get {
+                event_id,
+                calendar_id,
+            } => BatchPart {
+                index,
+                method: "GET",
+                path: self.api_path(
+                    self.resolve_calendar(calendar_id),
+                    &format!("events/{event_id}"),
+                )?,
+                if_match: None,
+                body: None,
+            },
+            BatchOperation::Create { payload } => BatchPart {
+                index,
+                method: "POST",
+                path: with_write_query(
+                    self.api_path(self.resolve_calendar(&payload.calendar_id), "events")?,
+                    payload,
+                ),
+                if_match: None,
+                body: Some(serde_json::Value::Object(Self::create_body(payload)?)),
+            },
+            BatchOperation::Update {
+                event_id,
+                if_match_etag,
+                payload,
+            } => BatchPart {
+                index,
+                method: "PATCH",
+                path: with_write_query(
+                    self.api_path(
+                        self.resolve_calendar(&payload.calendar_id),
+                        &format!("events/{event_id}"),
+                    )?,
+                    payload,
+                ),
+                if_match: if_match_etag.clone(),
+                body: Some(serde_json::Value::Object(Self::patch_body(payload)?)),
+            },
+        };
+        Ok(part)
+    }
+
+    /// Path and query of a calendar endpoint, as written in a batch part's request line.
+    fn api_path(&self, calendar_id: &str, path: &str) -> Result<String> {
+        let url = self.calendar_url(calendar_id, path)?;
+        Ok(url[Position::BeforePath..].to_owned())
+    }
+
+    /// `https://host/calendar/v3/` maps to `https://host/batch/calendar/v3`.
+    fn batch_url(&self) -> Result<Url> {
+        let api_path = self.api_base.path().trim_matches('/');
+        self.api_base
+            .join(&format!("/batch/{api_path}"))
+            .context("failed to compose batch endpoint")
+    }
+}
+
+fn with_write_query(path: String, payload: &EventPayload) -> String {
+    let query = super::write_query(payload)
+        .into_iter()
+        .map(|(name, value)| format!("{name}={value}"))
+        .collect::<Vec<_>>()
+        .join("&");
+    format!("{path}?{query}")
+}
+
+fn render_batch(boundary: &str, parts: &[BatchPart]) -> String {
+    let mut body = String::new();
+    for part in parts {
+        body.push_str(&format!("--{boundary}\r\n"));
+        body.push_str("Content-Type: application/http\r\n");
+        body.push_str(&format!("Content-ID: <item-{}>\r\n\r\n", part.index));
+        body.push_str(&format!("{} {} HTTP/1.1\r\n", part.method, part.path));
+        if let Some(etag) = &part.if_match {
+            body.push_str(&format!("If-Match: {etag}\r\n"));
+        }
+        match &part.body {
+            Some(json) => {
+                body.push_str("Content-Type: application/json; charset=UTF-8\r\n\r\n");
+                body.push_str(&json.to_string());
+                body.push_str("\r\n");
+            }
+            None => body.push_str("\r\n"),
+        }
+    }
+    body.push_str(&format!("--{boundary}--\r\n"));
+    body
+}
+
+fn multipart_boundary(content_type: &str) -> Option<String> {
+    content_type
+        .split(';')
+        .filter_map(|param| param.trim().strip_prefix("boundary="))
+        .map(|boundary| boundary.trim_matches('"').to_owned())
+        .next()
+}
+
+/// Splits a batch response into `(status, body)` per item index, keyed by `Content-ID`.
+fn parse_batch(boundary: &str, body: &str) -> HashMap<usize, (u16, String)> {
+    let delimiter = format!("--{boundary}");
+    body.split(delimiter.as_str())
+        .filter_map(|part| {
+            let (outer_headers, http) = split_headers(part)?;
+            let index = outer_headers
+                .lines()
+                .filter_map(|line| line.split_once(':'))
+                .find(|(name, _)| name.trim().eq_ignore_ascii_case("content-id"))
+                .and_then(|(_, value)| {
+                    value
+                        .trim()
+                        .trim_start_matches('<')
+                        .trim_end_matches('>')
+                        .rsplit('-')
+                        .next()?
+                        .parse::<usize>()
+                        .ok()
+                })?;
+
+            let (inner_headers, inner_body) = split_headers(http)?;
+            let status_code = inner_headers
+                .lines()
+                .next()?
+                .split_whitespace()
+                .nth(1)?
+                .parse::<u16>()
+                .ok()?;
+            Some((index, (status_code, inner_body.trim().to_owned())))
+        })
+        .collect()
+}
+
+fn split_headers(part: &str) -> Option<(&str, &str)> {
+    let part = part.trim_start_matches(['\r', '\n']);
+    part.split_once("\r\n\r\n")
+        .or_else(|| part.split_once("\n\n"))
+}
+
+fn item_result(index: usize, status_code: u16, body: &str) -> BatchItemResult {
+    if (200..300).contains(&status_code) {
+        match serde_json::from_str::<CalendarEvent>(body) {
+            Ok(event) => BatchItemResult {
+                index,
+                status_code,
+                event: Some(event),
+                error: None,
+            },
+            Err(err) => BatchItemResult::failed(
+                index,
+                status_code,
+                &GoogleCalendarError::Other {
+                    message: format!("unreadable batch item response: {err}"),
+                    reason: None,
+                    status_code,
+                },
+            ),
+        }
+    } else {
+        let err = GoogleCalendarClient::classify_error_body(status_code, None, body.as_bytes());
+        BatchItemResult::failed(index, status_code, &err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_out_of_order_batch_response() {
+        let body = "--batch_abc\r\n\
+            Content-Type: application/http\r\n\
+            Content-ID: <response-item-1>\r\n\r\n\
+            HTTP/1.1 404 Not Found\r\n\
+            Content-Type: application/json; charset=UTF-8\r\n\r\n\
+            {\"error\":{\"code\":404,\"message\":\"Not Found\",\"errors\":[{\"reason\":\"notFound\"}]}}\r\n\
+            --batch_abc\r\n\
+            Content-Type: application/http\r\n\
+            Content-ID: <response-item-0>\r\n\r\n\
+            HTTP/1.1 200 OK\r\n\
+            Content-Type: application/json; charset=UTF-8\r\n\r\n\
+            {\"id\":\"evt1\",\"summary\":\"Standup\"}\r\n\
+            --batch_abc--\r\n";
+
+        let boundary = multipart_boundary("multipart/mixed; boundary=batch_abc").unwrap();
+        let mut parsed = parse_batch(&boundary, body);
+        assert_eq!(parsed.len(), 2);
+
+        let (status, body) = parsed.remove(&0).unwrap();
+        let ok = item_result(0, status, &body);
+        assert_eq!(ok.event.unwrap().id.as_deref(), Some("evt1"));
+
+        let (status, body) = parsed.remove(&1).unwrap();
+        let missing = item_result(1, status, &body);
+        assert_eq!(missing.status_code, 404);
+        let json = serde_json::to_value(&missing).unwrap();
+        assert_eq!(json["error"]["reason"], "notFound");
+        let error = missing.error.unwrap();
+        assert_eq!(error.error, "not_found");
+        assert_eq!(error.reason.as_deref(), Some("notFound"));
+        assert_eq!(error.message, "Not Found");
+        assert!(!error.retryable);
+    }
+
+    #[test]
+    fn batch_url_sits_beside_api_base() {
+        let client = GoogleCalendarClient::new("https://www.googleapis.com/calendar/v3".into());
+        assert_eq!(
+            client.batch_url().unwrap().as_str(),
+            "https://www.googleapis.com/batch/calendar/v3"
+        );
+        assert_eq!(
+            client.api_path("primary", "events/abc").unwrap(),
+            "/calendar/v3/calendars/primary/events/abc"
+        );
+    }
+}
@@ -0,0 +1,203 @@
+use super::{CalendarListEntry, GoogleCalendarClient, GoogleCalendarError};
+use crate::oauth::UserToken;
+use anyhow::{Context, Result};
+use reqwest::Url;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// OAuth scope for creating, changing and deleting calendars.
+pub const CALENDARS_SCOPE: &str = "https://www.googleapis.com/auth/calendar.calendars";
+/// OAuth scope for adding calendars to and removing them from the user's calendar list.
+pub const CALENDAR_LIST_SCOPE: &str = "https://www.googleapis.com/auth/calendar.calendarlist";
+/// OAuth scope for reading the user's calendar list, e.g. their access role on a calendar.
+pub const CALENDAR_LIST_READONLY_SCOPE: &str =
+    "https://www.googleapis.com/auth/calendar.calendarlist.readonly";
+
+/// Metadata of a calendar (`calendars` resource). Unset fields are left unchanged on updates.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Calendar {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    /// IANA zone, e.g. "Europe/Berlin".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_zone: Option<String>,
+}
+
+impl GoogleCalendarClient {
+    fn calendars_url(&self, calendar_id: Option<&str>) -> Result<Url> {
+        let path = match calendar_id {
+            Some(id) => format!("calendars/{}", urlencoding::encode(id)),
+            None => "calendars".to_owned(),
+        };
+        self.api_base
+            .join(&path)
+            .context("failed to compose calendars endpoint")
+    }
+
+    fn calendar_list_url(&self, calendar_id: Option<&str>) -> Result<Url> {
+        let path = match calendar_id {
+            Some(id) => format!("users/me/calendarList/{}", urlencoding::encode(id)),
+            None => "users/me/calendarList".to_owned(),
+        };
+        self.api_base
+            .join(&path)
+            .context("failed to compose calendarList endpoint")
+    }
+
+    /// Creates a secondary calendar owned by the user.
+    pub async fn create_calendar(
+        &self,
+        token: &UserToken,
+        calendar: &Calendar,
+    ) -> Result<Calendar> {
+        if calendar
+            .summary
+            .as_deref()
+            .is_none_or(|summary| summary.trim().is_empty())
+        {
+            return Err(
+                GoogleCalendarError::invalid("summary is required to create a calendar").into(),
+            );
+        }
+        let request = self
+            .http
+            .post(self.calendars_url(None)?)
+            .bearer_auth(token.access_token())
+            .json(calendar);
+        // Without a client-chosen id a retried insert could create a second calendar.
+        let response = self.send(token, request, false).await?;
+
+        Ok(response.json::<Calendar>().await?)
+    }
+
+    pub async fn patch_calendar(
+        &self,
+        token: &UserToken,
+        calendar_id: &str,
+        patch: &Calendar,
+    ) -> Result<Calendar> {
+        let request = self
+            .http
+            .patch(self.calendars_url(Some(calendar_id))?)
+            .bearer_auth(token.access_token())
+            .json(patch);
+        let response = self.send(token, request, true).await?;
+
+        Ok(response.json::<Calendar>().await?)
+    }
+
+    /// Deletes a secondary calendar with all its events.
+    pub async fn delete_calendar(&self, token: &UserToken, calendar_id: &str) -> Result<()> {
+        self.ensure_secondary(token, calendar_id).await?;
+        let request = self
+            .http
+            .delete(self.calendars_url(Some(calendar_id))?)
+            .bearer_auth(token.access_token());
+        self.send(token, request, true).await?;
+        Ok(())
+    }
+
+    /// Adds an existing calendar, e.g. one shared with the user, to their calendar list.
+    pub async fn subscribe_calendar(
+        &self,
+        token: &UserToken,
+        calendar_id: &str,
+    ) -> Result<CalendarListEntry> {
+        let request = self
+            .http
+            .post(self.calendar_list_url(None)?)
+            .bearer_auth(token.access_token())
+            .json(&serde_json::json!({ "id": calendar_id }));
+        let response = self.send(token, request, true).await?;
+
+        Ok(response.json::<CalendarListEntry>().await?)
+    }
+
+    /// Removes a calendar from the user's calendar list; the calendar itself is kept.
+    pub async fn unsubscribe_calendar(&self, token: &UserToken, calendar_id: &str) -> Result<()> {
+        self.ensure_secondary(token, calendar_id).await?;
+        let request = self
+            .http
+            .delete(self.calendar_list_url(Some(calendar_id))?)
+            .bearer_auth(token.access_token());
+        self.send(token, request, true).await?;
+        Ok(())
+    }
+
+    /// Rejects the primary calendar, whether named `"primary"` or by its id (the user's email).
+    async fn ensure_secondary(&self, token: &UserToken, calendar_id: &str) -> Result<()> {
+        check_secondary(calendar_id, None)?;
+        let entry = match self.calendar_list_entry(token, calendar_id).await {
+            Ok(entry) => Some(entry),
+            // The primary calendar is always on the list, so an unlisted one is secondary.
+            Err(err)
+                if matches!(
+                    err.downcast_ref(),
+                    Some(GoogleCalendarError::NotFound { .. })
+                ) =>
+            {
+                None
+            }
+            Err(err) => return Err(err),
+        };
+        Ok(check_secondary(calendar_id, entry.as_ref())?)
+    }
+}
+
+fn check_secondary(
+    calendar_id: &str,
+    entry: Option<&CalendarListEntry>,
+) -> Result<(), GoogleCalendarError> {
+    if calendar_id.trim().is_empty()
+        || calendar_id == "primary"
+        || entry.is_some_and(|entry| entry.primary)
+    {
+        return Err(GoogleCalendarError::invalid(
+            "the primary calendar cannot be deleted or unsubscribed; pass a secondary calendar id",
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn calendar_patch_sends_only_set_fields() {
+        let patch = Calendar {
+            summary: Some("Project Atlas".into()),
+            time_zone: Some("Europe/Berlin".into()),
+            ..Default::default()
+        };
+        assert_eq!(
+            serde_json::to_value(&patch).unwrap(),
+            serde_json::json!({"summary": "Project Atlas", "timeZone": "Europe/Berlin"})
+        );
+    }
+
+    #[test]
+    fn primary_calendar_is_rejected_by_name_or_id() {
+        let entry = |id: &str, primary: bool| CalendarListEntry {
+            id: id.into(),
+            summary: None,
+            access_role: Some("owner".into()),
+            primary,
+            selected: true,
+            time_zone: None,
+        };
+
+        assert!(check_secondary("primary", None).is_err());
+        assert!(check_secondary("me@example.com", Some(&entry("me@example.com", true))).is_err());
+        let atlas = "atlas@group.calendar.google.com";
+        assert!(check_secondary(atlas, None).is_ok());
+        assert!(check_secondary(atlas, Some(&entry(atlas, false))).is_ok());
+    }
+}
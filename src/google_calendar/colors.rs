@@ -0,0 +1,40 @@
+use super::GoogleCalendarClient;
+use crate::oauth::UserToken;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// The fixed palettes behind `colorId` values (`colors` endpoint).
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Colors {
+    #[serde(default)]
+    pub updated: Option<DateTime<Utc>>,
+    /// Colors for calendars, keyed by `colorId`.
+    #[serde(default)]
+    pub calendar: BTreeMap<String, ColorDefinition>,
+    /// Colors for events, keyed by `colorId`.
+    #[serde(default)]
+    pub event: BTreeMap<String, ColorDefinition>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ColorDefinition {
+    /// Hex color such as "#a4bdfc".
+    pub background: String,
+    pub foreground: String,
+}
+
+impl GoogleCalendarClient {
+    pub async fn colors(&self, token: &UserToken) -> Result<Colors> {
+        let url = self
+            .api_base
+            .join("colors")
+            .context("failed to compose colors endpoint")?;
+        let request = self.http.get(url).bearer_auth(token.access_token());
+        let response = self.send(token, request, true).await?;
+
+        Ok(response.json::<Colors>().await?)
+    }
+}
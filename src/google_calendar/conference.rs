@@ -0,0 +1,184 @@
+use super::{CalendarEvent, GetEventParams, GoogleCalendarClient};
+use crate::oauth::UserToken;
+use anyhow::Result;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Polls of a pending conference before the event is returned as is.
+const CONFERENCE_POLL_ATTEMPTS: u32 = 8;
+const CONFERENCE_POLL_MAX_DELAY: Duration = Duration::from_secs(2);
+
+/// Conference attached to an event (`conferenceData`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ConferenceData {
+    /// Asks Google to create a new conference; only honoured with `conferenceDataVersion=1`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub create_request: Option<CreateConferenceRequest>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub entry_points: Vec<EntryPoint>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conference_solution: Option<ConferenceSolution>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conference_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateConferenceRequest {
+    /// Client-chosen id; reusing it returns the conference created for the first request.
+    pub request_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conference_solution_key: Option<ConferenceSolutionKey>,
+    /// Set by Google: `pending`, `success` or `failure`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<ConferenceRequestStatus>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ConferenceRequestStatus {
+    pub status_code: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ConferenceSolutionKey {
+    /// `hangoutsMeet` for Google Meet.
+    #[serde(rename = "type")]
+    pub kind: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ConferenceSolution {
+    pub key: ConferenceSolutionKey,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon_uri: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct EntryPoint {
+    /// `video`, `phone`, `sip` or `more`.
+    pub entry_point_type: String,
+    pub uri: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pin: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub passcode: Option<String>,
+}
+
+impl ConferenceData {
+    /// A request for a new Google Meet conference with a fresh request id.
+    pub fn meet_request() -> Self {
+        Self {
+            create_request: Some(CreateConferenceRequest {
+                request_id: uuid::Uuid::new_v4().simple().to_string(),
+                conference_solution_key: Some(ConferenceSolutionKey {
+                    kind: "hangoutsMeet".to_owned(),
+                }),
+                status: None,
+            }),
+            ..Default::default()
+        }
+    }
+
+    /// Status of the create request, if one was made.
+    pub fn request_status(&self) -> Option<&str> {
+        self.create_request
+            .as_ref()?
+            .status
+            .as_ref()
+            .map(|status| status.status_code.as_str())
+    }
+
+    /// URI of the video entry point, i.e. the link to join the call.
+    pub fn join_url(&self) -> Option<&str> {
+        self.entry_points
+            .iter()
+            .find(|entry| entry.entry_point_type == "video")
+            .map(|entry| entry.uri.as_str())
+    }
+}
+
+impl GoogleCalendarClient {
+    /// Re-reads `event` until a pending conference request has succeeded or failed.
+    ///
+    /// Conference creation is asynchronous; if it is still pending after the last poll the
+    /// latest event is returned and the status can be seen in its `conferenceData`.
+    pub(super) async fn await_conference(
+        &self,
+        token: &UserToken,
+        calendar_id: &str,
+        mut event: CalendarEvent,
+    ) -> Result<CalendarEvent> {
+        let Some(event_id) = event.id.clone() else {
+            return Ok(event);
+        };
+        let params = GetEventParams {
+            event_id,
+            calendar_id: Some(calendar_id.to_owned()),
+        };
+
+        for attempt in 1..=CONFERENCE_POLL_ATTEMPTS {
+            let pending = event
+                .conference_data
+                .as_ref()
+                .and_then(ConferenceData::request_status)
+                == Some("pending");
+            if !pending {
+                break;
+            }
+            let delay = (Duration::from_millis(250) * attempt).min(CONFERENCE_POLL_MAX_DELAY);
+            tokio::time::sleep(delay).await;
+            event = self.get_event(token, &params).await?;
+        }
+        Ok(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn join_url_comes_from_video_entry_point() {
+        let data: ConferenceData = serde_json::from_str(
+            r#"{
+                "createRequest": {"requestId": "abc", "status": {"statusCode": "success"}},
+                "entryPoints": [
+                    {"entryPointType": "phone", "uri": "tel:+1-555-0100"},
+                    {"entryPointType": "video", "uri": "https://meet.google.com/abc-defg-hij"}
+                ],
+                "conferenceSolution": {"key": {"type": "hangoutsMeet"}, "name": "Google Meet"}
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(data.request_status(), Some("success"));
+        assert_eq!(
+            data.join_url(),
+            Some("https://meet.google.com/abc-defg-hij")
+        );
+
+        let request = serde_json::to_value(ConferenceData::meet_request()).unwrap();
+        assert_eq!(
+            request["createRequest"]["conferenceSolutionKey"]["type"],
+            "hangoutsMeet"
+        );
+        assert_eq!(
+            request["createRequest"]["requestId"]
+                .as_str()
+                .unwrap()
+                .len(),
+            32
+        );
+    }
+}
@@ -0,0 +1,295 @@
+use super::analytics::blocks_time;
+use super::{
+    CalendarEvent, CalendarListEntry, EventPayload, GoogleCalendarClient, ListEventsParams,
+    Transparency,
+};
+use crate::oauth::UserToken;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashSet;
+
+/// Upper bound for events read per calendar when looking for conflicts.
+const MAX_EVENTS_PER_CALENDAR: usize = 250;
+
+/// A busy event overlapping a proposed time range.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct Conflict {
+    /// Calendar the overlapping event was found on.
+    pub calendar_id: String,
+    #[serde(flatten)]
+    pub event: CalendarEvent,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CalendarListPage {
+    #[serde(default)]
+    items: Vec<CalendarListEntry>,
+    #[serde(default)]
+    next_page_token: Option<String>,
+}
+
+/// The time `event` would block, or `None` when it cannot conflict: free (transparent),
+/// cancelled or all-day.
+pub fn busy_range(event: &CalendarEvent) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    if event.transparency == Some(Transparency::Transparent)
+        || event.status.as_deref() == Some("cancelled")
+    {
+        return None;
+    }
+    Some((
+        event.start.as_ref()?.date_time?,
+        event.end.as_ref()?.date_time?,
+    ))
+}
+
+/// The time a create or update would block: `payload` laid over the `current` event, with
+/// the same exceptions as [`busy_range`].
+pub fn proposed_range(
+    payload: &EventPayload,
+    current: Option<&CalendarEvent>,
+) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    let transparency = payload
+        .transparency
+        .or_else(|| current.and_then(|event| event.transparency));
+    if transparency == Some(Transparency::Transparent)
+        || current.is_some_and(|event| event.status.as_deref() == Some("cancelled"))
+    {
+        return None;
+    }
+    let start = payload
+        .start
+        .as_ref()
+        .or_else(|| current.and_then(|event| event.start.as_ref()))?;
+    let end = payload
+        .end
+        .as_ref()
+        .or_else(|| current.and_then(|event| event.end.as_ref()))?;
+    Some((start.date_time?, end.date_time?))
+}
+
+fn ical_uid(event: &CalendarEvent) -> Option<&str> {
+    event.extra.get("iCalUID").and_then(Value::as_str)
+}
+
+/// Whether `other` is `event` itself: the same event, one of its instances, or its copy on an
+/// attendee's calendar.
+fn is_same_event(event: Option<&CalendarEvent>, other: &CalendarEvent) -> bool {
+    let Some(event) = event else {
+        return false;
+    };
+    let same_id = event.id.is_some()
+        && (other.id == event.id
+            || other.extra.get("recurringEventId").and_then(Value::as_str) == event.id.as_deref());
+    same_id || ical_uid(event).is_some_and(|uid| ical_uid(other) == Some(uid))
+}
+
+/// Keeps the events of `calendar_id` that block time, leaving out `event` itself and events
+/// already reported for another calendar.
+fn collect_conflicts(
+    calendar_id: &str,
+    items: Vec<CalendarEvent>,
+    event: Option<&CalendarEvent>,
+    seen: &mut HashSet<(String, Option<DateTime<Utc>>)>,
+    conflicts: &mut Vec<Conflict>,
+) {
+    for other in items {
+        if !blocks_time(&other) || is_same_event(event, &other) {
+            continue;
+        }
+        // A shared meeting shows up on every attendee calendar with the same iCalUID.
+        let key = ical_uid(&other)
+            .map(str::to_owned)
+            .or_else(|| other.id.clone())
+            .map(|key| (key, other.start.as_ref().and_then(|start| start.date_time)));
+        if key.is_some_and(|key| !seen.insert(key)) {
+            continue;
+        }
+        conflicts.push(Conflict {
+            calendar_id: calendar_id.to_owned(),
+            event: other,
+        });
+    }
+}
+
+impl GoogleCalendarClient {
+    /// Ids of the calendars selected in the user's calendar list, with `"primary"` first.
+    pub async fn selected_calendars(&self, token: &UserToken) -> Result<Vec<String>> {
+        let url = self
+            .api_base
+            .join("users/me/calendarList")
+            .context("failed to compose calendarList endpoint")?;
+        let mut selected = Vec::new();
+        let mut page_token: Option<String> = None;
+        loop {
+            let mut request = self
+                .http
+                .get(url.clone())
+                .bearer_auth(token.access_token())
+                .query(&[("minAccessRole", "freeBusyReader")]);
+            if let Some(page_token) = &page_token {
+                request = request.query(&[("pageToken", page_token)]);
+            }
+            let page: CalendarListPage = self.send(token, request, true).await?.json().await?;
+            for entry in page.items {
+                if entry.primary {
+                    selected.insert(0, "primary".to_owned());
+                } else if entry.selected {
+                    selected.push(entry.id);
+                }
+            }
+            match page.next_page_token {
+                Some(next) => page_token = Some(next),
+                None => return Ok(selected),
+            }
+        }
+    }
+
+    /// Lists busy events on `calendar_ids` that overlap the time `event` would block.
+    ///
+    /// Free, cancelled and declined events never conflict, and neither does the event itself
+    /// when it already exists.
+    pub async fn find_conflicts(
+        &self,
+        token: &UserToken,
+        calendar_ids: &[String],
+        event: &CalendarEvent,
+    ) -> Result<Vec<Conflict>> {
+        let Some((start, end)) = busy_range(event) else {
+            return Ok(Vec::new());
+        };
+        self.conflicts_in_range(token, calendar_ids, start, end, Some(event))
+            .await
+    }
+
+    /// Lists busy events on `calendar_ids` overlapping `start..end`, leaving out `exclude`.
+    pub async fn conflicts_in_range(
+        &self,
+        token: &UserToken,
+        calendar_ids: &[String],
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        exclude: Option<&CalendarEvent>,
+    ) -> Result<Vec<Conflict>> {
+        let mut seen = HashSet::new();
+        let mut conflicts = Vec::new();
+        for calendar_id in calendar_ids {
+            let params = ListEventsParams {
+                calendar_id: Some(calendar_id.clone()),
+                time_min: Some(start.into()),
+                time_max: Some(end.into()),
+                single_events: true,
+                order_by_start_time: true,
+                ..Default::default()
+            };
+            let page = self
+                .list_all_events(token, &params, MAX_EVENTS_PER_CALENDAR)
+                .await?;
+            collect_conflicts(calendar_id, page.items, exclude, &mut seen, &mut conflicts);
+        }
+        Ok(conflicts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn proposed() -> CalendarEvent {
+        serde_json::from_value(json!({
+            "id": "evt",
+            "start": {"dateTime": "2025-10-20T09:00:00Z"},
+            "end": {"dateTime": "2025-10-20T10:00:00Z"}
+        }))
+        .unwrap()
+    }
+
+    /// Calendar and event ids of the conflicts with `proposed()` found on `calendars`.
+    fn conflict_ids(calendars: Vec<(&str, Value)>) -> Vec<(String, String)> {
+        let proposed = proposed();
+        let mut seen = HashSet::new();
+        let mut conflicts = Vec::new();
+        for (calendar_id, items) in calendars {
+            let items = serde_json::from_value(items).unwrap();
+            collect_conflicts(
+                calendar_id,
+                items,
+                Some(&proposed),
+                &mut seen,
+                &mut conflicts,
+            );
+        }
+        conflicts
+            .into_iter()
+            .map(|conflict| (conflict.calendar_id, conflict.event.id.unwrap_or_default()))
+            .collect()
+    }
+
+    #[test]
+    fn free_events_block_no_time() {
+        assert!(busy_range(&proposed()).is_some());
+        let free: CalendarEvent = serde_json::from_value(json!({
+            "transparency": "transparent",
+            "start": {"dateTime": "2025-10-20T09:00:00Z"},
+            "end": {"dateTime": "2025-10-20T10:00:00Z"}
+        }))
+        .unwrap();
+        assert!(busy_range(&free).is_none());
+    }
+
+    #[test]
+    fn free_cancelled_and_declined_events_never_conflict() {
+        let primary = json!([
+            {"id": "lunch", "transparency": "transparent"},
+            {"id": "cancelled", "status": "cancelled"},
+            {
+                "id": "declined",
+                "attendees": [{"email": "me@example.com", "self": true, "responseStatus": "declined"}]
+            }
+        ]);
+        assert!(conflict_ids(vec![("primary", primary)]).is_empty());
+    }
+
+    #[test]
+    fn the_proposed_event_does_not_conflict_with_itself() {
+        let primary = json!([{"id": "evt", "start": {"dateTime": "2025-10-20T09:00:00Z"}}]);
+        assert!(conflict_ids(vec![("primary", primary)]).is_empty());
+    }
+
+    #[test]
+    fn shared_meetings_are_reported_once() {
+        let standup = json!([{
+            "id": "standup",
+            "iCalUID": "standup@google.com",
+            "start": {"dateTime": "2025-10-20T09:30:00Z"},
+            "end": {"dateTime": "2025-10-20T09:45:00Z"}
+        }]);
+        let conflicts = conflict_ids(vec![("primary", standup.clone()), ("team", standup)]);
+        assert_eq!(conflicts, [("primary".to_owned(), "standup".to_owned())]);
+    }
+
+    #[test]
+    fn proposed_range_lays_the_payload_over_the_current_event() {
+        let payload: EventPayload = serde_json::from_value(json!({
+            "end": {"dateTime": "2025-10-20T11:00:00Z"}
+        }))
+        .unwrap();
+        let (start, end) = proposed_range(&payload, Some(&proposed())).unwrap();
+        assert_eq!(start.to_rfc3339(), "2025-10-20T09:00:00+00:00");
+        assert_eq!(end.to_rfc3339(), "2025-10-20T11:00:00+00:00");
+    }
+
+    #[test]
+    fn free_or_untimed_proposals_have_no_range() {
+        let free: EventPayload =
+            serde_json::from_value(json!({"transparency": "transparent"})).unwrap();
+        assert!(proposed_range(&free, Some(&proposed())).is_none());
+        let end_only: EventPayload =
+            serde_json::from_value(json!({"end": {"dateTime": "2025-10-20T11:00:00Z"}})).unwrap();
+        assert!(proposed_range(&end_only, None).is_none());
+    }
+}
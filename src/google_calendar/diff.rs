@@ -0,0 +1,133 @@
+use super::conference::ConferenceData;
+use super::{CalendarEvent, EventPayload};
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+/// A single field that a write would change, using Google's wire representation.
+#[derive(Debug, Clone, PartialEq, Serialize, JsonSchema)]
+pub struct FieldChange {
+    pub field: String,
+    /// Current value, absent when the field is unset or the event does not exist yet.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub before: Option<Value>,
+    pub after: Value,
+}
+
+/// Compares the fields set in `payload` against `current` and returns those that differ.
+pub fn diff_event(current: Option<&CalendarEvent>, payload: &EventPayload) -> Vec<FieldChange> {
+    let before = current
+        .and_then(|event| serde_json::to_value(event).ok())
+        .and_then(|value| value.as_object().cloned())
+        .unwrap_or_default();
+
+    payload_fields(payload)
+        .into_iter()
+        .filter_map(|(field, after)| {
+            let before = before.get(&field).filter(|value| !value.is_null()).cloned();
+            if before.as_ref() == Some(&after) {
+                None
+            } else {
+                Some(FieldChange {
+                    field,
+                    before,
+                    after,
+                })
+            }
+        })
+        .collect()
+}
+
+/// The JSON body fields carried by `payload` under Google's names, without the routing-only
+/// `calendar_id`.
+pub fn payload_fields(payload: &EventPayload) -> Map<String, Value> {
+    let mut fields: Map<String, Value> = serde_json::to_value(payload)
+        .ok()
+        .and_then(|value| value.as_object().cloned())
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|(field, _)| field != "calendar_id")
+        .map(|(field, value)| (camel_case(&field), value))
+        .collect();
+    if payload.add_meet_link && !fields.contains_key("conferenceData") {
+        let request = serde_json::to_value(ConferenceData::meet_request()).unwrap_or_default();
+        fields.insert("conferenceData".to_owned(), request);
+    }
+    fields
+}
+
+/// `guests_can_modify` -> `guestsCanModify`; names without underscores are kept as they are.
+fn camel_case(field: &str) -> String {
+    let mut parts = field.split('_');
+    let mut name = parts.next().unwrap_or_default().to_owned();
+    for part in parts {
+        let mut chars = part.chars();
+        if let Some(first) = chars.next() {
+            name.extend(first.to_uppercase());
+            name.push_str(chars.as_str());
+        }
+    }
+    name
+}
+
+/// Renders changes as one human-readable line per field.
+pub fn render_changes(changes: &[FieldChange]) -> String {
+    if changes.is_empty() {
+        return "(no field changes)".to_owned();
+    }
+
+    changes
+        .iter()
+        .map(|change| match &change.before {
+            Some(before) => format!(
+                "- {}: {} -> {}",
+                change.field,
+                display_value(before),
+                display_value(&change.after)
+            ),
+            None => format!("- {}: {}", change.field, display_value(&change.after)),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn display_value(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Object(object) => match object.get("dateTime").or_else(|| object.get("date")) {
+            Some(Value::String(when)) => when.clone(),
+            _ => value.to_string(),
+        },
+        Value::Array(items) if items.iter().all(|item| item.get("email").is_some()) => items
+            .iter()
+            .filter_map(|item| item.get("email").and_then(Value::as_str))
+            .collect::<Vec<_>>()
+            .join(", "),
+        _ => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_skips_unchanged_fields() {
+        let current: CalendarEvent = serde_json::from_value(serde_json::json!({
+            "id": "evt",
+            "summary": "Standup",
+            "location": "Room 1"
+        }))
+        .unwrap();
+        let payload = EventPayload {
+            summary: Some("Standup".into()),
+            location: Some("Room 2".into()),
+            ..Default::default()
+        };
+
+        let changes = diff_event(Some(&current), &payload);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].field, "location");
+        assert_eq!(render_changes(&changes), "- location: Room 1 -> Room 2");
+    }
+}
@@ -0,0 +1,263 @@
+use super::{EventPayload, EventType, GoogleCalendarError, Transparency};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Suffix of secondary calendar ids; special event types only exist on primary calendars.
+const SECONDARY_CALENDAR_SUFFIX: &str = "@group.calendar.google.com";
+
+/// Which invitations an out-of-office or focus time event declines automatically.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum AutoDeclineMode {
+    DeclineNone,
+    DeclineAllConflictingInvitations,
+    DeclineOnlyNewConflictingInvitations,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum ChatStatus {
+    Available,
+    DoNotDisturb,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct OutOfOfficeProperties {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_decline_mode: Option<AutoDeclineMode>,
+    /// Sent to organizers of declined invitations.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decline_message: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FocusTimeProperties {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_decline_mode: Option<AutoDeclineMode>,
+    /// Sent to organizers of declined invitations.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decline_message: Option<String>,
+    /// Google Chat status during the event.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chat_status: Option<ChatStatus>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum WorkingLocationType {
+    HomeOffice,
+    OfficeLocation,
+    CustomLocation,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkingLocationProperties {
+    #[serde(rename = "type")]
+    pub kind: WorkingLocationType,
+    /// Present (as `{}`) when working from home.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub home_office: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub custom_location: Option<CustomLocation>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub office_location: Option<OfficeLocation>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CustomLocation {
+    pub label: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct OfficeLocation {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub building_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub floor_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub floor_section_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub desk_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+impl WorkingLocationProperties {
+    pub fn home() -> Self {
+        Self {
+            kind: WorkingLocationType::HomeOffice,
+            home_office: Some(serde_json::json!({})),
+            custom_location: None,
+            office_location: None,
+        }
+    }
+
+    pub fn office(location: OfficeLocation) -> Self {
+        Self {
+            kind: WorkingLocationType::OfficeLocation,
+            home_office: None,
+            custom_location: None,
+            office_location: Some(location),
+        }
+    }
+
+    pub fn custom(label: String) -> Self {
+        Self {
+            kind: WorkingLocationType::CustomLocation,
+            home_office: None,
+            custom_location: Some(CustomLocation { label }),
+            office_location: None,
+        }
+    }
+}
+
+/// Checks the constraints Google places on `outOfOffice`, `focusTime` and `workingLocation`
+/// events, so that they fail with a clear message instead of a generic 400.
+///
+/// `creating` enables the checks that only apply to new events, where `event_type` is known.
+pub fn validate(payload: &EventPayload, creating: bool) -> Result<(), GoogleCalendarError> {
+    let typed = [
+        (
+            payload.out_of_office_properties.is_some(),
+            EventType::OutOfOffice,
+            "out_of_office_properties",
+        ),
+        (
+            payload.focus_time_properties.is_some(),
+            EventType::FocusTime,
+            "focus_time_properties",
+        ),
+        (
+            payload.working_location_properties.is_some(),
+            EventType::WorkingLocation,
+            "working_location_properties",
+        ),
+    ];
+    if creating {
+        for (present, kind, field) in typed {
+            if present && payload.event_type != Some(kind) {
+                return Err(GoogleCalendarError::invalid(format!(
+                    "{field} requires event_type \"{}\"",
+                    kind.as_str()
+                )));
+            }
+        }
+    }
+
+    let kind = match payload.event_type {
+        Some(
+            kind @ (EventType::OutOfOffice | EventType::FocusTime | EventType::WorkingLocation),
+        ) => kind,
+        _ => return validate_properties(payload),
+    };
+    if payload
+        .calendar_id
+        .as_deref()
+        .is_some_and(|id| id.ends_with(SECONDARY_CALENDAR_SUFFIX))
+    {
+        return Err(GoogleCalendarError::invalid(format!(
+            "{} events can only be created on the primary calendar",
+            kind.as_str()
+        )));
+    }
+    if payload
+        .attendees
+        .as_ref()
+        .is_some_and(|attendees| !attendees.is_empty())
+    {
+        return Err(GoogleCalendarError::invalid(format!(
+            "{} events cannot have attendees",
+            kind.as_str()
+        )));
+    }
+    if kind == EventType::WorkingLocation {
+        if payload.working_location_properties.is_none() {
+            return Err(GoogleCalendarError::invalid(
+                "workingLocation events require working_location_properties",
+            ));
+        }
+        if payload.transparency == Some(Transparency::Opaque) {
+            return Err(GoogleCalendarError::invalid(
+                "workingLocation events must be transparent",
+            ));
+        }
+    }
+    validate_properties(payload)
+}
+
+fn validate_properties(payload: &EventPayload) -> Result<(), GoogleCalendarError> {
+    let decline_settings = [
+        payload
+            .out_of_office_properties
+            .as_ref()
+            .map(|props| (props.auto_decline_mode, &props.decline_message)),
+        payload
+            .focus_time_properties
+            .as_ref()
+            .map(|props| (props.auto_decline_mode, &props.decline_message)),
+    ];
+    for (mode, message) in decline_settings.into_iter().flatten() {
+        if message.is_some() && matches!(mode, None | Some(AutoDeclineMode::DeclineNone)) {
+            return Err(GoogleCalendarError::invalid(
+                "decline_message requires an auto_decline_mode that declines invitations",
+            ));
+        }
+    }
+
+    if let Some(location) = &payload.working_location_properties {
+        let matches = match location.kind {
+            WorkingLocationType::HomeOffice => location.home_office.is_some(),
+            WorkingLocationType::OfficeLocation => location.office_location.is_some(),
+            WorkingLocationType::CustomLocation => location
+                .custom_location
+                .as_ref()
+                .is_some_and(|custom| !custom.label.trim().is_empty()),
+        };
+        if !matches {
+            return Err(GoogleCalendarError::invalid(
+                "working location details must match its type (homeOffice, officeLocation or customLocation with a label)",
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn special_event_constraints_are_checked_locally() {
+        let mut payload: EventPayload = serde_json::from_value(serde_json::json!({
+            "summary": "Vacation",
+            "event_type": "outOfOffice",
+            "out_of_office_properties": {"declineMessage": "Back on Monday"}
+        }))
+        .unwrap();
+        assert!(validate(&payload, true).is_err());
+
+        payload.out_of_office_properties = Some(OutOfOfficeProperties {
+            auto_decline_mode: Some(AutoDeclineMode::DeclineAllConflictingInvitations),
+            decline_message: Some("Back on Monday".into()),
+        });
+        assert!(validate(&payload, true).is_ok());
+
+        payload.calendar_id = Some("team@group.calendar.google.com".into());
+        assert!(validate(&payload, true).is_err());
+
+        let mismatched = EventPayload {
+            focus_time_properties: Some(FocusTimeProperties::default()),
+            ..Default::default()
+        };
+        assert!(validate(&mismatched, true).is_err());
+        assert!(validate(&mismatched, false).is_ok());
+
+        let body = serde_json::to_value(WorkingLocationProperties::custom("Cafe".into())).unwrap();
+        assert_eq!(body["type"], "customLocation");
+        assert_eq!(body["customLocation"]["label"], "Cafe");
+    }
+}
@@ -0,0 +1,806 @@
+use super::{
+    diff, CalendarEvent, EventAttendee, EventDateTime, EventPayload, EventSource, EventVisibility,
+    GoogleCalendarClient, Transparency,
+};
+use crate::oauth::UserToken;
+use anyhow::Result;
+use chrono::{Duration, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde_json::{json, Value};
+
+const PRODID: &str = "-//mcp-google-calendar//EN";
+/// Longest content line in octets before it is folded (RFC 5545 section 3.1).
+const MAX_LINE_OCTETS: usize = 75;
+const UTC_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+const LOCAL_FORMAT: &str = "%Y%m%dT%H%M%S";
+const DATE_FORMAT: &str = "%Y%m%d";
+
+/// Event fields that `events/import` accepts but a patch of an existing event must not carry.
+const IMPORT_ONLY_FIELDS: &[&str] = &["iCalUID", "sequence", "organizer"];
+
+#[derive(Debug, thiserror::Error)]
+#[error("line {line}: {message}")]
+pub struct IcsError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl IcsError {
+    fn new(line: usize, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+/// A `VEVENT` as an event body for `events/import`.
+#[derive(Debug, Clone)]
+pub struct IcsEvent {
+    pub ical_uid: String,
+    pub payload: EventPayload,
+}
+
+impl IcsEvent {
+    /// The payload without the fields that only `events/import` may set, for updating the copy
+    /// imported earlier.
+    pub fn into_patch(self) -> EventPayload {
+        let mut payload = self.payload;
+        payload
+            .extra
+            .retain(|field, _| !IMPORT_ONLY_FIELDS.contains(&field.as_str()));
+        payload
+    }
+}
+
+/// Events read from a `VCALENDAR`, and the `VEVENT`s that could not be used.
+#[derive(Debug, Default)]
+pub struct ParsedCalendar {
+    pub events: Vec<IcsEvent>,
+    pub skipped: Vec<IcsError>,
+}
+
+/// Renders `events` as a `VCALENDAR`.
+///
+/// Times are written in UTC, except for recurring events with a known zone, whose `DTSTART`
+/// keeps its IANA `TZID` so that the rule follows daylight saving time.
+pub fn write_calendar(name: Option<&str>, events: &[CalendarEvent]) -> String {
+    let mut out = IcsWriter::default();
+    out.line("BEGIN:VCALENDAR");
+    out.line("VERSION:2.0");
+    out.line(&format!("PRODID:{PRODID}"));
+    out.line("CALSCALE:GREGORIAN");
+    out.line("METHOD:PUBLISH");
+    if let Some(name) = name {
+        out.line(&format!("X-WR-CALNAME:{}", escape_text(name)));
+    }
+    for event in events {
+        write_event(&mut out, event);
+    }
+    out.line("END:VCALENDAR");
+    out.text
+}
+
+fn write_event(out: &mut IcsWriter, event: &CalendarEvent) {
+    let uid = match (
+        event.extra.get("iCalUID").and_then(Value::as_str),
+        &event.id,
+    ) {
+        (Some(uid), _) => uid.to_owned(),
+        (None, Some(id)) => format!("{id}@google.com"),
+        (None, None) => return,
+    };
+    let recurrence: Vec<&str> = event
+        .extra
+        .get("recurrence")
+        .and_then(Value::as_array)
+        .map(|rules| rules.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+
+    out.line("BEGIN:VEVENT");
+    out.line(&format!("UID:{}", escape_text(&uid)));
+    let stamp = event.updated_at.unwrap_or_else(Utc::now);
+    out.line(&format!("DTSTAMP:{}", stamp.format(UTC_FORMAT)));
+    for (name, time) in [("DTSTART", &event.start), ("DTEND", &event.end)] {
+        if let Some(time) = time {
+            if let Some(value) = format_time(name, time, !recurrence.is_empty()) {
+                out.line(&value);
+            }
+        }
+    }
+    for rule in &recurrence {
+        out.line(rule);
+    }
+    for (name, value) in [
+        ("SUMMARY", &event.summary),
+        ("DESCRIPTION", &event.description),
+        ("LOCATION", &event.location),
+    ] {
+        if let Some(value) = value {
+            out.line(&format!("{name}:{}", escape_text(value)));
+        }
+    }
+    if let Some(status) = &event.status {
+        out.line(&format!("STATUS:{}", status.to_uppercase()));
+    }
+    if let Some(transparency) = event.transparency {
+        let value = match transparency {
+            Transparency::Opaque => "OPAQUE",
+            Transparency::Transparent => "TRANSPARENT",
+        };
+        out.line(&format!("TRANSP:{value}"));
+    }
+    if let Some(visibility) = event.visibility {
+        let value = match visibility {
+            EventVisibility::Default | EventVisibility::Public => "PUBLIC",
+            EventVisibility::Private => "PRIVATE",
+            EventVisibility::Confidential => "CONFIDENTIAL",
+        };
+        out.line(&format!("CLASS:{value}"));
+    }
+    if let Some(email) = event.organizer.as_ref().and_then(|o| o.email.as_deref()) {
+        let name = event
+            .organizer
+            .as_ref()
+            .and_then(|o| o.display_name.as_deref());
+        out.line(&format!(
+            "ORGANIZER{}:mailto:{email}",
+            name.map(|name| format!(";CN={}", param_value(name)))
+                .unwrap_or_default()
+        ));
+    }
+    for attendee in event.attendees.iter().flatten() {
+        let mut params = String::new();
+        if let Some(name) = attendee.extra.get("displayName").and_then(Value::as_str) {
+            params.push_str(&format!(";CN={}", param_value(name)));
+        }
+        let role = if attendee.optional {
+            "OPT-PARTICIPANT"
+        } else {
+            "REQ-PARTICIPANT"
+        };
+        params.push_str(&format!(";ROLE={role}"));
+        let status = match attendee.response_status.as_deref() {
+            Some("accepted") => "ACCEPTED",
+            Some("declined") => "DECLINED",
+            Some("tentative") => "TENTATIVE",
+            _ => "NEEDS-ACTION",
+        };
+        params.push_str(&format!(";PARTSTAT={status}"));
+        out.line(&format!("ATTENDEE{params}:mailto:{}", attendee.email));
+    }
+    if let Some(link) = &event.html_link {
+        out.line(&format!("URL:{link}"));
+    }
+    if let Some(created) = event.created_at {
+        out.line(&format!("CREATED:{}", created.format(UTC_FORMAT)));
+    }
+    if let Some(updated) = event.updated_at {
+        out.line(&format!("LAST-MODIFIED:{}", updated.format(UTC_FORMAT)));
+    }
+    if let Some(sequence) = event.extra.get("sequence").and_then(Value::as_u64) {
+        out.line(&format!("SEQUENCE:{sequence}"));
+    }
+    out.line("END:VEVENT");
+}
+
+fn format_time(name: &str, time: &EventDateTime, recurring: bool) -> Option<String> {
+    if let Some(date) = time.date {
+        return Some(format!("{name};VALUE=DATE:{}", date.format(DATE_FORMAT)));
+    }
+    let at = time.date_time?;
+    let zone = time
+        .time_zone
+        .as_deref()
+        .and_then(|zone| zone.parse::<Tz>().ok());
+    Some(match zone {
+        Some(tz) if recurring => format!(
+            "{name};TZID={}:{}",
+            tz.name(),
+            at.with_timezone(&tz).format(LOCAL_FORMAT)
+        ),
+        _ => format!("{name}:{}", at.format(UTC_FORMAT)),
+    })
+}
+
+/// Accumulates CRLF-terminated lines, folding those longer than 75 octets.
+#[derive(Default)]
+struct IcsWriter {
+    text: String,
+}
+
+impl IcsWriter {
+    fn line(&mut self, content: &str) {
+        let mut rest = content;
+        let mut limit = MAX_LINE_OCTETS;
+        loop {
+            let mut split = rest.len().min(limit);
+            while !rest.is_char_boundary(split) {
+                split -= 1;
+            }
+            self.text.push_str(&rest[..split]);
+            self.text.push_str("\r\n");
+            rest = &rest[split..];
+            if rest.is_empty() {
+                break;
+            }
+            // Continuation lines start with a space, which counts towards their length.
+            self.text.push(' ');
+            limit = MAX_LINE_OCTETS - 1;
+        }
+    }
+}
+
+fn escape_text(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+fn unescape_text(value: &str) -> String {
+    let mut text = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => text.push('\n'),
+            Some(other) => text.push(other),
+            None => text.push('\\'),
+        }
+    }
+    text
+}
+
+fn param_value(value: &str) -> String {
+    let value = value.replace('"', "");
+    if value.contains([';', ':', ',']) {
+        format!("\"{value}\"")
+    } else {
+        value
+    }
+}
+
+/// One unfolded content line: `NAME;PARAM=VALUE:value`.
+struct ContentLine {
+    line: usize,
+    name: String,
+    params: Vec<(String, String)>,
+    value: String,
+}
+
+impl ContentLine {
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(param, _)| param == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Joins folded lines and splits them into name, parameters and value.
+fn content_lines(text: &str) -> Result<Vec<ContentLine>, IcsError> {
+    let mut unfolded: Vec<(usize, String)> = Vec::new();
+    for (index, raw) in text.lines().enumerate() {
+        let raw = raw.strip_suffix('\r').unwrap_or(raw);
+        match (raw.strip_prefix([' ', '\t']), unfolded.last_mut()) {
+            (Some(continuation), Some((_, previous))) => previous.push_str(continuation),
+            _ if raw.trim().is_empty() => {}
+            _ => unfolded.push((index + 1, raw.to_owned())),
+        }
+    }
+    unfolded
+        .into_iter()
+        .map(|(line, raw)| parse_content_line(line, &raw))
+        .collect()
+}
+
+fn parse_content_line(line: usize, raw: &str) -> Result<ContentLine, IcsError> {
+    let name_end = raw
+        .find([';', ':'])
+        .ok_or_else(|| IcsError::new(line, "expected NAME:value"))?;
+    let name = raw[..name_end].trim().to_ascii_uppercase();
+    if name.is_empty() {
+        return Err(IcsError::new(line, "missing property name"));
+    }
+
+    let mut params = Vec::new();
+    let mut rest = &raw[name_end..];
+    while let Some(param) = rest.strip_prefix(';') {
+        let eq = param
+            .find('=')
+            .ok_or_else(|| IcsError::new(line, format!("parameter without value in {name}")))?;
+        let param_name = param[..eq].trim().to_ascii_uppercase();
+        let after = &param[eq + 1..];
+        let (value, remaining) = match after.strip_prefix('"') {
+            Some(quoted) => {
+                let close = quoted
+                    .find('"')
+                    .ok_or_else(|| IcsError::new(line, "unterminated quoted parameter"))?;
+                (&quoted[..close], &quoted[close + 1..])
+            }
+            None => {
+                let end = after.find([';', ':']).unwrap_or(after.len());
+                (&after[..end], &after[end..])
+            }
+        };
+        params.push((param_name, value.to_owned()));
+        rest = remaining;
+    }
+    let value = rest
+        .strip_prefix(':')
+        .ok_or_else(|| IcsError::new(line, format!("missing ':' after {name}")))?;
+
+    Ok(ContentLine {
+        line,
+        name,
+        params,
+        value: value.to_owned(),
+    })
+}
+
+/// Reads the `VEVENT`s of an iCalendar document.
+///
+/// Times without a zone, and zones that are not IANA names (such as Windows zone names), are
+/// read in `default_tz`. Recurrence exceptions (`RECURRENCE-ID`) and events without `UID` or
+/// `DTSTART` are reported in `skipped`.
+pub fn parse_calendar(text: &str, default_tz: Tz) -> Result<ParsedCalendar, IcsError> {
+    let lines = content_lines(text)?;
+    if !lines
+        .first()
+        .is_some_and(|first| first.name == "BEGIN" && first.value.eq_ignore_ascii_case("VCALENDAR"))
+    {
+        return Err(IcsError::new(
+            lines.first().map_or(1, |first| first.line),
+            "not an iCalendar document (expected BEGIN:VCALENDAR)",
+        ));
+    }
+
+    let mut parsed = ParsedCalendar::default();
+    let mut current: Option<(usize, Vec<&ContentLine>)> = None;
+    let mut nested = 0usize;
+    for line in &lines {
+        let component = line.value.to_ascii_uppercase();
+        match (line.name.as_str(), current.as_mut()) {
+            ("BEGIN", None) if component == "VEVENT" => current = Some((line.line, Vec::new())),
+            ("BEGIN", Some(_)) => nested += 1,
+            ("END", Some(_)) if nested > 0 => nested -= 1,
+            ("END", Some(_)) if component == "VEVENT" => {
+                let (start_line, properties) = current.take().expect("inside a VEVENT");
+                match build_event(start_line, &properties, default_tz) {
+                    Ok(event) => parsed.events.push(event),
+                    Err(err) => parsed.skipped.push(err),
+                }
+            }
+            (_, Some((_, properties))) if nested == 0 => properties.push(line),
+            _ => {}
+        }
+    }
+    if let Some((start_line, _)) = current {
+        return Err(IcsError::new(start_line, "VEVENT is not closed"));
+    }
+    Ok(parsed)
+}
+
+fn build_event(
+    start_line: usize,
+    properties: &[&ContentLine],
+    default_tz: Tz,
+) -> Result<IcsEvent, IcsError> {
+    let mut uid = None;
+    let mut payload = EventPayload::default();
+    let mut duration = None;
+    let mut recurrence = Vec::new();
+    let mut attendees = Vec::new();
+
+    for property in properties {
+        let value = property.value.as_str();
+        match property.name.as_str() {
+            "UID" => uid = Some(unescape_text(value)),
+            "SUMMARY" => payload.summary = Some(unescape_text(value)),
+            "DESCRIPTION" => payload.description = Some(unescape_text(value)),
+            "LOCATION" => payload.location = Some(unescape_text(value)),
+            "DTSTART" => payload.start = Some(parse_time(property, default_tz)?),
+            "DTEND" => payload.end = Some(parse_time(property, default_tz)?),
+            "DURATION" => {
+                duration = Some(parse_duration(value).ok_or_else(|| {
+                    IcsError::new(property.line, format!("invalid DURATION '{value}'"))
+                })?)
+            }
+            "RRULE" | "EXRULE" | "RDATE" | "EXDATE" => {
+                recurrence.push(recurrence_line(property, default_tz))
+            }
+            "RECURRENCE-ID" => {
+                return Err(IcsError::new(
+                    start_line,
+                    "recurrence exceptions (RECURRENCE-ID) are not imported",
+                ))
+            }
+            "STATUS" => {
+                payload
+                    .extra
+                    .insert("status".into(), json!(value.to_ascii_lowercase()));
+            }
+            "TRANSP" => {
+                payload.transparency = match value.to_ascii_uppercase().as_str() {
+                    "TRANSPARENT" => Some(Transparency::Transparent),
+                    _ => Some(Transparency::Opaque),
+                }
+            }
+            "CLASS" => {
+                payload.visibility = match value.to_ascii_uppercase().as_str() {
+                    "PRIVATE" => Some(EventVisibility::Private),
+                    "CONFIDENTIAL" => Some(EventVisibility::Confidential),
+                    _ => Some(EventVisibility::Public),
+                }
+            }
+            "SEQUENCE" => {
+                if let Ok(sequence) = value.trim().parse::<u64>() {
+                    payload.extra.insert("sequence".into(), json!(sequence));
+                }
+            }
+            "URL" if value.starts_with("https://") || value.starts_with("http://") => {
+                payload.source = Some(EventSource {
+                    url: value.to_owned(),
+                    title: None,
+                })
+            }
+            "ORGANIZER" => {
+                let mut organizer = json!({ "email": mailto(value) });
+                if let Some(name) = property.param("CN") {
+                    organizer["displayName"] = json!(name);
+                }
+                payload.extra.insert("organizer".into(), organizer);
+            }
+            "ATTENDEE" => attendees.push(attendee(property)),
+            _ => {}
+        }
+    }
+
+    let uid = uid
+        .filter(|uid| !uid.trim().is_empty())
+        .ok_or_else(|| IcsError::new(start_line, "VEVENT has no UID"))?;
+    let start = payload
+        .start
+        .clone()
+        .ok_or_else(|| IcsError::new(start_line, format!("VEVENT {uid} has no DTSTART")))?;
+    if payload.end.is_none() {
+        // RFC 5545: without DTEND or DURATION a date lasts one day and a date-time is instant.
+        let duration = duration.unwrap_or(if start.date.is_some() {
+            Duration::days(1)
+        } else {
+            Duration::zero()
+        });
+        let out_of_range = || {
+            IcsError::new(
+                start_line,
+                format!("VEVENT {uid} ends out of the supported range"),
+            )
+        };
+        payload.end = Some(EventDateTime {
+            date_time: start
+                .date_time
+                .map(|at| at.checked_add_signed(duration).ok_or_else(out_of_range))
+                .transpose()?,
+            date: start
+                .date
+                .map(|date| date.checked_add_signed(duration).ok_or_else(out_of_range))
+                .transpose()?,
+            time_zone: start.time_zone.clone(),
+            ..Default::default()
+        });
+    }
+    if !recurrence.is_empty() {
+        // Google requires a zone on recurring events to expand the rule.
+        for time in [payload.start.as_mut(), payload.end.as_mut()]
+            .into_iter()
+            .flatten()
+        {
+            if time.date.is_none() && time.time_zone.is_none() {
+                time.time_zone = Some(default_tz.name().to_owned());
+            }
+        }
+        payload.extra.insert("recurrence".into(), json!(recurrence));
+    }
+    if !attendees.is_empty() {
+        payload.attendees = Some(attendees);
+    }
+    payload.extra.insert("iCalUID".into(), json!(uid));
+
+    Ok(IcsEvent {
+        ical_uid: uid,
+        payload,
+    })
+}
+
+fn zone_of(property: &ContentLine, default_tz: Tz) -> Tz {
+    property
+        .param("TZID")
+        .and_then(|zone| zone.trim_start_matches('/').parse::<Tz>().ok())
+        .unwrap_or(default_tz)
+}
+
+fn parse_time(property: &ContentLine, default_tz: Tz) -> Result<EventDateTime, IcsError> {
+    let value = property.value.trim();
+    let invalid = || {
+        IcsError::new(
+            property.line,
+            format!("invalid {} '{value}'", property.name),
+        )
+    };
+
+    if property.param("VALUE") == Some("DATE") || value.len() == 8 {
+        let date = NaiveDate::parse_from_str(value, DATE_FORMAT).map_err(|_| invalid())?;
+        return Ok(EventDateTime {
+            date: Some(date),
+            ..Default::default()
+        });
+    }
+    if let Some(utc) = value.strip_suffix(['Z', 'z']) {
+        let at = NaiveDateTime::parse_from_str(utc, LOCAL_FORMAT).map_err(|_| invalid())?;
+        return Ok(EventDateTime {
+            date_time: Some(at.and_utc()),
+            ..Default::default()
+        });
+    }
+
+    let tz = zone_of(property, default_tz);
+    let local = NaiveDateTime::parse_from_str(value, LOCAL_FORMAT).map_err(|_| invalid())?;
+    // A local time skipped by a DST change is moved forward by the gap.
+    let at = tz
+        .from_local_datetime(&local)
+        .earliest()
+        .or_else(|| {
+            tz.from_local_datetime(&(local + Duration::hours(1)))
+                .earliest()
+        })
+        .ok_or_else(invalid)?;
+    Ok(EventDateTime {
+        date_time: Some(at.with_timezone(&Utc)),
+        time_zone: Some(tz.name().to_owned()),
+        ..Default::default()
+    })
+}
+
+/// Rebuilds a recurrence property for Google, replacing a non-IANA `TZID` with the zone used.
+fn recurrence_line(property: &ContentLine, default_tz: Tz) -> String {
+    let mut line = property.name.clone();
+    for (name, value) in &property.params {
+        let value = if name == "TZID" {
+            zone_of(property, default_tz).name().to_owned()
+        } else {
+            value.clone()
+        };
+        line.push_str(&format!(";{name}={}", param_value(&value)));
+    }
+    line.push(':');
+    line.push_str(&property.value);
+    line
+}
+
+fn mailto(value: &str) -> String {
+    let value = value.trim();
+    match value.get(..7) {
+        Some(scheme) if scheme.eq_ignore_ascii_case("mailto:") => value[7..].to_owned(),
+        _ => value.to_owned(),
+    }
+}
+
+fn attendee(property: &ContentLine) -> EventAttendee {
+    let mut attendee = EventAttendee {
+        email: mailto(&property.value),
+        optional: property.param("ROLE") == Some("OPT-PARTICIPANT"),
+        response_status: property.param("PARTSTAT").map(|status| {
+            match status.to_ascii_uppercase().as_str() {
+                "ACCEPTED" => "accepted",
+                "DECLINED" => "declined",
+                "TENTATIVE" => "tentative",
+                _ => "needsAction",
+            }
+            .to_owned()
+        }),
+        ..Default::default()
+    };
+    if let Some(name) = property.param("CN") {
+        attendee.extra.insert("displayName".into(), json!(name));
+    }
+    attendee
+}
+
+/// Parses `P1W`, `PT1H30M`, `-P1DT2H` and similar durations.
+fn parse_duration(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    let (sign, value) = match value.strip_prefix('-') {
+        Some(rest) => (-1, rest),
+        None => (1, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let mut rest = value.strip_prefix('P')?;
+    let mut total = Duration::zero();
+    let mut in_time = false;
+    while !rest.is_empty() {
+        if let Some(time) = rest.strip_prefix('T') {
+            in_time = true;
+            rest = time;
+            continue;
+        }
+        let digits = rest.find(|c: char| !c.is_ascii_digit())?;
+        let amount: i64 = rest[..digits].parse().ok()?;
+        let unit = rest[digits..].chars().next()?;
+        let part = match (unit, in_time) {
+            ('W', false) => Duration::try_weeks(amount)?,
+            ('D', false) => Duration::try_days(amount)?,
+            ('H', true) => Duration::try_hours(amount)?,
+            ('M', true) => Duration::try_minutes(amount)?,
+            ('S', true) => Duration::try_seconds(amount)?,
+            _ => return None,
+        };
+        total = total.checked_add(&part)?;
+        rest = &rest[digits + 1..];
+    }
+    total.checked_mul(sign)
+}
+
+impl GoogleCalendarClient {
+    /// The event with `ical_uid` on the calendar, if one was imported or created before.
+    pub async fn find_by_ical_uid(
+        &self,
+        token: &UserToken,
+        calendar_id: &Option<String>,
+        ical_uid: &str,
+    ) -> Result<Option<CalendarEvent>> {
+        let calendar_id = self.resolve_calendar(calendar_id);
+        let url = self.calendar_url(calendar_id, "events")?;
+        let request = self
+            .http
+            .get(url)
+            .bearer_auth(token.access_token())
+            .query(&[("iCalUID", ical_uid)]);
+        let response = self.send(token, request, true).await?;
+
+        let page = response.json::<super::ListEventsResponse>().await?;
+        Ok(page
+            .items
+            .into_iter()
+            .find(|event| !event.extra.contains_key("recurringEventId")))
+    }
+
+    /// Adds a private copy of an event via `events/import`, keyed by its `iCalUID`.
+    pub async fn import_event(
+        &self,
+        token: &UserToken,
+        payload: &EventPayload,
+    ) -> Result<CalendarEvent> {
+        let calendar_id = self.resolve_calendar(&payload.calendar_id);
+        let url = self.calendar_url(calendar_id, "events/import")?;
+        let request = self
+            .http
+            .post(url)
+            .bearer_auth(token.access_token())
+            .query(&[
+                ("conferenceDataVersion", "1"),
+                ("supportsAttachments", "true"),
+            ])
+            .json(&diff::payload_fields(payload));
+        // Importing the same iCalUID again updates the copy instead of adding one.
+        let response = self.send(token, request, true).await?;
+
+        Ok(response.json::<CalendarEvent>().await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ics_round_trips_through_google_fields() {
+        let event: CalendarEvent = serde_json::from_value(json!({
+            "id": "evt",
+            "iCalUID": "evt@google.com",
+            "status": "confirmed",
+            "summary": "Planning, Q4; budget",
+            "description": "Line one\nLine two with a long tail that needs folding because it runs past seventy-five octets",
+            "start": {"dateTime": "2025-10-20T08:00:00Z", "timeZone": "Europe/Berlin"},
+            "end": {"dateTime": "2025-10-20T09:00:00Z", "timeZone": "Europe/Berlin"},
+            "recurrence": ["RRULE:FREQ=WEEKLY;COUNT=4"],
+            "attendees": [
+                {"email": "ana@example.com", "displayName": "Ana", "responseStatus": "accepted"},
+                {"email": "bo@example.com", "optional": true}
+            ]
+        }))
+        .unwrap();
+
+        let ics = write_calendar(Some("Team"), &[event]);
+        assert!(ics.lines().all(|line| line.len() <= MAX_LINE_OCTETS + 1));
+        assert!(ics.contains("DTSTART;TZID=Europe/Berlin:20251020T100000\r\n"));
+        assert!(ics.contains("SUMMARY:Planning\\, Q4\\; budget\r\n"));
+
+        let parsed = parse_calendar(&ics, chrono_tz::UTC).unwrap();
+        assert!(parsed.skipped.is_empty());
+        let imported = &parsed.events[0];
+        assert_eq!(imported.ical_uid, "evt@google.com");
+        let payload = &imported.payload;
+        assert_eq!(payload.summary.as_deref(), Some("Planning, Q4; budget"));
+        assert!(payload
+            .description
+            .as_deref()
+            .unwrap()
+            .ends_with("seventy-five octets"));
+        assert_eq!(
+            payload
+                .start
+                .as_ref()
+                .unwrap()
+                .date_time
+                .unwrap()
+                .to_rfc3339(),
+            "2025-10-20T08:00:00+00:00"
+        );
+        assert_eq!(payload.extra["recurrence"][0], "RRULE:FREQ=WEEKLY;COUNT=4");
+        let attendees = payload.attendees.as_ref().unwrap();
+        assert_eq!(attendees[0].response_status.as_deref(), Some("accepted"));
+        assert_eq!(attendees[0].extra["displayName"], "Ana");
+        assert!(attendees[1].optional);
+        assert!(!imported.clone().into_patch().extra.contains_key("iCalUID"));
+    }
+
+    #[test]
+    fn parser_handles_all_day_durations_and_unknown_zones() {
+        let ics = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\n\
+            BEGIN:VEVENT\r\nUID:a\r\nDTSTART;VALUE=DATE:20251224\r\nSUMMARY:Holiday\r\n\
+            BEGIN:VALARM\r\nTRIGGER:-PT15M\r\nEND:VALARM\r\nEND:VEVENT\r\n\
+            BEGIN:VEVENT\r\nUID:b\r\nDTSTART;TZID=W. Europe Standard Time:20250301T090000\r\n\
+            DURATION:PT1H30M\r\nEND:VEVENT\r\n\
+            BEGIN:VEVENT\r\nUID:b\r\nRECURRENCE-ID:20250308T090000Z\r\nDTSTART:20250308T100000Z\r\nEND:VEVENT\r\n\
+            END:VCALENDAR\r\n";
+
+        let parsed = parse_calendar(ics, chrono_tz::Asia::Tokyo).unwrap();
+        assert_eq!(parsed.events.len(), 2);
+        assert_eq!(parsed.skipped.len(), 1);
+
+        let holiday = &parsed.events[0].payload;
+        assert_eq!(
+            holiday.end.as_ref().unwrap().date,
+            NaiveDate::from_ymd_opt(2025, 12, 25)
+        );
+        let meeting = &parsed.events[1].payload;
+        assert_eq!(
+            meeting.start.as_ref().unwrap().time_zone.as_deref(),
+            Some("Asia/Tokyo")
+        );
+        assert_eq!(
+            meeting
+                .end
+                .as_ref()
+                .unwrap()
+                .date_time
+                .unwrap()
+                .to_rfc3339(),
+            "2025-03-01T01:30:00+00:00"
+        );
+
+        assert!(parse_calendar("BEGIN:VEVENT\r\n", chrono_tz::UTC).is_err());
+    }
+
+    #[test]
+    fn oversized_durations_are_skipped() {
+        let ics = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\n\
+            BEGIN:VEVENT\r\nUID:weeks\r\nDTSTART:20250301T090000Z\r\nDURATION:P99999999999999W\r\nEND:VEVENT\r\n\
+            BEGIN:VEVENT\r\nUID:sum\r\nDTSTART:20250301T090000Z\r\n\
+            DURATION:P15000000000W2000000000D\r\nEND:VEVENT\r\n\
+            BEGIN:VEVENT\r\nUID:timed\r\nDTSTART:20250301T090000Z\r\nDURATION:P1000000000D\r\nEND:VEVENT\r\n\
+            BEGIN:VEVENT\r\nUID:day\r\nDTSTART;VALUE=DATE:20250301\r\nDURATION:P1000000000D\r\nEND:VEVENT\r\n\
+            BEGIN:VEVENT\r\nUID:ok\r\nDTSTART:20250301T090000Z\r\nDURATION:PT1H\r\nEND:VEVENT\r\n\
+            END:VCALENDAR\r\n";
+
+        let parsed = parse_calendar(ics, chrono_tz::UTC).unwrap();
+        assert_eq!(parsed.events.len(), 1);
+        assert_eq!(parsed.skipped.len(), 4);
+        assert!(parse_duration("P15000000000W").is_some());
+    }
+}
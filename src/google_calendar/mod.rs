@@ -1,23 +1,117 @@
-use crate::oauth::TokenInfo;
-use anyhow::{anyhow, Context, Result};
-use chrono::{DateTime, Utc};
-use reqwest::Client;
+pub mod acl;
+pub mod agenda;
+pub mod analytics;
+pub mod batch;
+pub mod calendars;
+pub mod colors;
+pub mod conference;
+pub mod conflicts;
+pub mod diff;
+pub mod event_types;
+pub mod export;
+pub mod ics;
+pub mod retry;
+pub mod time_expr;
+
+use crate::config::GoogleConfig;
+use crate::oauth::UserToken;
+use anyhow::{Context, Result};
+use chrono::{DateTime, FixedOffset, NaiveDate, Utc};
+use chrono_tz::Tz;
+use conference::ConferenceData;
+use event_types::{FocusTimeProperties, OutOfOfficeProperties, WorkingLocationProperties};
+use reqwest::{Client, RequestBuilder, Response};
+use retry::{RateLimiter, RetryPolicy};
 use schemars::{JsonSchema, Schema, SchemaGenerator};
-use serde::{de, Deserialize, Deserializer, Serialize};
+use serde::{de, ser::SerializeStruct, Deserialize, Deserializer, Serialize, Serializer};
+use sha2::{Digest, Sha256};
 use std::borrow::Cow;
 use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use time_expr::{TimeExprError, TimeInput, TimeResolver};
 use url::Url;
 
+/// Largest `maxResults` Google accepts for events.list.
+pub const MAX_PAGE_SIZE: u32 = 2500;
+
+/// Which guests Google emails about a create, update or move.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum SendUpdates {
+    All,
+    #[serde(alias = "external_only")]
+    ExternalOnly,
+    #[default]
+    None,
+}
+
+impl SendUpdates {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::All => "all",
+            Self::ExternalOnly => "externalOnly",
+            Self::None => "none",
+        }
+    }
+}
+
+fn send_updates_query(send_updates: Option<SendUpdates>) -> Vec<(&'static str, &'static str)> {
+    send_updates
+        .map(|send_updates| ("sendUpdates", send_updates.as_str()))
+        .into_iter()
+        .collect()
+}
+
+/// Query of an event insert or patch; `conferenceDataVersion=1` and `supportsAttachments=true`
+/// let `conferenceData` and `attachments` through.
+fn write_query(payload: &EventPayload) -> Vec<(&'static str, &'static str)> {
+    let mut query = vec![
+        ("conferenceDataVersion", "1"),
+        ("supportsAttachments", "true"),
+    ];
+    query.extend(send_updates_query(payload.send_updates));
+    query
+}
+
 #[derive(Clone)]
 pub struct GoogleCalendarClient {
     http: Client,
     api_base: Url,
     default_calendar_id: Option<String>,
+    retry: RetryPolicy,
+    rate_limiter: Option<Arc<RateLimiter>>,
 }
 
 impl GoogleCalendarClient {
     pub fn new(api_base: String) -> Self {
-        let http = Client::builder()
+        Self::with_http(api_base, Client::builder())
+    }
+
+    /// Builds a client with the timeouts, retry policy and rate limit from `config`.
+    pub fn from_config(config: &GoogleConfig) -> Self {
+        let builder = Client::builder()
+            .timeout(Duration::from_secs(config.request_timeout_secs))
+            .connect_timeout(Duration::from_secs(config.connect_timeout_secs));
+        let mut client = Self::with_http(config.api_base.clone(), builder)
+            .with_default_calendar(config.calendar_id.clone());
+
+        client.retry = RetryPolicy {
+            max_retries: config.max_retries,
+            base_delay: Duration::from_millis(config.retry_base_delay_ms),
+            max_delay: Duration::from_millis(config.retry_max_delay_ms),
+        };
+        if config.rate_limit_per_second > 0.0 {
+            client.rate_limiter = Some(Arc::new(RateLimiter::new(
+                config.rate_limit_per_second,
+                config.rate_limit_burst,
+            )));
+        }
+        client
+    }
+
+    fn with_http(api_base: String, builder: reqwest::ClientBuilder) -> Self {
+        let http = builder
             .user_agent("mcp-google-calendar/0.1.0")
             .build()
             .expect("reqwest client");
@@ -33,6 +127,8 @@ impl GoogleCalendarClient {
             http,
             api_base,
             default_calendar_id: None,
+            retry: RetryPolicy::default(),
+            rate_limiter: None,
         }
     }
 
@@ -50,7 +146,71 @@ impl GoogleCalendarClient {
         Ok(joined)
     }
 
-    fn resolve_calendar<'a>(&'a self, override_id: &'a Option<String>) -> &'a str {
+    /// Sends `request` through the per-user rate limiter, retrying rate-limit errors and, when
+    /// `retry_transient` is set, timeouts and 5xx responses.
+    ///
+    /// Only pass `retry_transient` for requests that are safe to repeat after an ambiguous
+    /// failure. Error responses are returned as [`GoogleCalendarError`].
+    async fn send(
+        &self,
+        token: &UserToken,
+        request: RequestBuilder,
+        retry_transient: bool,
+    ) -> Result<Response> {
+        let mut attempt = 0;
+        loop {
+            self.throttle(token).await?;
+            let pending = request
+                .try_clone()
+                .context("request body cannot be retried")?;
+
+            let error = match pending.send().await {
+                Ok(response) if response.status().is_success() => return Ok(response),
+                Ok(response) => Self::classify_error(response).await,
+                Err(err) if err.is_timeout() || err.is_connect() => {
+                    GoogleCalendarError::Transient {
+                        message: err.to_string(),
+                        status_code: None,
+                    }
+                }
+                Err(err) => return Err(err.into()),
+            };
+
+            let retry_after = match &error {
+                GoogleCalendarError::RateLimited { retry_after, .. } => *retry_after,
+                GoogleCalendarError::Transient { .. } if retry_transient => None,
+                _ => return Err(error.into()),
+            };
+            let Some(delay) = self.retry.next_delay(attempt, retry_after) else {
+                return Err(error.into());
+            };
+
+            tracing::warn!(attempt, ?delay, %error, "retrying Google Calendar request");
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    /// Waits for the caller's token bucket, failing fast if the wait would exceed the retry cap.
+    async fn throttle(&self, token: &UserToken) -> Result<(), GoogleCalendarError> {
+        let Some(limiter) = &self.rate_limiter else {
+            return Ok(());
+        };
+        match limiter.reserve(&token.user_id, self.retry.max_delay) {
+            Ok(Duration::ZERO) => Ok(()),
+            Ok(wait) => {
+                tokio::time::sleep(wait).await;
+                Ok(())
+            }
+            Err(wait) => Err(GoogleCalendarError::RateLimited {
+                message: "per-user request rate exceeded".to_owned(),
+                reason: None,
+                retry_after: Some(wait),
+            }),
+        }
+    }
+
+    pub fn resolve_calendar<'a>(&'a self, override_id: &'a Option<String>) -> &'a str {
         override_id
             .as_deref()
             .or(self.default_calendar_id.as_deref())
@@ -59,19 +219,19 @@ impl GoogleCalendarClient {
 
     pub async fn list_events(
         &self,
-        token: &TokenInfo,
+        token: &UserToken,
         params: &ListEventsParams,
     ) -> Result<ListEventsResponse> {
         let calendar_id = self.resolve_calendar(&params.calendar_id);
         let url = self.calendar_url(calendar_id, "events")?;
-        let mut request = self.http.get(url).bearer_auth(&token.access_token);
+        let mut request = self.http.get(url).bearer_auth(token.access_token());
 
         let mut query: HashMap<&str, String> = HashMap::new();
-        if let Some(time_min) = params.time_min {
-            query.insert("timeMin", time_min.to_rfc3339());
+        if let Some(time_min) = &params.time_min {
+            query.insert("timeMin", Self::absolute_time("time_min", time_min)?);
         }
-        if let Some(time_max) = params.time_max {
-            query.insert("timeMax", time_max.to_rfc3339());
+        if let Some(time_max) = &params.time_max {
+            query.insert("timeMax", Self::absolute_time("time_max", time_max)?);
         }
         if let Some(max_results) = params.max_results {
             query.insert("maxResults", max_results.to_string());
@@ -88,147 +248,583 @@ impl GoogleCalendarClient {
         if params.order_by_start_time {
             query.insert("orderBy", "startTime".into());
         }
+        for kind in &params.event_types {
+            if *kind == EventType::Other {
+                return Err(
+                    GoogleCalendarError::invalid("event_types contains an unknown type").into(),
+                );
+            }
+            request = request.query(&[("eventTypes", kind.as_str())]);
+        }
+        if let Some(fields) = &params.fields {
+            // Event fields apply to `items`; page-level fields are always kept for pagination.
+            query.insert(
+                "fields",
+                format!("kind,summary,nextPageToken,items({fields})"),
+            );
+        }
 
         if !query.is_empty() {
             request = request.query(&query);
         }
 
-        let response = request.send().await?;
-        if !response.status().is_success() {
-            return Err(Self::classify_error(response).await.into());
-        }
+        let response = self.send(token, request, true).await?;
         let payload = response.json::<ListEventsResponse>().await?;
         Ok(payload)
     }
 
+    fn absolute_time(field: &str, time: &TimeInput) -> Result<String> {
+        match time.absolute() {
+            Some(at) => Ok(at.to_rfc3339()),
+            None => Err(GoogleCalendarError::invalid(format!(
+                "{field} must be resolved to an absolute time before calling Google"
+            ))
+            .into()),
+        }
+    }
+
+    /// IANA time zone of `calendar_id`.
+    ///
+    /// Read from the `timeZone` of an events.list page so that the events scope is enough.
+    pub async fn calendar_time_zone(
+        &self,
+        token: &UserToken,
+        calendar_id: &Option<String>,
+    ) -> Result<Tz> {
+        #[derive(Deserialize)]
+        struct Page {
+            #[serde(rename = "timeZone")]
+            time_zone: Option<String>,
+        }
+
+        let calendar_id = self.resolve_calendar(calendar_id);
+        let url = self.calendar_url(calendar_id, "events")?;
+        let request = self
+            .http
+            .get(url)
+            .bearer_auth(token.access_token())
+            .query(&[("maxResults", "1"), ("fields", "timeZone")]);
+        let response = self.send(token, request, true).await?;
+        let page = response.json::<Page>().await?;
+
+        let name = page.time_zone.unwrap_or_else(|| "UTC".to_owned());
+        name.parse::<Tz>()
+            .map_err(|err| anyhow::anyhow!("unknown calendar time zone '{name}': {err}"))
+    }
+
+    /// The user's time zone from their Calendar settings.
+    ///
+    /// Settings need a broader scope than `calendar.events`; without it the time zone of the
+    /// primary calendar is used, which Google initializes from the same setting.
+    pub async fn user_time_zone(&self, token: &UserToken) -> Result<Tz> {
+        #[derive(Deserialize)]
+        struct Setting {
+            value: String,
+        }
+
+        let url = self
+            .api_base
+            .join("users/me/settings/timezone")
+            .context("failed to compose settings endpoint")?;
+        let request = self.http.get(url).bearer_auth(token.access_token());
+        match self.send(token, request, true).await {
+            Ok(response) => {
+                let setting = response.json::<Setting>().await?;
+                setting.value.parse::<Tz>().map_err(|err| {
+                    anyhow::anyhow!("unknown user time zone '{}': {err}", setting.value)
+                })
+            }
+            Err(err)
+                if matches!(
+                    err.downcast_ref(),
+                    Some(
+                        GoogleCalendarError::InsufficientScope { .. }
+                            | GoogleCalendarError::Forbidden { .. }
+                    )
+                ) =>
+            {
+                self.calendar_time_zone(token, &Some("primary".to_owned()))
+                    .await
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Follows `nextPageToken` until `max_items` events are collected or the last page is read.
+    ///
+    /// Page sizes are shrunk near the cap so the returned `next_page_token` resumes exactly after
+    /// the last returned event; it is only set when the cap stopped pagination early.
+    pub async fn list_all_events(
+        &self,
+        token: &UserToken,
+        params: &ListEventsParams,
+        max_items: usize,
+    ) -> Result<ListEventsResponse> {
+        let page_size = params
+            .max_results
+            .unwrap_or(MAX_PAGE_SIZE)
+            .min(MAX_PAGE_SIZE) as usize;
+        let mut params = params.clone();
+        let mut collected: Option<ListEventsResponse> = None;
+
+        loop {
+            let have = collected
+                .as_ref()
+                .map_or(0, |response| response.items.len());
+            params.max_results = Some(page_size.min(max_items - have).max(1) as u32);
+
+            let page = self.list_events(token, &params).await?;
+            let next_page_token = page.next_page_token.clone();
+            let response = match collected.as_mut() {
+                Some(response) => {
+                    response.items.extend(page.items);
+                    response.next_page_token = page.next_page_token;
+                    response
+                }
+                None => collected.insert(page),
+            };
+
+            match next_page_token {
+                Some(next) if response.items.len() < max_items => params.page_token = Some(next),
+                _ => break,
+            }
+        }
+
+        let mut response = collected.expect("at least one page is fetched");
+        response.items.truncate(max_items);
+        Ok(response)
+    }
+
     pub async fn get_event(
         &self,
-        token: &TokenInfo,
+        token: &UserToken,
         params: &GetEventParams,
     ) -> Result<CalendarEvent> {
         let calendar_id = self.resolve_calendar(&params.calendar_id);
         let url = self.calendar_url(calendar_id, &format!("events/{}", params.event_id))?;
-        let response = self
-            .http
-            .get(url)
-            .bearer_auth(&token.access_token)
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            return Err(Self::classify_error(response).await.into());
-        }
+        let request = self.http.get(url).bearer_auth(token.access_token());
+        let response = self.send(token, request, true).await?;
 
         Ok(response.json::<CalendarEvent>().await?)
     }
 
     pub async fn create_event(
         &self,
-        token: &TokenInfo,
+        token: &UserToken,
         request: &EventPayload,
     ) -> Result<CalendarEvent> {
         let calendar_id = self.resolve_calendar(&request.calendar_id);
         let url = self.calendar_url(calendar_id, "events")?;
+        let body = Self::create_body(request)?;
 
-        let mut body = serde_json::to_value(request)?
-            .as_object()
-            .cloned()
-            .unwrap_or_default();
-        body.remove("calendar_id");
-        if !body.contains_key("summary") {
-            return Err(anyhow!("summary is required to create an event"));
-        }
-        if !body.contains_key("start") || !body.contains_key("end") {
-            return Err(anyhow!(
-                "start and end dateTimes are required to create an event"
-            ));
-        }
+        // Without a client-chosen id a retried insert could create a duplicate.
+        let request = self
+            .http
+            .post(url)
+            .bearer_auth(token.access_token())
+            .query(&write_query(request))
+            .json(&body);
+        let response = self.send(token, request, false).await?;
+
+        let event = response.json::<CalendarEvent>().await?;
+        self.await_conference(token, calendar_id, event).await
+    }
+
+    /// Creates an event with a caller-chosen `id` so that retries cannot duplicate it.
+    ///
+    /// Returns the event and whether this call created it. A 409 means an earlier attempt already
+    /// created the event, which is then fetched and returned instead.
+    pub async fn create_event_with_id(
+        &self,
+        token: &UserToken,
+        request: &EventPayload,
+        event_id: &str,
+    ) -> Result<(CalendarEvent, bool)> {
+        let calendar_id = self.resolve_calendar(&request.calendar_id);
+        let url = self.calendar_url(calendar_id, "events")?;
+        let mut body = Self::create_body(request)?;
+        body.insert("id".into(), serde_json::Value::String(event_id.to_owned()));
 
-        let response = self
+        let request = self
             .http
             .post(url)
-            .bearer_auth(&token.access_token)
-            .json(&body)
-            .send()
-            .await?;
+            .bearer_auth(token.access_token())
+            .query(&write_query(request))
+            .json(&body);
 
-        if !response.status().is_success() {
-            return Err(Self::classify_error(response).await.into());
+        match self.send(token, request, true).await {
+            Err(err)
+                if matches!(
+                    err.downcast_ref(),
+                    Some(GoogleCalendarError::Conflict { .. })
+                ) =>
+            {
+                let params = GetEventParams {
+                    event_id: event_id.to_owned(),
+                    calendar_id: Some(calendar_id.to_owned()),
+                };
+                Ok((self.get_event(token, &params).await?, false))
+            }
+            result => {
+                let event = result?.json::<CalendarEvent>().await?;
+                Ok((
+                    self.await_conference(token, calendar_id, event).await?,
+                    true,
+                ))
+            }
         }
-
-        Ok(response.json::<CalendarEvent>().await?)
     }
 
     pub async fn update_event(
         &self,
-        token: &TokenInfo,
+        token: &UserToken,
         event_id: &str,
         patch: &EventPayload,
+        if_match_etag: Option<&str>,
     ) -> Result<CalendarEvent> {
         let calendar_id = self.resolve_calendar(&patch.calendar_id);
         let url = self.calendar_url(calendar_id, &format!("events/{event_id}"))?;
+        let body = Self::patch_body(patch)?;
+
+        let mut request = self
+            .http
+            .patch(url)
+            .bearer_auth(token.access_token())
+            .query(&write_query(patch))
+            .json(&body);
+        if let Some(etag) = if_match_etag {
+            request = request.header(reqwest::header::IF_MATCH, etag);
+        }
+
+        // A conditional PATCH is not retried: if a timed-out attempt was applied, the retry
+        // would fail on its own etag change and be reported as someone else's edit.
+        let retry_transient = if_match_etag.is_none();
+        let response = match self.send(token, request, retry_transient).await {
+            Err(err)
+                if matches!(
+                    err.downcast_ref(),
+                    Some(GoogleCalendarError::PreconditionFailed { .. })
+                ) =>
+            {
+                // Someone else changed the event; hand back their version so the caller can rebase.
+                let params = GetEventParams {
+                    event_id: event_id.to_owned(),
+                    calendar_id: Some(calendar_id.to_owned()),
+                };
+                let current = self.get_event(token, &params).await.ok().map(Box::new);
+                return Err(GoogleCalendarError::PreconditionFailed {
+                    message: format!("event {event_id} no longer matches the supplied etag"),
+                    current,
+                }
+                .into());
+            }
+            result => result?,
+        };
+
+        let event = response.json::<CalendarEvent>().await?;
+        self.await_conference(token, calendar_id, event).await
+    }
+
+    /// Creates an event from free text via `events/quickAdd`.
+    pub async fn quick_add(
+        &self,
+        token: &UserToken,
+        calendar_id: &Option<String>,
+        text: &str,
+        send_updates: Option<SendUpdates>,
+    ) -> Result<CalendarEvent> {
+        let calendar_id = self.resolve_calendar(calendar_id);
+        let url = self.calendar_url(calendar_id, "events/quickAdd")?;
+        let request = self
+            .http
+            .post(url)
+            .bearer_auth(token.access_token())
+            .query(&[("text", text)])
+            .query(&send_updates_query(send_updates));
+        let response = self.send(token, request, false).await?;
+
+        Ok(response.json::<CalendarEvent>().await?)
+    }
+
+    /// Deletes an event, emailing guests according to `send_updates`.
+    pub async fn delete_event(
+        &self,
+        token: &UserToken,
+        calendar_id: &Option<String>,
+        event_id: &str,
+        send_updates: Option<SendUpdates>,
+    ) -> Result<()> {
+        let calendar_id = self.resolve_calendar(calendar_id);
+        let url = self.calendar_url(calendar_id, &format!("events/{event_id}"))?;
+        // A repeated delete after an ambiguous failure would fail with 410 Gone.
+        let request = self
+            .http
+            .delete(url)
+            .bearer_auth(token.access_token())
+            .query(&send_updates_query(send_updates));
+        self.send(token, request, false).await?;
+        Ok(())
+    }
+
+    /// Moves an event to `destination` via `events/move`; the event keeps its id.
+    pub async fn move_event(
+        &self,
+        token: &UserToken,
+        calendar_id: &Option<String>,
+        event_id: &str,
+        destination: &str,
+        send_updates: Option<SendUpdates>,
+    ) -> Result<CalendarEvent> {
+        let calendar_id = self.resolve_calendar(calendar_id);
+        let url = self.calendar_url(calendar_id, &format!("events/{event_id}/move"))?;
+        // A repeated move after an ambiguous failure would 404 on the source calendar.
+        let request = self
+            .http
+            .post(url)
+            .bearer_auth(token.access_token())
+            .query(&[("destination", destination)])
+            .query(&send_updates_query(send_updates));
+        let response = self.send(token, request, false).await?;
 
-        let mut body = serde_json::to_value(patch)?
+        Ok(response.json::<CalendarEvent>().await?)
+    }
+
+    /// The user's `calendarList` entry for `calendar_id`, including their access role.
+    pub async fn calendar_list_entry(
+        &self,
+        token: &UserToken,
+        calendar_id: &str,
+    ) -> Result<CalendarListEntry> {
+        let url = self
+            .api_base
+            .join(&format!(
+                "users/me/calendarList/{}",
+                urlencoding::encode(calendar_id)
+            ))
+            .context("failed to compose calendarList endpoint")?;
+        let request = self.http.get(url).bearer_auth(token.access_token());
+        let response = self.send(token, request, true).await?;
+
+        Ok(response.json::<CalendarListEntry>().await?)
+    }
+
+    /// Computes what `create_event` would send without calling the API, with conflicts on
+    /// `conflict_calendars`.
+    pub async fn dry_run_create(
+        &self,
+        token: &UserToken,
+        request: &EventPayload,
+        conflict_calendars: &[String],
+    ) -> Result<(CalendarEvent, DryRunReport)> {
+        let calendar_id = self.resolve_calendar(&request.calendar_id).to_owned();
+        let body = Self::create_body(request)?;
+        let event: CalendarEvent = serde_json::from_value(serde_json::Value::Object(body.clone()))?;
+
+        let report = DryRunReport {
+            conflicts: self
+                .find_conflicts(token, conflict_calendars, &event)
+                .await?,
+            changes: diff::diff_event(None, request),
+            calendar_id,
+            request_body: serde_json::Value::Object(body),
+        };
+        Ok((event, report))
+    }
+
+    /// Computes what `update_event` would send and the resulting event, without patching it.
+    pub async fn dry_run_update(
+        &self,
+        token: &UserToken,
+        event_id: &str,
+        patch: &EventPayload,
+        conflict_calendars: &[String],
+    ) -> Result<(CalendarEvent, DryRunReport)> {
+        let calendar_id = self.resolve_calendar(&patch.calendar_id).to_owned();
+        let body = Self::patch_body(patch)?;
+        let current = self
+            .get_event(
+                token,
+                &GetEventParams {
+                    event_id: event_id.to_owned(),
+                    calendar_id: Some(calendar_id.clone()),
+                },
+            )
+            .await?;
+
+        // PATCH semantics: top-level fields in the body replace the current ones.
+        let mut merged = serde_json::to_value(&current)?
             .as_object()
             .cloned()
             .unwrap_or_default();
-        body.remove("calendar_id");
+        merged.extend(body.clone());
+        let event: CalendarEvent = serde_json::from_value(serde_json::Value::Object(merged))?;
+        Self::validate_time_range(event.start.as_ref(), event.end.as_ref())?;
 
-        let response = self
-            .http
-            .patch(url)
-            .bearer_auth(&token.access_token)
-            .json(&body)
-            .send()
-            .await?;
+        let report = DryRunReport {
+            conflicts: self
+                .find_conflicts(token, conflict_calendars, &event)
+                .await?,
+            changes: diff::diff_event(Some(&current), patch),
+            calendar_id,
+            request_body: serde_json::Value::Object(body),
+        };
+        Ok((event, report))
+    }
 
-        if !response.status().is_success() {
-            return Err(Self::classify_error(response).await.into());
+    fn create_body(request: &EventPayload) -> Result<serde_json::Map<String, serde_json::Value>> {
+        let body = diff::payload_fields(request);
+        if !body.contains_key("summary") {
+            return Err(
+                GoogleCalendarError::invalid("summary is required to create an event").into(),
+            );
         }
+        if !body.contains_key("start") || !body.contains_key("end") {
+            return Err(GoogleCalendarError::invalid(
+                "start and end dateTimes are required to create an event",
+            )
+            .into());
+        }
+        Self::validate_time_range(request.start.as_ref(), request.end.as_ref())?;
+        event_types::validate(request, true)?;
+        Ok(body)
+    }
 
-        Ok(response.json::<CalendarEvent>().await?)
+    fn patch_body(patch: &EventPayload) -> Result<serde_json::Map<String, serde_json::Value>> {
+        Self::validate_time_range(patch.start.as_ref(), patch.end.as_ref())?;
+        event_types::validate(patch, false)?;
+        Ok(diff::payload_fields(patch))
+    }
+
+    fn validate_time_range(
+        start: Option<&EventDateTime>,
+        end: Option<&EventDateTime>,
+    ) -> Result<()> {
+        for (field, time) in [("start", start), ("end", end)] {
+            if let Some(expression) = time.and_then(|time| time.expression.as_deref()) {
+                return Err(GoogleCalendarError::invalid(format!(
+                    "{field} '{expression}' must be resolved to an absolute time before calling Google"
+                ))
+                .into());
+            }
+        }
+        let (start, end) = (
+            start.cloned().unwrap_or_default(),
+            end.cloned().unwrap_or_default(),
+        );
+        let backwards = match (start.date_time, end.date_time, start.date, end.date) {
+            (Some(start), Some(end), _, _) => end <= start,
+            // All-day events end on the (exclusive) day after their last day.
+            (_, _, Some(start), Some(end)) => end <= start,
+            _ => false,
+        };
+        if backwards {
+            return Err(GoogleCalendarError::invalid("event end must be after its start").into());
+        }
+        Ok(())
     }
 
     /// Google Calendar APIからのエラーレスポンスを分類
+    ///
+    /// `errors[].reason` を優先し、理由コードがない場合のみメッセージ内容で判定する。
     async fn classify_error(response: reqwest::Response) -> GoogleCalendarError {
         let status_code = response.status().as_u16();
+        let retry_after = retry::retry_after(response.headers());
+        let body = response.bytes().await.unwrap_or_default();
+        Self::classify_error_body(status_code, retry_after, &body)
+    }
 
-        // Google API error JSONをパース
-        let error_body = match response.json::<GoogleApiError>().await {
+    /// Classifies an error from its status and JSON body; shared with batch item responses.
+    fn classify_error_body(
+        status_code: u16,
+        retry_after: Option<Duration>,
+        body: &[u8],
+    ) -> GoogleCalendarError {
+        // Google API error JSONをパース（失敗時はステータスコードのみで分類）
+        let detail = match serde_json::from_slice::<GoogleApiError>(body) {
             Ok(api_error) => api_error.error,
-            Err(_) => {
-                // JSONパース失敗時はフォールバック
-                return GoogleCalendarError::Other {
-                    message: "Unknown Google API error".to_string(),
-                    status_code,
-                };
-            }
+            Err(_) => GoogleApiErrorDetail {
+                message: "Unknown Google API error".to_string(),
+                errors: Vec::new(),
+            },
         };
+        let reason = detail.reason().map(str::to_owned);
+        let message = detail.message;
 
-        // ステータスコードとメッセージ内容でエラー分類
         match status_code {
-            403 if error_body.message.to_lowercase().contains("insufficient")
-                || error_body.message.to_lowercase().contains("scope")
-                || error_body.status == "PERMISSION_DENIED" =>
-            {
-                GoogleCalendarError::InsufficientScope {
-                    message: error_body.message,
+            400 => GoogleCalendarError::InvalidRequest { message, reason },
+            401 => GoogleCalendarError::Unauthorized { message, reason },
+            // レート制限の403もPERMISSION_DENIEDを返すため、権限判定より先に理由コードを確認する
+            429 => GoogleCalendarError::RateLimited {
+                message,
+                reason,
+                retry_after,
+            },
+            403 if reason.as_deref().is_some_and(is_rate_limit_reason) => {
+                GoogleCalendarError::RateLimited {
+                    message,
+                    reason,
+                    retry_after,
                 }
             }
-            401 => GoogleCalendarError::Unauthorized {
-                message: error_body.message,
+            // 理由コードが無い場合に限りメッセージから判定する
+            403 if reason.as_deref() == Some("insufficientPermissions")
+                || (reason.is_none()
+                    && (message.to_lowercase().contains("insufficient")
+                        || message.to_lowercase().contains("scope"))) =>
+            {
+                GoogleCalendarError::InsufficientScope { message, reason }
+            }
+            403 => GoogleCalendarError::Forbidden { message, reason },
+            404 => GoogleCalendarError::NotFound { message, reason },
+            409 => GoogleCalendarError::Conflict { message, reason },
+            410 => GoogleCalendarError::Gone { message, reason },
+            412 => GoogleCalendarError::PreconditionFailed {
+                message,
+                current: None,
             },
-            404 => GoogleCalendarError::NotFound {
-                message: error_body.message,
+            500..=599 => GoogleCalendarError::Transient {
+                message,
+                status_code: Some(status_code),
             },
             _ => GoogleCalendarError::Other {
-                message: error_body.message,
+                message,
+                reason,
                 status_code,
             },
         }
     }
 }
 
+/// Derives a Google event id from a client idempotency key.
+///
+/// Event ids must use base32hex characters (`0-9`, `a-v`) and be 5-1024 long, so the key is
+/// hashed together with the user id and encoded as 52 base32hex characters.
+pub fn idempotent_event_id(user_id: &str, idempotency_key: &str) -> String {
+    const ALPHABET: &[u8; 32] = b"0123456789abcdefghijklmnopqrstuv";
+
+    let digest = Sha256::new()
+        .chain_update(user_id.as_bytes())
+        .chain_update([0u8])
+        .chain_update(idempotency_key.as_bytes())
+        .finalize();
+
+    let mut encoded = String::with_capacity(52);
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for byte in digest {
+        buffer = (buffer << 8) | u32::from(byte);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            encoded.push(ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        encoded.push(ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+    }
+    encoded
+}
+
 /// Google API error response structure
 #[derive(Debug, Deserialize)]
 struct GoogleApiError {
@@ -237,47 +833,188 @@ struct GoogleApiError {
 
 #[derive(Debug, Deserialize)]
 struct GoogleApiErrorDetail {
-    code: u16,
     message: String,
-    status: String,
+    #[serde(default)]
+    errors: Vec<GoogleApiErrorItem>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GoogleApiErrorItem {
+    #[serde(default)]
+    reason: String,
+}
+
+impl GoogleApiErrorDetail {
+    /// The first non-empty `errors[].reason`, e.g. `rateLimitExceeded` or `timeRangeEmpty`.
+    fn reason(&self) -> Option<&str> {
+        self.errors
+            .iter()
+            .map(|item| item.reason.as_str())
+            .find(|reason| !reason.is_empty())
+    }
+}
+
+/// Quota errors arrive as 403s, distinguishable from permission errors only by reason.
+fn is_rate_limit_reason(reason: &str) -> bool {
+    matches!(
+        reason,
+        "rateLimitExceeded" | "userRateLimitExceeded" | "quotaExceeded" | "dailyLimitExceeded"
+    )
 }
 
 /// 分類されたGoogle Calendar APIエラー
-#[derive(Debug)]
+///
+/// `reason` carries Google's `errors[].reason` code when the response included one.
+#[derive(Debug, thiserror::Error)]
 pub enum GoogleCalendarError {
-    InsufficientScope { message: String },
-    Unauthorized { message: String },
-    NotFound { message: String },
-    Other { message: String, status_code: u16 },
+    /// The request was rejected as malformed, either locally or by Google (400).
+    #[error("invalid_request: {message}")]
+    InvalidRequest {
+        message: String,
+        reason: Option<String>,
+    },
+    #[error("unauthorized: {message}")]
+    Unauthorized {
+        message: String,
+        reason: Option<String>,
+    },
+    /// The OAuth token lacks a scope the operation needs.
+    #[error("insufficient_scope: {message}")]
+    InsufficientScope {
+        message: String,
+        reason: Option<String>,
+    },
+    /// The user lacks access to the calendar or event, e.g. `requiredAccessLevel`.
+    #[error("forbidden: {message}")]
+    Forbidden {
+        message: String,
+        reason: Option<String>,
+    },
+    #[error("not_found: {message}")]
+    NotFound {
+        message: String,
+        reason: Option<String>,
+    },
+    /// The resource already exists, e.g. an insert with a reused event id.
+    #[error("conflict: {message}")]
+    Conflict {
+        message: String,
+        reason: Option<String>,
+    },
+    /// The event was deleted, or a sync token expired (`fullSyncRequired`).
+    #[error("gone: {message}")]
+    Gone {
+        message: String,
+        reason: Option<String>,
+    },
+    /// `If-Match` did not match; carries the event as it currently exists on the server.
+    #[error("precondition_failed: {message}")]
+    PreconditionFailed {
+        message: String,
+        current: Option<Box<CalendarEvent>>,
+    },
+    /// 429 or a quota 403; `retry_after` is the server's or the local limiter's requested pause.
+    #[error("rate_limited: {message}")]
+    RateLimited {
+        message: String,
+        reason: Option<String>,
+        retry_after: Option<Duration>,
+    },
+    /// 5xx responses, timeouts and connection failures that outlasted the retries.
+    #[error("transient: {message}")]
+    Transient {
+        message: String,
+        status_code: Option<u16>,
+    },
+    #[error("google_api_error ({status_code}): {message}")]
+    Other {
+        message: String,
+        reason: Option<String>,
+        status_code: u16,
+    },
 }
 
-impl From<GoogleCalendarError> for anyhow::Error {
-    fn from(err: GoogleCalendarError) -> Self {
-        match err {
-            GoogleCalendarError::InsufficientScope { message } => {
-                anyhow!("insufficient_scope: {}", message)
-            }
-            GoogleCalendarError::Unauthorized { message } => {
-                anyhow!("unauthorized: {}", message)
-            }
-            GoogleCalendarError::NotFound { message } => {
-                anyhow!("not_found: {}", message)
-            }
-            GoogleCalendarError::Other {
-                message,
-                status_code,
-            } => {
-                anyhow!("google_api_error ({}): {}", status_code, message)
-            }
+impl GoogleCalendarError {
+    fn invalid(message: impl Into<String>) -> Self {
+        Self::InvalidRequest {
+            message: message.into(),
+            reason: None,
         }
     }
+
+    /// Stable identifier for this kind of failure.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::InvalidRequest { .. } => "invalid_request",
+            Self::Unauthorized { .. } => "unauthorized",
+            Self::InsufficientScope { .. } => "insufficient_scope",
+            Self::Forbidden { .. } => "forbidden",
+            Self::NotFound { .. } => "not_found",
+            Self::Conflict { .. } => "conflict",
+            Self::Gone { .. } => "gone",
+            Self::PreconditionFailed { .. } => "precondition_failed",
+            Self::RateLimited { .. } => "rate_limited",
+            Self::Transient { .. } => "transient",
+            Self::Other { .. } => "google_api_error",
+        }
+    }
+
+    /// Google's `errors[].reason`, when known.
+    pub fn reason(&self) -> Option<&str> {
+        match self {
+            Self::InvalidRequest { reason, .. }
+            | Self::Unauthorized { reason, .. }
+            | Self::InsufficientScope { reason, .. }
+            | Self::Forbidden { reason, .. }
+            | Self::NotFound { reason, .. }
+            | Self::Conflict { reason, .. }
+            | Self::Gone { reason, .. }
+            | Self::RateLimited { reason, .. }
+            | Self::Other { reason, .. } => reason.as_deref(),
+            _ => None,
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            Self::InvalidRequest { message, .. }
+            | Self::Unauthorized { message, .. }
+            | Self::InsufficientScope { message, .. }
+            | Self::Forbidden { message, .. }
+            | Self::NotFound { message, .. }
+            | Self::Conflict { message, .. }
+            | Self::Gone { message, .. }
+            | Self::PreconditionFailed { message, .. }
+            | Self::RateLimited { message, .. }
+            | Self::Transient { message, .. }
+            | Self::Other { message, .. } => message,
+        }
+    }
+
+    /// Whether the same request may succeed if repeated later.
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, Self::RateLimited { .. } | Self::Transient { .. })
+    }
+}
+
+/// What a create/update would do, computed without a mutating API call.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct DryRunReport {
+    /// Calendar the write would target after applying defaults.
+    pub calendar_id: String,
+    /// Exact JSON body that would be sent to Google.
+    pub request_body: serde_json::Value,
+    /// Fields that differ from the current event (all fields for a create).
+    pub changes: Vec<diff::FieldChange>,
+    /// Busy events overlapping the resulting time range.
+    pub conflicts: Vec<conflicts::Conflict>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
 pub struct ListEventsParams {
     pub calendar_id: Option<String>,
-    pub time_min: Option<DateTime<Utc>>,
-    pub time_max: Option<DateTime<Utc>>,
+    pub time_min: Option<TimeInput>,
+    pub time_max: Option<TimeInput>,
     pub max_results: Option<u32>,
     pub page_token: Option<String>,
     pub query: Option<String>,
@@ -285,6 +1022,12 @@ pub struct ListEventsParams {
     pub single_events: bool,
     #[serde(default)]
     pub order_by_start_time: bool,
+    /// Event fields to return, in Google partial-response syntax (e.g. "id,summary,start,end").
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fields: Option<String>,
+    /// Only return events of these types (e.g. ["outOfOffice", "focusTime"]).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub event_types: Vec<EventType>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
@@ -321,17 +1064,17 @@ pub struct EventPayload {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub location: Option<String>,
 
-    /// Start date-time in RFC3339 format (e.g., "2025-10-15T18:30:00+09:00")
+    /// Start date-time in RFC3339 format or as a relative expression (e.g., "tomorrow 3pm")
     #[serde(skip_serializing_if = "Option::is_none")]
     #[schemars(
-        description = "Start date-time as RFC3339 string (e.g., \"2025-10-15T18:30:00+09:00\")"
+        description = "Start as RFC3339 string (e.g., \"2025-10-15T18:30:00+09:00\") or a relative expression in the user's time zone (e.g., \"tomorrow 3pm\")"
     )]
     pub start: Option<EventDateTime>,
 
-    /// End date-time in RFC3339 format (e.g., "2025-10-15T20:30:00+09:00")
+    /// End date-time in RFC3339 format or as a relative expression (e.g., "tomorrow 4pm")
     #[serde(skip_serializing_if = "Option::is_none")]
     #[schemars(
-        description = "End date-time as RFC3339 string (e.g., \"2025-10-15T20:30:00+09:00\")"
+        description = "End as RFC3339 string (e.g., \"2025-10-15T20:30:00+09:00\") or a relative expression in the user's time zone (e.g., \"tomorrow 4pm\")"
     )]
     pub end: Option<EventDateTime>,
 
@@ -345,15 +1088,169 @@ pub struct EventPayload {
 
     /// Conference data (e.g., Google Meet details)
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub conference_data: Option<serde_json::Value>,
+    pub conference_data: Option<ConferenceData>,
+
+    /// Event color id ("1"-"11", see `google_calendar_list_colors`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color_id: Option<String>,
+
+    /// Who can see the event details
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub visibility: Option<EventVisibility>,
+
+    /// Whether the event blocks time ("opaque") or shows as free ("transparent")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transparency: Option<Transparency>,
+
+    /// Whether attendees other than the organizer may modify the event
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub guests_can_modify: Option<bool>,
+
+    /// Whether attendees may invite others
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub guests_can_invite_others: Option<bool>,
+
+    /// Whether attendees may see the guest list
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub guests_can_see_other_guests: Option<bool>,
+
+    /// Private (per-copy) and shared key/value properties
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extended_properties: Option<ExtendedProperties>,
+
+    /// Where the event was created from, e.g. a web page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<EventSource>,
+
+    /// Google Drive file attachments
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attachments: Option<Vec<EventAttachment>>,
+
+    /// Event type; can only be set when the event is created
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event_type: Option<EventType>,
+
+    /// Settings of an "outOfOffice" event
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub out_of_office_properties: Option<OutOfOfficeProperties>,
+
+    /// Settings of a "focusTime" event
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub focus_time_properties: Option<FocusTimeProperties>,
+
+    /// Settings of a "workingLocation" event
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub working_location_properties: Option<WorkingLocationProperties>,
+
+    /// Other event fields, in Google's names (e.g. "recurrence"), carried over from an existing
+    /// event by [`EventPayload::from_event`]. Never read from tool input.
+    #[serde(flatten, skip_deserializing)]
+    #[schemars(skip)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+
+    /// Create a Google Meet conference for the event; its join URL is returned as `join_url`
+    #[serde(default, skip_serializing)]
+    pub add_meet_link: bool,
+
+    /// Who is emailed about the change: "all", "externalOnly" or "none" (server default if unset)
+    #[serde(default, skip_serializing)]
+    pub send_updates: Option<SendUpdates>,
+}
+
+impl EventPayload {
+    /// The writable fields of an existing event, e.g. to re-create a parsed quickAdd event.
+    pub fn from_event(calendar_id: Option<String>, event: &CalendarEvent) -> Self {
+        Self {
+            calendar_id,
+            summary: event.summary.clone(),
+            description: event.description.clone(),
+            location: event.location.clone(),
+            start: event.start.clone(),
+            end: event.end.clone(),
+            attendees: event.attendees.clone(),
+            reminders: event.reminders.clone(),
+            conference_data: None,
+            color_id: event.color_id.clone(),
+            visibility: event.visibility,
+            transparency: event.transparency,
+            guests_can_modify: event.guests_can_modify,
+            guests_can_invite_others: event.guests_can_invite_others,
+            guests_can_see_other_guests: event.guests_can_see_other_guests,
+            extended_properties: event.extended_properties.clone(),
+            source: event.source.clone(),
+            attachments: event.attachments.clone(),
+            event_type: event.event_type.filter(|kind| *kind != EventType::Other),
+            out_of_office_properties: event.out_of_office_properties.clone(),
+            focus_time_properties: event.focus_time_properties.clone(),
+            working_location_properties: event.working_location_properties.clone(),
+            extra: event
+                .extra
+                .iter()
+                .filter(|(field, _)| !READ_ONLY_EVENT_FIELDS.contains(&field.as_str()))
+                .map(|(field, value)| (field.clone(), value.clone()))
+                .collect(),
+            add_meet_link: false,
+            send_updates: None,
+        }
+    }
+
+    /// Resolves relative `start`/`end` expressions.
+    pub fn resolve_times(&mut self, resolver: &mut TimeResolver) -> Result<(), TimeExprError> {
+        resolver.event_time("start", &mut self.start)?;
+        resolver.event_time("end", &mut self.end)
+    }
+
+    pub fn has_time_expressions(&self) -> bool {
+        [&self.start, &self.end]
+            .into_iter()
+            .flatten()
+            .any(|time| time.expression.is_some())
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct EventDateTime {
-    #[serde(rename = "dateTime", skip_serializing_if = "Option::is_none")]
     pub date_time: Option<DateTime<Utc>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Day of an all-day event; set instead of `date_time`.
+    pub date: Option<NaiveDate>,
     pub time_zone: Option<String>,
+    /// Relative expression given instead of RFC3339, pending a [`TimeResolver`].
+    pub expression: Option<String>,
+    /// Zone `dateTime` is rendered in; UTC when unset. Not sent separately to Google.
+    pub display_zone: Option<Tz>,
+}
+
+impl EventDateTime {
+    /// `date_time` as an offset time in `display_zone`.
+    pub fn display(&self) -> Option<DateTime<FixedOffset>> {
+        let date_time = self.date_time?;
+        Some(match self.display_zone {
+            Some(tz) => date_time.with_timezone(&tz).fixed_offset(),
+            None => date_time.fixed_offset(),
+        })
+    }
+}
+
+impl Serialize for EventDateTime {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("EventDateTime", 3)?;
+        match self.display() {
+            Some(date_time) if self.display_zone.is_some() => {
+                state.serialize_field("dateTime", &date_time)?
+            }
+            Some(_) => state.serialize_field("dateTime", &self.date_time)?,
+            None => state.skip_field("dateTime")?,
+        }
+        match &self.date {
+            Some(date) => state.serialize_field("date", date)?,
+            None => state.skip_field("date")?,
+        }
+        match &self.time_zone {
+            Some(time_zone) => state.serialize_field("timeZone", time_zone)?,
+            None => state.skip_field("timeZone")?,
+        }
+        state.end()
+    }
 }
 
 impl JsonSchema for EventDateTime {
@@ -370,7 +1267,7 @@ impl JsonSchema for EventDateTime {
             object.insert(
                 "description".to_string(),
                 serde_json::Value::String(
-                    "RFC3339 date-time string (e.g., \"2025-10-15T18:30:00+09:00\")".to_string(),
+                    "RFC3339 date-time string (e.g., \"2025-10-15T18:30:00+09:00\") or a relative expression such as \"tomorrow 3pm\"".to_string(),
                 ),
             );
         }
@@ -395,42 +1292,57 @@ impl<'de> Deserialize<'de> for EventDateTime {
             #[serde(rename = "dateTime")]
             date_time: Option<DateTime<Utc>>,
             #[serde(default)]
+            date: Option<NaiveDate>,
+            #[serde(default, rename = "timeZone", alias = "time_zone")]
             time_zone: Option<String>,
         }
 
         let repr = Repr::deserialize(deserializer)?;
         match repr {
-            Repr::String(value) => {
-                let parsed = DateTime::parse_from_rfc3339(&value).map_err(|err| {
-                    de::Error::custom(format!(
-                        "failed to parse RFC3339 date-time string '{value}': {err}"
-                    ))
-                })?;
-                Ok(EventDateTime {
+            Repr::String(value) => match DateTime::parse_from_rfc3339(value.trim()) {
+                Ok(parsed) => Ok(EventDateTime {
                     date_time: Some(parsed.with_timezone(&Utc)),
-                    time_zone: None,
-                })
-            }
+                    ..Default::default()
+                }),
+                Err(_) if value.trim().is_empty() => {
+                    Err(de::Error::custom("date-time string must not be empty"))
+                }
+                Err(_) => Ok(EventDateTime {
+                    expression: Some(value),
+                    ..Default::default()
+                }),
+            },
             Repr::Object(object) => Ok(EventDateTime {
                 date_time: object.date_time,
+                date: object.date,
                 time_zone: object.time_zone,
+                ..Default::default()
             }),
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct EventAttendee {
     pub email: String,
     #[serde(default)]
     pub optional: bool,
     #[serde(rename = "responseStatus", skip_serializing_if = "Option::is_none")]
     pub response_status: Option<String>,
+    /// Note added to the RSVP.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    /// Whether this entry is the authorized user.
+    #[serde(rename = "self", default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_self: bool,
+    /// Other attendee fields (e.g. `displayName`), kept so that rewriting the list loses nothing.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct EventReminders {
-    #[serde(default)]
+    #[serde(default, rename = "useDefault", alias = "use_default")]
     pub use_default: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub overrides: Option<Vec<ReminderOverride>>,
@@ -442,9 +1354,121 @@ pub struct ReminderOverride {
     pub minutes: i64,
 }
 
+/// Most attachments Google keeps on one event.
+pub const MAX_EVENT_ATTACHMENTS: usize = 25;
+
+/// Unknown event fields that Google sets itself and rejects or misreads on writes.
+const READ_ONLY_EVENT_FIELDS: &[&str] = &[
+    "kind",
+    "iCalUID",
+    "sequence",
+    "recurringEventId",
+    "originalStartTime",
+    "privateCopy",
+    "locked",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum EventVisibility {
+    /// The calendar's default visibility.
+    Default,
+    Public,
+    Private,
+    /// Same as `private`; kept for compatibility.
+    Confidential,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum Transparency {
+    /// Busy: the event blocks time.
+    Opaque,
+    /// Free: the event does not block time.
+    Transparent,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum EventType {
+    Default,
+    Birthday,
+    FocusTime,
+    FromGmail,
+    OutOfOffice,
+    WorkingLocation,
+    /// A type this server does not know yet; never sent back to Google.
+    #[serde(other)]
+    Other,
+}
+
+impl EventType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Default => "default",
+            Self::Birthday => "birthday",
+            Self::FocusTime => "focusTime",
+            Self::FromGmail => "fromGmail",
+            Self::OutOfOffice => "outOfOffice",
+            Self::WorkingLocation => "workingLocation",
+            Self::Other => "other",
+        }
+    }
+}
+
+/// The organizer or creator of an event.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct EventPerson {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    /// Whether this is the authorized user.
+    #[serde(rename = "self", default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_self: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ExtendedProperties {
+    /// Visible only on this attendee's copy of the event.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub private: HashMap<String, String>,
+    /// Visible on every attendee's copy.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub shared: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct EventSource {
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct EventAttachment {
+    /// Link to the file; required when adding an attachment.
+    pub file_url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon_link: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_id: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct CalendarEvent {
     pub id: Option<String>,
+    /// Version tag; pass back as `if_match_etag` to reject the update if the event changed.
+    #[serde(default)]
+    pub etag: Option<String>,
     pub status: Option<String>,
     pub summary: Option<String>,
     pub description: Option<String>,
@@ -455,10 +1479,220 @@ pub struct CalendarEvent {
     pub reminders: Option<EventReminders>,
     #[serde(rename = "htmlLink")]
     pub html_link: Option<String>,
+    #[serde(
+        rename = "conferenceData",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub conference_data: Option<ConferenceData>,
+    #[serde(rename = "colorId", default, skip_serializing_if = "Option::is_none")]
+    pub color_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub visibility: Option<EventVisibility>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transparency: Option<Transparency>,
+    #[serde(
+        rename = "guestsCanModify",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub guests_can_modify: Option<bool>,
+    #[serde(
+        rename = "guestsCanInviteOthers",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub guests_can_invite_others: Option<bool>,
+    #[serde(
+        rename = "guestsCanSeeOtherGuests",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub guests_can_see_other_guests: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub organizer: Option<EventPerson>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub creator: Option<EventPerson>,
+    #[serde(
+        rename = "extendedProperties",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub extended_properties: Option<ExtendedProperties>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<EventSource>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attachments: Option<Vec<EventAttachment>>,
+    #[serde(rename = "eventType", default, skip_serializing_if = "Option::is_none")]
+    pub event_type: Option<EventType>,
+    #[serde(
+        rename = "outOfOfficeProperties",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub out_of_office_properties: Option<OutOfOfficeProperties>,
+    #[serde(
+        rename = "focusTimeProperties",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub focus_time_properties: Option<FocusTimeProperties>,
+    #[serde(
+        rename = "workingLocationProperties",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub working_location_properties: Option<WorkingLocationProperties>,
+    /// Link to the event's classic Hangouts / Meet call, if any.
+    #[serde(
+        rename = "hangoutLink",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub hangout_link: Option<String>,
     #[serde(rename = "created")]
     pub created_at: Option<DateTime<Utc>>,
     #[serde(rename = "updated")]
     pub updated_at: Option<DateTime<Utc>>,
+    /// Fields without a typed counterpart (e.g. `recurrence`), kept so that nothing is lost.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl CalendarEvent {
+    /// The attendee list with the authorized user's entry set to `response`.
+    ///
+    /// Returns `None` when the user is not on the guest list.
+    pub fn with_response(
+        &self,
+        response: RsvpResponse,
+        comment: Option<String>,
+    ) -> Option<Vec<EventAttendee>> {
+        let mut attendees = self.attendees.clone()?;
+        let entry = attendees.iter_mut().find(|attendee| attendee.is_self)?;
+        entry.response_status = Some(response.as_str().to_owned());
+        if comment.is_some() {
+            entry.comment = comment;
+        }
+        Some(attendees)
+    }
+
+    /// The attachment list with `attachment` appended after the existing ones.
+    ///
+    /// Returns `None` when a file with the same URL is already attached.
+    pub fn with_attachment(&self, attachment: EventAttachment) -> Option<Vec<EventAttachment>> {
+        let mut attachments = self.attachments.clone().unwrap_or_default();
+        if attachments
+            .iter()
+            .any(|existing| existing.file_url == attachment.file_url)
+        {
+            return None;
+        }
+        attachments.push(attachment);
+        Some(attachments)
+    }
+
+    /// Renders `start`/`end` in `tz` for tool output; the instants are unchanged.
+    pub fn localize(&mut self, tz: Tz) {
+        for time in [self.start.as_mut(), self.end.as_mut()]
+            .into_iter()
+            .flatten()
+        {
+            time.display_zone = Some(tz);
+        }
+    }
+}
+
+/// Minimal view of an event for listing large calendars within a context budget.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct CompactEvent {
+    pub id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start: Option<CompactTime>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end: Option<CompactTime>,
+    pub attendee_count: usize,
+}
+
+/// An offset time, or the day of an all-day event.
+#[derive(Debug, Clone, PartialEq, Serialize, JsonSchema)]
+#[serde(untagged)]
+pub enum CompactTime {
+    DateTime(DateTime<FixedOffset>),
+    Date(NaiveDate),
+}
+
+impl CompactTime {
+    fn from_event_time(time: EventDateTime) -> Option<Self> {
+        match time.display() {
+            Some(date_time) => Some(Self::DateTime(date_time)),
+            None => time.date.map(Self::Date),
+        }
+    }
+}
+
+impl CompactEvent {
+    /// Fields to request from Google when only the compact view is needed.
+    pub const FIELDS: &'static str = "id,summary,start,end,attendees(email)";
+}
+
+impl From<CalendarEvent> for CompactEvent {
+    fn from(event: CalendarEvent) -> Self {
+        Self {
+            id: event.id,
+            summary: event.summary,
+            start: event.start.and_then(CompactTime::from_event_time),
+            end: event.end.and_then(CompactTime::from_event_time),
+            attendee_count: event.attendees.map_or(0, |attendees| attendees.len()),
+        }
+    }
+}
+
+/// An answer to an invitation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RsvpResponse {
+    Accepted,
+    Declined,
+    Tentative,
+}
+
+impl RsvpResponse {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Accepted => "accepted",
+            Self::Declined => "declined",
+            Self::Tentative => "tentative",
+        }
+    }
+}
+
+/// A calendar as it appears in the user's calendar list.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CalendarListEntry {
+    pub id: String,
+    #[serde(default)]
+    pub summary: Option<String>,
+    /// `freeBusyReader`, `reader`, `writer` or `owner`.
+    #[serde(default)]
+    pub access_role: Option<String>,
+    #[serde(default)]
+    pub primary: bool,
+    /// Whether the calendar is shown in the user's calendar UI.
+    #[serde(default)]
+    pub selected: bool,
+    #[serde(default)]
+    pub time_zone: Option<String>,
+}
+
+impl CalendarListEntry {
+    /// Whether the user may create and modify events on this calendar.
+    pub fn can_write(&self) -> bool {
+        matches!(self.access_role.as_deref(), Some("writer" | "owner"))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
@@ -472,6 +1706,24 @@ pub struct GetEventParams {
 mod tests {
     use super::*;
 
+    #[test]
+    fn payload_input_ignores_unknown_fields() {
+        let payload: EventPayload = serde_json::from_value(serde_json::json!({
+            "summary": "Standup",
+            "recurrence": ["RRULE:FREQ=DAILY"],
+            "iCalUID": "spoofed@example.com"
+        }))
+        .unwrap();
+        assert!(payload.extra.is_empty());
+        assert_eq!(
+            serde_json::to_value(&payload).unwrap(),
+            serde_json::json!({"summary": "Standup"})
+        );
+
+        let schema = serde_json::to_value(schemars::schema_for!(EventPayload)).unwrap();
+        assert_ne!(schema["additionalProperties"], true);
+    }
+
     #[test]
     fn payload_serialization_strips_none() {
         let payload = EventPayload {
@@ -484,6 +1736,102 @@ mod tests {
         assert!(json.get("location").is_none());
     }
 
+    #[test]
+    fn create_body_rejects_end_before_start() {
+        let payload: EventPayload = serde_json::from_value(serde_json::json!({
+            "summary": "Backwards",
+            "start": "2025-10-14T12:00:00Z",
+            "end": "2025-10-14T11:00:00Z"
+        }))
+        .unwrap();
+
+        assert!(GoogleCalendarClient::create_body(&payload).is_err());
+    }
+
+    #[test]
+    fn idempotent_event_id_is_stable_base32hex() {
+        let first = idempotent_event_id("user", "retry-key");
+        assert_eq!(first, idempotent_event_id("user", "retry-key"));
+        assert_ne!(first, idempotent_event_id("other-user", "retry-key"));
+        assert_eq!(first.len(), 52);
+        assert!(first
+            .chars()
+            .all(|c| c.is_ascii_digit() || ('a'..='v').contains(&c)));
+    }
+
+    #[test]
+    fn quota_errors_are_not_scope_errors() {
+        let body: GoogleApiError = serde_json::from_value(serde_json::json!({
+            "error": {
+                "code": 403,
+                "message": "Rate Limit Exceeded: quota exhausted",
+                "status": "PERMISSION_DENIED",
+                "errors": [{ "domain": "usageLimits", "reason": "userRateLimitExceeded" }]
+            }
+        }))
+        .unwrap();
+        assert!(body.error.reason().is_some_and(is_rate_limit_reason));
+    }
+
+    #[test]
+    fn reason_codes_take_precedence_over_the_message() {
+        let body = |code: u16, message: &str, reason: &str| {
+            serde_json::json!({
+                "error": {
+                    "code": code,
+                    "message": message,
+                    "errors": [{ "domain": "global", "reason": reason }]
+                }
+            })
+            .to_string()
+        };
+
+        let access = body(
+            403,
+            "Insufficient access to this calendar",
+            "requiredAccessLevel",
+        );
+        let err = GoogleCalendarClient::classify_error_body(403, None, access.as_bytes());
+        assert!(matches!(err, GoogleCalendarError::Forbidden { .. }));
+        assert_eq!(err.reason(), Some("requiredAccessLevel"));
+
+        let scope = body(
+            403,
+            "Request had insufficient authentication scopes.",
+            "insufficientPermissions",
+        );
+        let err = GoogleCalendarClient::classify_error_body(403, None, scope.as_bytes());
+        assert!(matches!(err, GoogleCalendarError::InsufficientScope { .. }));
+        assert_eq!(err.reason(), Some("insufficientPermissions"));
+
+        let missing = body(404, "Not Found", "notFound");
+        let err = GoogleCalendarClient::classify_error_body(404, None, missing.as_bytes());
+        assert_eq!(err.reason(), Some("notFound"));
+
+        let expired = body(401, "Invalid Credentials", "authError");
+        let err = GoogleCalendarClient::classify_error_body(401, None, expired.as_bytes());
+        assert_eq!(err.reason(), Some("authError"));
+    }
+
+    #[test]
+    fn failed_precondition_is_classified() {
+        let body = serde_json::json!({
+            "error": {
+                "code": 412,
+                "message": "Precondition Failed",
+                "status": "FAILED_PRECONDITION",
+                "errors": [{ "domain": "global", "reason": "conditionNotMet" }]
+            }
+        });
+        let err = GoogleCalendarClient::classify_error_body(412, None, body.to_string().as_bytes());
+        assert!(matches!(
+            err,
+            GoogleCalendarError::PreconditionFailed { current: None, .. }
+        ));
+        assert_eq!(err.code(), "precondition_failed");
+        assert!(!err.is_retryable());
+    }
+
     #[test]
     fn event_date_time_accepts_rfc3339_string() {
         let json = "\"2025-10-14T12:34:56Z\"";
@@ -494,4 +1842,228 @@ mod tests {
         );
         assert!(parsed.time_zone.is_none());
     }
+
+    #[test]
+    fn event_date_time_keeps_relative_expressions_for_resolution() {
+        let mut payload: EventPayload =
+            serde_json::from_str(r#"{"start":"tomorrow 3pm","end":"tomorrow 4pm"}"#).unwrap();
+        assert!(payload.has_time_expressions());
+        assert!(GoogleCalendarClient::patch_body(&payload).is_err());
+
+        let now = DateTime::parse_from_rfc3339("2025-11-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let mut resolver = TimeResolver::new(now, chrono_tz::America::New_York);
+        payload.resolve_times(&mut resolver).unwrap();
+
+        let body = GoogleCalendarClient::patch_body(&payload).unwrap();
+        assert_eq!(body["start"]["dateTime"], "2025-11-02T20:00:00Z");
+        assert_eq!(body["start"]["timeZone"], "America/New_York");
+        assert_eq!(resolver.into_resolved().unwrap().fields.len(), 2);
+    }
+
+    #[test]
+    fn localized_event_renders_offset_times() {
+        let mut event: CalendarEvent = serde_json::from_str(
+            r#"{"id":"evt","start":{"dateTime":"2025-10-20T01:00:00Z","timeZone":"UTC"}}"#,
+        )
+        .unwrap();
+        event.localize(chrono_tz::Asia::Tokyo);
+
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["start"]["dateTime"], "2025-10-20T10:00:00+09:00");
+        assert_eq!(json["start"]["timeZone"], "UTC");
+        let compact = serde_json::to_value(CompactEvent::from(event)).unwrap();
+        assert_eq!(compact["start"], "2025-10-20T10:00:00+09:00");
+    }
+
+    #[test]
+    fn compact_all_day_event_keeps_its_dates() {
+        let event: CalendarEvent = serde_json::from_str(
+            r#"{"id":"offsite","start":{"date":"2025-10-20"},"end":{"date":"2025-10-22"}}"#,
+        )
+        .unwrap();
+
+        let compact = CompactEvent::from(event);
+        let day = |value| NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap();
+        assert_eq!(compact.start, Some(CompactTime::Date(day("2025-10-20"))));
+        let json = serde_json::to_value(&compact).unwrap();
+        assert_eq!(json["start"], "2025-10-20");
+        assert_eq!(json["end"], "2025-10-22");
+    }
+
+    #[test]
+    fn calendar_list_entry_requires_writer_role() {
+        let entry: CalendarListEntry = serde_json::from_str(
+            r#"{"id":"team@group.calendar.google.com","accessRole":"reader"}"#,
+        )
+        .unwrap();
+        assert!(!entry.can_write());
+
+        let owner = CalendarListEntry {
+            access_role: Some("owner".into()),
+            ..entry
+        };
+        assert!(owner.can_write());
+    }
+
+    #[test]
+    fn event_fields_round_trip_into_payload() {
+        let event: CalendarEvent = serde_json::from_value(serde_json::json!({
+            "id": "evt",
+            "kind": "calendar#event",
+            "iCalUID": "evt@google.com",
+            "colorId": "5",
+            "visibility": "private",
+            "transparency": "transparent",
+            "guestsCanModify": true,
+            "organizer": {"email": "me@example.com", "self": true},
+            "extendedProperties": {"private": {"ticket": "OPS-1"}},
+            "eventType": "someFutureType",
+            "reminders": {"useDefault": true},
+            "recurrence": ["RRULE:FREQ=WEEKLY"],
+            "anyoneCanAddSelf": true
+        }))
+        .unwrap();
+        assert_eq!(event.visibility, Some(EventVisibility::Private));
+        assert_eq!(event.event_type, Some(EventType::Other));
+        assert!(event.organizer.as_ref().unwrap().is_self);
+        assert!(event.reminders.as_ref().unwrap().use_default);
+        assert_eq!(event.extra["recurrence"][0], "RRULE:FREQ=WEEKLY");
+
+        let body = diff::payload_fields(&EventPayload::from_event(None, &event));
+        assert_eq!(body["colorId"], "5");
+        assert_eq!(body["transparency"], "transparent");
+        assert_eq!(body["guestsCanModify"], true);
+        assert_eq!(body["extendedProperties"]["private"]["ticket"], "OPS-1");
+        assert_eq!(body["reminders"]["useDefault"], true);
+        assert_eq!(body["anyoneCanAddSelf"], true);
+        assert!(body.contains_key("recurrence"));
+        for read_only in ["kind", "iCalUID", "eventType", "organizer"] {
+            assert!(!body.contains_key(read_only), "{read_only}");
+        }
+    }
+
+    #[test]
+    fn attachments_are_appended_once() {
+        let event: CalendarEvent = serde_json::from_str(
+            r#"{"id":"evt","attachments":[
+                {"fileUrl":"https://drive.google.com/file/d/agenda","title":"Agenda","fileId":"agenda"}
+            ]}"#,
+        )
+        .unwrap();
+        let notes = EventAttachment {
+            file_url: "https://docs.google.com/document/d/notes".into(),
+            title: Some("Notes".into()),
+            mime_type: None,
+            icon_link: None,
+            file_id: None,
+        };
+
+        let attachments = event.with_attachment(notes.clone()).unwrap();
+        assert_eq!(attachments.len(), 2);
+        assert_eq!(attachments[0].file_id.as_deref(), Some("agenda"));
+        assert_eq!(attachments[1].title.as_deref(), Some("Notes"));
+
+        let updated = CalendarEvent {
+            attachments: Some(attachments),
+            ..event
+        };
+        assert!(updated.with_attachment(notes).is_none());
+        assert!(write_query(&EventPayload::default()).contains(&("supportsAttachments", "true")));
+    }
+
+    #[test]
+    fn rsvp_changes_only_the_users_entry() {
+        let event: CalendarEvent = serde_json::from_str(
+            r#"{"id":"evt","attendees":[
+                {"email":"host@example.com","organizer":true,"responseStatus":"accepted"},
+                {"email":"me@example.com","self":true,"displayName":"Me","responseStatus":"needsAction"}
+            ]}"#,
+        )
+        .unwrap();
+
+        let attendees = event
+            .with_response(RsvpResponse::Tentative, Some("Might be late".into()))
+            .unwrap();
+        let json = serde_json::to_value(&attendees).unwrap();
+        assert_eq!(json[0]["responseStatus"], "accepted");
+        assert_eq!(json[0]["organizer"], true);
+        assert_eq!(json[1]["responseStatus"], "tentative");
+        assert_eq!(json[1]["comment"], "Might be late");
+        assert_eq!(json[1]["displayName"], "Me");
+        assert_eq!(json[1]["self"], true);
+    }
+
+    #[tokio::test]
+    async fn refreshed_tokens_share_the_users_rate_limit() {
+        let mut client = GoogleCalendarClient::new("https://example.com/calendar/v3".into());
+        client.retry.max_delay = Duration::from_millis(1);
+        client.rate_limiter = Some(Arc::new(RateLimiter::new(0.01, 1)));
+        let token = |user_id: &str, access_token: &str| UserToken {
+            user_id: user_id.into(),
+            token: crate::oauth::TokenInfo {
+                access_token: access_token.into(),
+                refresh_token: None,
+                expires_at: None,
+                scope: None,
+                token_type: "Bearer".into(),
+            },
+        };
+
+        client.throttle(&token("alice", "old")).await.unwrap();
+        let refreshed = client.throttle(&token("alice", "new")).await;
+        assert!(matches!(
+            refreshed,
+            Err(GoogleCalendarError::RateLimited { .. })
+        ));
+        client.throttle(&token("bob", "old")).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn conditional_updates_are_not_retried() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let counter = attempts.clone();
+        let app = axum::Router::new().fallback(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+            async { axum::http::StatusCode::SERVICE_UNAVAILABLE }
+        });
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let mut client = GoogleCalendarClient::new(format!("http://{addr}/calendar/v3"));
+        client.retry.max_retries = 2;
+        client.retry.base_delay = Duration::from_millis(1);
+        client.retry.max_delay = Duration::from_millis(1);
+        let token = UserToken {
+            user_id: "alice".into(),
+            token: crate::oauth::TokenInfo {
+                access_token: "token".into(),
+                refresh_token: None,
+                expires_at: None,
+                scope: None,
+                token_type: "Bearer".into(),
+            },
+        };
+        let patch = EventPayload::default();
+
+        let conditional = client
+            .update_event(&token, "evt", &patch, Some("\"etag\""))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            conditional.downcast_ref(),
+            Some(GoogleCalendarError::Transient { .. })
+        ));
+        assert_eq!(attempts.swap(0, Ordering::SeqCst), 1);
+
+        client
+            .update_event(&token, "evt", &patch, None)
+            .await
+            .unwrap_err();
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
 }
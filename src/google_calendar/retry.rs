@@ -0,0 +1,163 @@
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use rand::Rng;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Buckets untouched for this long are dropped once the map grows past `PRUNE_THRESHOLD`.
+const IDLE_BUCKET_TTL: Duration = Duration::from_secs(600);
+const PRUNE_THRESHOLD: usize = 1024;
+
+/// How failed Google API calls are retried.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Exponential backoff with jitter for the given zero-based retry `attempt`.
+    ///
+    /// The delay doubles per attempt up to `max_delay`, and a random value between half and the
+    /// full delay is used so that concurrent callers do not retry in lockstep.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exponential = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_delay);
+        let half = exponential / 2;
+        half + rand::thread_rng().gen_range(Duration::ZERO..=exponential - half)
+    }
+
+    /// Delay before retrying `attempt`, preferring the server's `Retry-After` when present.
+    ///
+    /// Returns `None` when retries are exhausted or the server asks for a longer pause than
+    /// `max_delay`, in which case the error is surfaced to the caller instead.
+    pub fn next_delay(&self, attempt: u32, retry_after: Option<Duration>) -> Option<Duration> {
+        if attempt >= self.max_retries {
+            return None;
+        }
+        match retry_after {
+            Some(delay) if delay > self.max_delay => None,
+            Some(delay) => Some(delay),
+            None => Some(self.backoff(attempt)),
+        }
+    }
+}
+
+/// Parses a `Retry-After` header given either as delta-seconds or as an HTTP date.
+pub fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let at = DateTime::parse_from_rfc2822(value)
+        .ok()?
+        .with_timezone(&Utc);
+    (at - Utc::now()).to_std().ok().or(Some(Duration::ZERO))
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// Token bucket per caller, so one busy user cannot drain the project-wide API quota.
+#[derive(Debug)]
+pub struct RateLimiter {
+    per_second: f64,
+    burst: f64,
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl RateLimiter {
+    pub fn new(per_second: f64, burst: u32) -> Self {
+        Self {
+            per_second,
+            burst: f64::from(burst.max(1)),
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Reserves one request for `key` and returns how long to wait before sending it.
+    ///
+    /// Fails with the required wait when it exceeds `max_wait`; nothing is reserved then.
+    pub fn reserve(&self, key: &str, max_wait: Duration) -> Result<Duration, Duration> {
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock();
+        if buckets.len() > PRUNE_THRESHOLD {
+            buckets.retain(|_, bucket| now.duration_since(bucket.updated) < IDLE_BUCKET_TTL);
+        }
+
+        let bucket = buckets.entry(key.to_owned()).or_insert(Bucket {
+            tokens: self.burst,
+            updated: now,
+        });
+        let refill = now.duration_since(bucket.updated).as_secs_f64() * self.per_second;
+        bucket.tokens = (bucket.tokens + refill).min(self.burst);
+        bucket.updated = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            return Ok(Duration::ZERO);
+        }
+
+        let wait = Duration::from_secs_f64((1.0 - bucket.tokens) / self.per_second);
+        if wait > max_wait {
+            return Err(wait);
+        }
+        // A negative balance queues this request behind earlier reservations.
+        bucket.tokens -= 1.0;
+        Ok(wait)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn retry_after_accepts_seconds_and_gives_up_past_max_delay() {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("7"));
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(7)));
+
+        let policy = RetryPolicy {
+            max_retries: 2,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(5),
+        };
+        assert_eq!(policy.next_delay(0, retry_after(&headers)), None);
+        assert_eq!(
+            policy.next_delay(0, Some(Duration::from_secs(1))),
+            Some(Duration::from_secs(1))
+        );
+        assert!(policy.next_delay(1, None).unwrap() <= Duration::from_millis(200));
+        assert_eq!(policy.next_delay(2, None), None);
+    }
+
+    #[test]
+    fn rate_limiter_isolates_users() {
+        let limiter = RateLimiter::new(1.0, 2);
+        let max_wait = Duration::from_millis(100);
+
+        assert_eq!(limiter.reserve("a", max_wait), Ok(Duration::ZERO));
+        assert_eq!(limiter.reserve("a", max_wait), Ok(Duration::ZERO));
+        assert!(limiter.reserve("a", max_wait).is_err());
+        assert_eq!(limiter.reserve("b", max_wait), Ok(Duration::ZERO));
+    }
+}
@@ -0,0 +1,430 @@
+use super::EventDateTime;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use schemars::{JsonSchema, Schema, SchemaGenerator};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::borrow::Cow;
+
+#[derive(Debug, thiserror::Error)]
+#[error(
+    "could not understand time expression '{0}'; use RFC3339 or phrases like 'tomorrow 3pm', 'next friday 9:30', 'in 2 hours' or 'end of month'"
+)]
+pub struct TimeExprError(String);
+
+/// Resolves an RFC3339 timestamp or a relative expression to an instant.
+///
+/// Expressions are interpreted as wall-clock times in `tz` relative to `now`, so DST transitions
+/// are handled by the zone rules rather than by the caller. Supported forms:
+/// - `now`, `in 2 hours`, `30 minutes ago`, `3 days from now`
+/// - `today`, `tomorrow`, `yesterday`, `2025-10-20`, optionally followed by a time (`3pm`,
+///   `15:30`, `noon`)
+/// - weekdays: `friday` (today or later), `next friday` (after today), `last friday`
+/// - `this week`, `next week`, `next month` (their first day), `start of week`, `end of month`
+///
+/// A bare time such as `3pm` means today. `end of ...` is the first instant after the period.
+pub fn resolve(expr: &str, now: DateTime<Utc>, tz: Tz) -> Result<DateTime<Utc>, TimeExprError> {
+    let trimmed = expr.trim();
+    if let Ok(parsed) = DateTime::parse_from_rfc3339(trimmed) {
+        return Ok(parsed.with_timezone(&Utc));
+    }
+
+    let normalized = trimmed.to_lowercase().replace(',', " ");
+    let words: Vec<&str> = normalized
+        .split_whitespace()
+        .filter(|word| !matches!(*word, "at" | "on"))
+        .collect();
+    parse_words(&words, now, tz).ok_or_else(|| TimeExprError(trimmed.to_owned()))
+}
+
+fn parse_words(words: &[&str], now: DateTime<Utc>, tz: Tz) -> Option<DateTime<Utc>> {
+    let today = now.with_timezone(&tz).date_naive();
+    match words {
+        [] => None,
+        ["now"] => Some(now),
+        ["in", amount, unit] | [amount, unit, "from", "now"] => {
+            now.checked_add_signed(duration(amount, unit)?)
+        }
+        [amount, unit, "ago"] => now.checked_sub_signed(duration(amount, unit)?),
+        ["start" | "beginning", "of", period @ ..] => {
+            let (start, _) = period_bounds(period, today)?;
+            local(start.and_time(NaiveTime::MIN), tz)
+        }
+        ["end", "of", period @ ..] => {
+            let (_, end) = period_bounds(period, today)?;
+            local(end.and_time(NaiveTime::MIN), tz)
+        }
+        _ => {
+            let (date, rest) = parse_date(words, today)?;
+            let time = if rest.is_empty() {
+                NaiveTime::MIN
+            } else {
+                parse_time(&rest.concat())?
+            };
+            local(date.and_time(time), tz)
+        }
+    }
+}
+
+fn duration(amount: &str, unit: &str) -> Option<Duration> {
+    let amount = match amount {
+        "a" | "an" => 1,
+        number => number.parse::<i64>().ok()?,
+    };
+    match unit.trim_end_matches('s') {
+        "minute" | "min" => Duration::try_minutes(amount),
+        "hour" | "hr" | "h" => Duration::try_hours(amount),
+        "day" => Duration::try_days(amount),
+        "week" => Duration::try_weeks(amount),
+        _ => None,
+    }
+}
+
+/// Splits leading date words off `words`, defaulting to `today` when there are none.
+fn parse_date<'a, 'w>(
+    words: &'a [&'w str],
+    today: NaiveDate,
+) -> Option<(NaiveDate, &'a [&'w str])> {
+    let monday = today - Duration::days(i64::from(today.weekday().num_days_from_monday()));
+    let first_of_month = today.with_day(1)?;
+
+    let parsed = match words {
+        ["today", rest @ ..] => (today, rest),
+        ["tomorrow", rest @ ..] => (today + Duration::days(1), rest),
+        ["yesterday", rest @ ..] => (today - Duration::days(1), rest),
+        ["this", "week", rest @ ..] => (monday, rest),
+        ["next", "week", rest @ ..] => (monday + Duration::weeks(1), rest),
+        ["last", "week", rest @ ..] => (monday - Duration::weeks(1), rest),
+        ["this", "month", rest @ ..] => (first_of_month, rest),
+        ["next", "month", rest @ ..] => (next_month(first_of_month)?, rest),
+        ["next", day, rest @ ..] => (weekday_after(today, day, 1)?, rest),
+        ["this", day, rest @ ..] => (weekday_after(today, day, 0)?, rest),
+        ["last", day, rest @ ..] => {
+            let weekday = weekday(day)?;
+            let back =
+                (7 + today.weekday().num_days_from_monday() - weekday.num_days_from_monday() - 1)
+                    % 7
+                    + 1;
+            (today - Duration::days(i64::from(back)), rest)
+        }
+        [first, rest @ ..] if weekday(first).is_some() => (weekday_after(today, first, 0)?, rest),
+        [first, rest @ ..] if NaiveDate::parse_from_str(first, "%Y-%m-%d").is_ok() => {
+            (NaiveDate::parse_from_str(first, "%Y-%m-%d").ok()?, rest)
+        }
+        rest => (today, rest),
+    };
+    Some(parsed)
+}
+
+/// First and exclusive last day of a named period.
+fn period_bounds(period: &[&str], today: NaiveDate) -> Option<(NaiveDate, NaiveDate)> {
+    let monday = today - Duration::days(i64::from(today.weekday().num_days_from_monday()));
+    let first_of_month = today.with_day(1)?;
+    let first_of_year = today.with_ordinal(1)?;
+
+    match period {
+        ["day"] | ["today"] | ["the", "day"] => Some((today, today + Duration::days(1))),
+        ["tomorrow"] => Some((today + Duration::days(1), today + Duration::days(2))),
+        ["week"] | ["this", "week"] | ["the", "week"] => {
+            Some((monday, monday + Duration::weeks(1)))
+        }
+        ["next", "week"] => Some((monday + Duration::weeks(1), monday + Duration::weeks(2))),
+        ["month"] | ["this", "month"] | ["the", "month"] => {
+            Some((first_of_month, next_month(first_of_month)?))
+        }
+        ["next", "month"] => {
+            let start = next_month(first_of_month)?;
+            Some((start, next_month(start)?))
+        }
+        ["year"] | ["this", "year"] | ["the", "year"] => Some((
+            first_of_year,
+            first_of_year.with_year(first_of_year.year() + 1)?,
+        )),
+        _ => None,
+    }
+}
+
+fn next_month(first_of_month: NaiveDate) -> Option<NaiveDate> {
+    match first_of_month.month() {
+        12 => NaiveDate::from_ymd_opt(first_of_month.year() + 1, 1, 1),
+        month => first_of_month.with_month(month + 1),
+    }
+}
+
+fn weekday(word: &str) -> Option<chrono::Weekday> {
+    match word {
+        "monday" | "mon" => Some(chrono::Weekday::Mon),
+        "tuesday" | "tue" | "tues" => Some(chrono::Weekday::Tue),
+        "wednesday" | "wed" => Some(chrono::Weekday::Wed),
+        "thursday" | "thu" | "thurs" => Some(chrono::Weekday::Thu),
+        "friday" | "fri" => Some(chrono::Weekday::Fri),
+        "saturday" | "sat" => Some(chrono::Weekday::Sat),
+        "sunday" | "sun" => Some(chrono::Weekday::Sun),
+        _ => None,
+    }
+}
+
+/// The first `word` weekday at least `min_days` after `today`.
+fn weekday_after(today: NaiveDate, word: &str, min_days: u32) -> Option<NaiveDate> {
+    let target = weekday(word)?.num_days_from_monday();
+    let current = today.weekday().num_days_from_monday();
+    let mut ahead = (7 + target - current) % 7;
+    if ahead < min_days {
+        ahead += 7;
+    }
+    Some(today + Duration::days(i64::from(ahead)))
+}
+
+/// Parses `3pm`, `3:30pm`, `15:30`, `15`, `noon` or `midnight`.
+fn parse_time(text: &str) -> Option<NaiveTime> {
+    match text {
+        "noon" | "midday" => return NaiveTime::from_hms_opt(12, 0, 0),
+        "midnight" => return Some(NaiveTime::MIN),
+        _ => {}
+    }
+
+    let text = text.replace('.', "");
+    let (clock, meridiem) = if let Some(clock) = text.strip_suffix("am") {
+        (clock, Some(false))
+    } else if let Some(clock) = text.strip_suffix("pm") {
+        (clock, Some(true))
+    } else {
+        (text.as_str(), None)
+    };
+
+    let (hour, minute) = match clock.split_once(':') {
+        Some((hour, minute)) => (hour.parse::<u32>().ok()?, minute.parse::<u32>().ok()?),
+        None => (clock.parse::<u32>().ok()?, 0),
+    };
+    let hour = match meridiem {
+        Some(_) if hour == 0 || hour > 12 => return None,
+        Some(true) => hour % 12 + 12,
+        Some(false) => hour % 12,
+        None => hour,
+    };
+    NaiveTime::from_hms_opt(hour, minute, 0)
+}
+
+/// Maps a wall-clock time in `tz` to UTC, taking the earlier instant in a DST overlap and
+/// moving forward past a DST gap.
+pub(super) fn local(naive: NaiveDateTime, tz: Tz) -> Option<DateTime<Utc>> {
+    tz.from_local_datetime(&naive)
+        .earliest()
+        .or_else(|| {
+            tz.from_local_datetime(&(naive + Duration::hours(1)))
+                .earliest()
+        })
+        .map(|time| time.with_timezone(&Utc))
+}
+
+/// A point in time given as RFC3339 or as a relative expression, resolved once the user's time
+/// zone is known.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TimeInput {
+    At(DateTime<Utc>),
+    Expression(String),
+}
+
+impl TimeInput {
+    /// The instant, if this input has been resolved.
+    pub fn absolute(&self) -> Option<DateTime<Utc>> {
+        match self {
+            Self::At(at) => Some(*at),
+            Self::Expression(_) => None,
+        }
+    }
+}
+
+impl From<DateTime<Utc>> for TimeInput {
+    fn from(at: DateTime<Utc>) -> Self {
+        Self::At(at)
+    }
+}
+
+impl Serialize for TimeInput {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::At(at) => at.serialize(serializer),
+            Self::Expression(expr) => expr.serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for TimeInput {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        Ok(match DateTime::parse_from_rfc3339(value.trim()) {
+            Ok(at) => Self::At(at.with_timezone(&Utc)),
+            Err(_) => Self::Expression(value),
+        })
+    }
+}
+
+impl JsonSchema for TimeInput {
+    fn schema_name() -> Cow<'static, str> {
+        Cow::Borrowed("TimeInput")
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        let mut schema = String::json_schema(gen);
+        if let Some(object) = schema.as_object_mut() {
+            object.insert(
+                "description".to_string(),
+                serde_json::Value::String(
+                    "RFC3339 date-time or a relative expression in the user's time zone (e.g. \"tomorrow 3pm\", \"next week\", \"end of month\")".to_string(),
+                ),
+            );
+        }
+        schema
+    }
+}
+
+/// Resolves the expressions of one request against a single `now`, recording what each became.
+#[derive(Debug)]
+pub struct TimeResolver {
+    now: DateTime<Utc>,
+    tz: Tz,
+    resolved: Vec<ResolvedTime>,
+}
+
+/// The absolute times that relative expressions in a request resolved to.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ResolvedTimes {
+    /// IANA zone the expressions were interpreted in.
+    pub time_zone: String,
+    /// Reference time for relative expressions.
+    pub now: DateTime<Utc>,
+    pub fields: Vec<ResolvedTime>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ResolvedTime {
+    pub field: String,
+    pub expression: String,
+    pub resolved: DateTime<Utc>,
+}
+
+impl TimeResolver {
+    pub fn new(now: DateTime<Utc>, tz: Tz) -> Self {
+        Self {
+            now,
+            tz,
+            resolved: Vec::new(),
+        }
+    }
+
+    pub fn time_input(
+        &mut self,
+        field: &str,
+        time: &mut Option<TimeInput>,
+    ) -> Result<(), TimeExprError> {
+        if let Some(TimeInput::Expression(expression)) = time {
+            let at = self.resolve(field, expression)?;
+            *time = Some(TimeInput::At(at));
+        }
+        Ok(())
+    }
+
+    pub fn event_time(
+        &mut self,
+        field: &str,
+        time: &mut Option<EventDateTime>,
+    ) -> Result<(), TimeExprError> {
+        if let Some(time) = time {
+            if let Some(expression) = &time.expression {
+                time.date_time = Some(self.resolve(field, expression)?);
+                time.time_zone = Some(self.tz.name().to_owned());
+                time.expression = None;
+            }
+        }
+        Ok(())
+    }
+
+    /// `None` when the request contained no expressions.
+    pub fn into_resolved(self) -> Option<ResolvedTimes> {
+        (!self.resolved.is_empty()).then(|| ResolvedTimes {
+            time_zone: self.tz.name().to_owned(),
+            now: self.now,
+            fields: self.resolved,
+        })
+    }
+
+    fn resolve(&mut self, field: &str, expression: &str) -> Result<DateTime<Utc>, TimeExprError> {
+        let at = resolve(expression, self.now, self.tz)?;
+        self.resolved.push(ResolvedTime {
+            field: field.to_owned(),
+            expression: expression.to_owned(),
+            resolved: at,
+        });
+        Ok(at)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(value: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(value)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn resolves_across_dst_change() {
+        // Saturday 2025-11-01 08:00 in New York; DST ends early on Sunday.
+        let now = at("2025-11-01T12:00:00Z");
+        let tz = chrono_tz::America::New_York;
+
+        assert_eq!(
+            resolve("tomorrow 3pm", now, tz).unwrap(),
+            at("2025-11-02T20:00:00Z")
+        );
+        assert_eq!(
+            resolve("Today at 9:30am", now, tz).unwrap(),
+            at("2025-11-01T13:30:00Z")
+        );
+        assert_eq!(
+            resolve("next friday", now, tz).unwrap(),
+            at("2025-11-07T05:00:00Z")
+        );
+        assert_eq!(
+            resolve("in 2 hours", now, tz).unwrap(),
+            at("2025-11-01T14:00:00Z")
+        );
+        assert_eq!(
+            resolve("end of month", now, tz).unwrap(),
+            at("2025-12-01T05:00:00Z")
+        );
+        assert_eq!(
+            resolve("next week", now, tz).unwrap(),
+            at("2025-11-03T05:00:00Z")
+        );
+        assert_eq!(
+            resolve("2025-10-14T12:34:56Z", now, tz).unwrap(),
+            at("2025-10-14T12:34:56Z")
+        );
+        assert!(resolve("whenever", now, tz).is_err());
+    }
+
+    #[test]
+    fn weekday_words_pick_the_expected_day() {
+        let today = NaiveDate::from_ymd_opt(2025, 10, 15).unwrap(); // Wednesday
+        let day = |words: &[&str]| parse_date(words, today).unwrap().0.day();
+
+        assert_eq!(day(&["wednesday"]), 15);
+        assert_eq!(day(&["next", "wednesday"]), 22);
+        assert_eq!(day(&["friday"]), 17);
+        assert_eq!(day(&["last", "wednesday"]), 8);
+        assert_eq!(day(&["last", "monday"]), 13);
+    }
+
+    #[test]
+    fn out_of_range_offsets_are_rejected() {
+        let now = at("2025-11-01T12:00:00Z");
+        let tz = chrono_tz::UTC;
+
+        assert!(resolve("in 100000000000 weeks", now, tz).is_err());
+        assert!(resolve("9223372036854775807 minutes ago", now, tz).is_err());
+        assert!(resolve("in 1000000000 days", now, tz).is_err());
+    }
+}
@@ -1,14 +1,21 @@
-use crate::mcp::{HttpMcpServer, ToolRequest, ToolResponse};
+use crate::google_calendar::export::{ExportColumn, ExportFormat};
+use crate::google_calendar::time_expr::TimeInput;
+use crate::mcp::{ExportEventsInput, HttpMcpServer, OpenExport, ToolRequest, ToolResponse};
 use crate::proxy::{
     AuthorizationParams, AuthorizationServerMetadata as ProxyMetadata, ClientRegistrationRequest,
     ClientRegistrationResponse, OpenIdConfiguration as ProxyOpenIdConfiguration,
     ProtectedResourceMetadata as ProxyResourceMetadata, TokenRequest,
 };
-use crate::token_ingest::{ingest_bearer_token_from_headers, BearerTokenError};
+use crate::token_ingest::{
+    bearer_matches_stored_token, ingest_bearer_token_from_headers, BearerTokenError,
+};
 use crate::{AppState, AuthorizationSession};
 use anyhow::Context;
+use axum::body::Body;
 use axum::extract::{Form, Path, Query};
-use axum::http::header::{ACCESS_CONTROL_ALLOW_ORIGIN, WWW_AUTHENTICATE};
+use axum::http::header::{
+    ACCESS_CONTROL_ALLOW_ORIGIN, CONTENT_DISPOSITION, CONTENT_TYPE, WWW_AUTHENTICATE,
+};
 use axum::http::{HeaderMap, HeaderValue, StatusCode};
 use axum::response::{IntoResponse, Json, Redirect};
 use axum::{
@@ -19,6 +26,8 @@ use chrono::{Duration, Utc};
 use serde::Deserialize;
 use serde_json::json;
 use std::sync::Arc;
+use tokio::io::AsyncWriteExt;
+use tokio_util::io::ReaderStream;
 
 pub fn build_router(state: Arc<AppState>) -> Router {
     let mut router = Router::new()
@@ -26,7 +35,8 @@ pub fn build_router(state: Arc<AppState>) -> Router {
         .route("/oauth/authorize", get(authorize))
         .route("/oauth/callback", get(callback))
         .route("/oauth/token/{user_id}", delete(clear_token))
-        .route("/mcp/tool", post(handle_tool));
+        .route("/mcp/tool", post(handle_tool))
+        .route("/export/events", get(export_events));
 
     if state
         .proxy_state
@@ -246,6 +256,7 @@ async fn handle_tool(
                 status: crate::mcp::ResponseStatus::Error,
                 data: Some(payload),
                 error: Some("authorization required".into()),
+                error_code: Some(crate::mcp::errors::UNAUTHORIZED.0),
             };
 
             let metadata_url = if let Some(proxy) = state.proxy_state.as_ref() {
@@ -294,10 +305,11 @@ async fn handle_tool(
                         protected_resource_metadata_url(&state.config.server.public_url)
                     };
                     let resource_id = state.config.server.public_url.trim_end_matches('/');
+                    let configured_scopes = state.config.oauth.scopes.join(" ");
                     let scope = data
                         .get("required_scope")
                         .and_then(|v| v.as_str())
-                        .unwrap_or(&state.config.oauth.scopes.join(" "));
+                        .unwrap_or(&configured_scopes);
                     let description = data
                         .get("description")
                         .and_then(|v| v.as_str())
@@ -328,14 +340,120 @@ async fn handle_tool(
         }
     }
 
-    // 通常のエラー/成功レスポンス
-    let status = match response.status {
-        crate::mcp::ResponseStatus::Success => StatusCode::OK,
-        crate::mcp::ResponseStatus::Error => StatusCode::BAD_REQUEST,
+    // 通常のエラー/成功レスポンス（エラーはMCPエラーコードからHTTPステータスを決定）
+    let status = match (&response.status, response.error_code) {
+        (crate::mcp::ResponseStatus::Success, _) => StatusCode::OK,
+        (crate::mcp::ResponseStatus::Error, Some(code)) => {
+            let status = crate::mcp::errors::http_status(rmcp::model::ErrorCode(code));
+            StatusCode::from_u16(status).unwrap_or(StatusCode::BAD_REQUEST)
+        }
+        (crate::mcp::ResponseStatus::Error, None) => StatusCode::BAD_REQUEST,
     };
     Ok((status, Json(response)).into_response())
 }
 
+#[derive(Debug, Deserialize)]
+struct ExportQuery {
+    user_id: String,
+    #[serde(default)]
+    calendar_id: Option<String>,
+    #[serde(default)]
+    time_min: Option<TimeInput>,
+    #[serde(default)]
+    time_max: Option<TimeInput>,
+    #[serde(default)]
+    query: Option<String>,
+    #[serde(default)]
+    format: ExportFormat,
+    /// Comma-separated column names, e.g. "summary,start,duration_minutes".
+    #[serde(default)]
+    columns: Option<String>,
+    #[serde(default)]
+    max_items: Option<u32>,
+}
+
+/// Bytes of rendered rows buffered ahead of a slow download.
+const EXPORT_BUFFER_BYTES: usize = 64 * 1024;
+
+/// Streams an event export as a file download while later pages are still being fetched.
+async fn export_events(
+    Extension(state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(query): Query<ExportQuery>,
+) -> Result<impl IntoResponse, HandlerError> {
+    // Downloads are plain links, so the caller must present the user's current access token.
+    if !bearer_matches_stored_token(&state, &headers, &query.user_id).await? {
+        return Err(HandlerError::unauthorized(format!(
+            "an Authorization: Bearer header with the access token of user '{}' is required",
+            query.user_id
+        )));
+    }
+
+    let columns = match &query.columns {
+        Some(list) => ExportColumn::parse_list(list)
+            .map_err(|err| HandlerError::new(StatusCode::BAD_REQUEST, err.to_string(), None))?,
+        None => Vec::new(),
+    };
+    let input = ExportEventsInput {
+        user_id: Some(query.user_id),
+        calendar_id: query.calendar_id,
+        time_min: query.time_min,
+        time_max: query.time_max,
+        query: query.query,
+        format: query.format,
+        columns,
+        max_items: query.max_items,
+    };
+    let server = HttpMcpServer::new(state.clone());
+    let open = server.open_export(input).await.map_err(|err| {
+        let status = crate::mcp::errors::http_status(err.code);
+        HandlerError::new(
+            StatusCode::from_u16(status).unwrap_or(StatusCode::BAD_REQUEST),
+            err.message.to_string(),
+            None,
+        )
+    })?;
+
+    let content_type = open.export.spec().format.mime_type();
+    let disposition = format!("attachment; filename=\"{}\"", open.file_name);
+    let (mut writer, reader) = tokio::io::duplex(EXPORT_BUFFER_BYTES);
+    tokio::spawn(async move {
+        let OpenExport {
+            token,
+            mut export,
+            first_chunk,
+            ..
+        } = open;
+        let mut chunk = Some(first_chunk);
+        while let Some(text) = chunk {
+            if writer.write_all(text.as_bytes()).await.is_err() {
+                // The client closed the download.
+                return;
+            }
+            chunk = match state
+                .google_calendar
+                .next_export_chunk(&token, &mut export)
+                .await
+            {
+                Ok(next) => next,
+                Err(err) => {
+                    // The status line is already sent; ending the body early is all that is left.
+                    tracing::warn!(error = ?err, "event export aborted");
+                    return;
+                }
+            };
+        }
+    });
+
+    Ok((
+        [
+            (CONTENT_TYPE, content_type.to_owned()),
+            (CONTENT_DISPOSITION, disposition),
+        ],
+        Body::from_stream(ReaderStream::new(reader)),
+    ))
+}
+
 fn cleanup_sessions(state: &Arc<AppState>) {
     let cutoff = Utc::now() - Duration::minutes(10);
     let mut sessions = state.auth_sessions.write();
@@ -401,6 +519,7 @@ async fn authorization_server_metadata(
         token_endpoint_auth_methods_supported: vec!["client_secret_post"],
         subject_types_supported: vec!["public"],
         id_token_signing_alg_values_supported: vec!["RS256"],
+        client_id_metadata_document_supported: None,
     }))
 }
 
@@ -632,7 +751,10 @@ impl RequestUserId for ToolRequest {
             ToolRequest::List { user_id, .. }
             | ToolRequest::Get { user_id, .. }
             | ToolRequest::Create { user_id, .. }
-            | ToolRequest::Update { user_id, .. } => Some(user_id.as_str()),
+            | ToolRequest::Update { user_id, .. }
+            | ToolRequest::Commit { user_id, .. }
+            | ToolRequest::Batch { user_id, .. }
+            | ToolRequest::Move { user_id, .. } => Some(user_id.as_str()),
         }
     }
 }
@@ -4,11 +4,16 @@ pub mod handlers;
 pub mod mcp;
 pub mod oauth;
 pub mod proxy;
+pub mod stdio;
 pub mod token_ingest;
+pub mod user_data;
 
 use anyhow::Result;
 use config::AppConfig;
 use google_calendar::GoogleCalendarClient;
+use mcp::confirmation::PendingWrite;
+use mcp::exports::StoredExport;
+use mcp::time_zone::CachedTimeZone;
 use oauth::{storage::TokenStorage, AuthorizationContext, OAuthClient};
 use parking_lot::RwLock;
 use proxy::ProxyState;
@@ -16,6 +21,7 @@ use std::{
     collections::{HashMap, HashSet},
     sync::Arc,
 };
+use user_data::{FileUserDataStorage, InMemoryUserDataStorage, UserDataStorage};
 
 use chrono::{DateTime, Utc};
 
@@ -24,16 +30,27 @@ pub struct AppState {
     pub oauth_client: OAuthClient,
     pub google_calendar: GoogleCalendarClient,
     pub token_storage: Arc<dyn TokenStorage>,
+    pub user_data: Arc<dyn UserDataStorage>,
     pub auth_sessions: Arc<RwLock<HashMap<String, AuthorizationSession>>>,
     pub revoked_tokens: Arc<RwLock<HashMap<String, HashSet<String>>>>,
+    pub pending_writes: Arc<RwLock<HashMap<String, PendingWrite>>>,
+    /// Calendar time zones read from Google, per user.
+    pub time_zones: Arc<RwLock<HashMap<String, CachedTimeZone>>>,
+    /// Event exports waiting to be read through their resource link, by export id.
+    pub exports: Arc<RwLock<HashMap<String, StoredExport>>>,
     pub proxy_state: Option<Arc<ProxyState>>,
 }
 
 impl AppState {
     pub fn new(config: AppConfig, storage: Arc<dyn TokenStorage>) -> Result<Self> {
         let oauth_client = OAuthClient::from_config(&config.oauth)?;
-        let google_calendar = GoogleCalendarClient::new(config.google.api_base.clone())
-            .with_default_calendar(config.google.calendar_id.clone());
+        let google_calendar = GoogleCalendarClient::from_config(&config.google);
+
+        let user_data: Arc<dyn UserDataStorage> = if config.security.use_in_memory {
+            Arc::new(InMemoryUserDataStorage::new())
+        } else {
+            Arc::new(FileUserDataStorage::new(&config.security.user_data_path)?)
+        };
 
         let proxy_state = if config.proxy.enabled {
             Some(Arc::new(ProxyState::new(&config)?))
@@ -46,8 +63,12 @@ impl AppState {
             oauth_client,
             google_calendar,
             token_storage: storage,
+            user_data,
             auth_sessions: Arc::new(RwLock::new(HashMap::new())),
             revoked_tokens: Arc::new(RwLock::new(HashMap::new())),
+            pending_writes: Arc::new(RwLock::new(HashMap::new())),
+            time_zones: Arc::new(RwLock::new(HashMap::new())),
+            exports: Arc::new(RwLock::new(HashMap::new())),
             proxy_state,
         })
     }
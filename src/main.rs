@@ -2,17 +2,19 @@ use anyhow::Result;
 use axum::Router;
 use mcp_google_calendar::config::AppConfig;
 use mcp_google_calendar::handlers::build_router;
-use mcp_google_calendar::mcp::service_factory;
+use mcp_google_calendar::mcp::{service_factory, CalendarService};
 use mcp_google_calendar::oauth::storage::{FileTokenStorage, InMemoryTokenStorage, TokenStorage};
-use mcp_google_calendar::AppState;
+use mcp_google_calendar::{stdio, AppState};
 use rmcp::transport::sse_server::{SseServer, SseServerConfig};
+use rmcp::ServiceExt;
 use std::sync::Arc;
 use tokio::signal;
 use tokio_util::sync::CancellationToken;
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    init_tracing();
+    let stdio_mode = std::env::args().skip(1).any(|arg| arg == "--stdio");
+    init_tracing(stdio_mode);
 
     let config = AppConfig::load()?;
     let storage: Arc<dyn TokenStorage> = if config.security.use_in_memory {
@@ -25,6 +27,10 @@ async fn main() -> Result<()> {
     };
 
     let state = Arc::new(AppState::new(config, storage)?);
+    if stdio_mode {
+        return run_stdio(state).await;
+    }
+
     let bind_address = state.config.server.bind_address.clone();
 
     let sse_config = SseServerConfig {
@@ -66,15 +72,49 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
-fn init_tracing() {
+/// Serves `CalendarService` over stdin/stdout for local desktop clients.
+async fn run_stdio(state: Arc<AppState>) -> Result<()> {
+    let user_id = stdio::resolve_local_user(&state).await?;
+    if state.token_storage.fetch(&user_id).await?.is_none() {
+        // Authorize in the background so the MCP handshake is not blocked on the browser.
+        let auth_state = state.clone();
+        let auth_user = user_id.clone();
+        tokio::spawn(async move {
+            if let Err(err) = stdio::authorize_via_loopback(auth_state, &auth_user).await {
+                tracing::error!(error = ?err, "loopback authorization failed");
+            }
+        });
+    }
+
+    tracing::info!(user_id = %user_id, "starting stdio server");
+
+    let service = CalendarService::new(state)
+        .with_local_user(user_id)
+        .serve(rmcp::transport::stdio())
+        .await?;
+
+    tokio::select! {
+        result = service.waiting() => {
+            result?;
+        }
+        _ = shutdown_signal() => {}
+    }
+    Ok(())
+}
+
+fn init_tracing(stdio_mode: bool) {
     if tracing::dispatcher::has_been_set() {
         return;
     }
 
-    let subscriber = tracing_subscriber::fmt()
-        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
-        .finish();
-    let _ = tracing::subscriber::set_global_default(subscriber);
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env());
+    // stdout carries the MCP protocol in stdio mode, so logs must go to stderr.
+    let _ = if stdio_mode {
+        tracing::subscriber::set_global_default(builder.with_writer(std::io::stderr).finish())
+    } else {
+        tracing::subscriber::set_global_default(builder.finish())
+    };
 }
 
 async fn shutdown_signal() {
@@ -0,0 +1,348 @@
+use crate::config::ConfirmationPolicy;
+use crate::google_calendar::diff::{diff_event, render_changes, FieldChange};
+use crate::google_calendar::{CalendarEvent, EventPayload};
+use chrono::{DateTime, Duration, Utc};
+use rmcp::handler::server::common::{AsRequestContext, FromContextPart};
+use rmcp::{ErrorData, Peer, RoleServer};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Staged writes expire after this many minutes, matching the OAuth session lifetime.
+pub const PENDING_WRITE_TTL_MINUTES: i64 = 10;
+
+/// Fields of a quick-add event shown to the user, as parsed by Google.
+const QUICK_ADD_FIELDS: [&str; 5] = ["summary", "start", "end", "location", "attendees"];
+
+/// Connection back to the MCP client, used to ask the user for confirmation.
+///
+/// Empty for calls made through the plain HTTP `/mcp/tool` endpoint.
+#[derive(Clone, Default)]
+pub struct ClientPeer(pub Option<Peer<RoleServer>>);
+
+impl ClientPeer {
+    pub fn supports_elicitation(&self) -> bool {
+        self.0
+            .as_ref()
+            .map(|peer| peer.supports_elicitation())
+            .unwrap_or(false)
+    }
+}
+
+impl<C> FromContextPart<C> for ClientPeer
+where
+    C: AsRequestContext,
+{
+    fn from_context_part(context: &mut C) -> Result<Self, ErrorData> {
+        Ok(Self(Some(context.as_request_context().peer.clone())))
+    }
+}
+
+/// Answer collected from the user through MCP elicitation.
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct WriteConfirmation {
+    /// Set to true to apply the change to Google Calendar.
+    pub confirm: bool,
+}
+
+rmcp::elicit_safe!(WriteConfirmation);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WriteOperation {
+    Create,
+    Update,
+    Move,
+}
+
+/// A create, update or move waiting for the user's go-ahead.
+#[derive(Debug, Clone)]
+pub struct PendingWrite {
+    pub user_id: String,
+    pub operation: WriteOperation,
+    pub event_id: Option<String>,
+    pub payload: EventPayload,
+    /// Client key that makes a create safe to retry.
+    pub idempotency_key: Option<String>,
+    /// Expected event `etag` for an update.
+    pub if_match_etag: Option<String>,
+    /// Target calendar of a move; the source is `payload.calendar_id`.
+    pub destination_calendar_id: Option<String>,
+    /// Free text of a create made through `events/quickAdd`, parsed by Google once approved.
+    pub quick_add_text: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl PendingWrite {
+    pub fn is_expired(&self) -> bool {
+        Utc::now() - self.created_at > Duration::minutes(PENDING_WRITE_TTL_MINUTES)
+    }
+}
+
+/// Removes and returns the write staged under `confirmation_token` if it belongs to `user_id`
+/// and has not expired. A token presented by another user leaves the staged write in place.
+pub fn take_pending_write(
+    pending: &mut HashMap<String, PendingWrite>,
+    confirmation_token: &str,
+    user_id: &str,
+) -> Option<PendingWrite> {
+    let staged = pending.get(confirmation_token)?;
+    if staged.is_expired() {
+        pending.remove(confirmation_token);
+        return None;
+    }
+    if staged.user_id != user_id {
+        return None;
+    }
+    pending.remove(confirmation_token)
+}
+
+/// Human-readable description of a write, shown to the user before it is applied.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct WritePreview {
+    /// Pass to `google_calendar_commit_write` to apply the change.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confirmation_token: Option<String>,
+    pub operation: WriteOperation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event_id: Option<String>,
+    pub changes: Vec<FieldChange>,
+    /// Whether attendees may receive an email about this change.
+    pub notifies_attendees: bool,
+    /// The same changes rendered as text for display.
+    pub summary: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl WritePreview {
+    /// Describes `write` against `current`, the event it changes. For a quick-add, `current` is
+    /// the event Google parsed from the text, if it has been created already.
+    pub fn new(write: &PendingWrite, current: Option<&CalendarEvent>) -> Self {
+        let changes = match (write.operation, &write.quick_add_text) {
+            (_, Some(text)) => quick_add_changes(text, write, current),
+            (WriteOperation::Move, _) => vec![FieldChange {
+                field: "calendar_id".to_owned(),
+                before: write.payload.calendar_id.clone().map(Into::into),
+                after: write.destination_calendar_id.clone().into(),
+            }],
+            _ => diff_event(current, &write.payload),
+        };
+        let notifies_attendees = notifies_attendees(current, &write.payload);
+
+        let name = current
+            .filter(|_| write.quick_add_text.is_none())
+            .and_then(|event| event.summary.as_deref())
+            .or(write.event_id.as_deref())
+            .unwrap_or_default();
+        let heading = match write.operation {
+            WriteOperation::Create if write.quick_add_text.is_some() => {
+                "Quick-add calendar event".to_owned()
+            }
+            WriteOperation::Create => "Create calendar event".to_owned(),
+            WriteOperation::Update => format!("Update calendar event '{name}'"),
+            WriteOperation::Move => format!("Move calendar event '{name}'"),
+        };
+        let mut summary = format!("{heading}:\n{}", render_changes(&changes));
+        if notifies_attendees {
+            summary.push_str("\nAttendees may be notified by email.");
+        }
+
+        Self {
+            confirmation_token: None,
+            operation: write.operation,
+            event_id: write.event_id.clone(),
+            changes,
+            notifies_attendees,
+            summary,
+            expires_at: None,
+        }
+    }
+}
+
+/// Whether `policy` requires confirmation for this write.
+/// The quick-add text, followed by the calendar and the fields Google filled in from it.
+fn quick_add_changes(
+    text: &str,
+    write: &PendingWrite,
+    parsed: Option<&CalendarEvent>,
+) -> Vec<FieldChange> {
+    let mut changes = vec![FieldChange {
+        field: "text".to_owned(),
+        before: None,
+        after: text.into(),
+    }];
+    let Some(parsed) = parsed.and_then(|event| serde_json::to_value(event).ok()) else {
+        return changes;
+    };
+    if let Some(calendar_id) = &write.payload.calendar_id {
+        changes.push(FieldChange {
+            field: "calendar_id".to_owned(),
+            before: None,
+            after: calendar_id.clone().into(),
+        });
+    }
+    for field in QUICK_ADD_FIELDS {
+        if let Some(after) = parsed.get(field).filter(|value| !value.is_null()) {
+            changes.push(FieldChange {
+                field: field.to_owned(),
+                before: None,
+                after: after.clone(),
+            });
+        }
+    }
+    changes
+}
+
+pub fn requires_confirmation(
+    policy: ConfirmationPolicy,
+    current: Option<&CalendarEvent>,
+    payload: &EventPayload,
+) -> bool {
+    match policy {
+        ConfirmationPolicy::Never => false,
+        ConfirmationPolicy::Attendees => notifies_attendees(current, payload),
+        ConfirmationPolicy::Always => true,
+    }
+}
+
+fn notifies_attendees(current: Option<&CalendarEvent>, payload: &EventPayload) -> bool {
+    let has_attendees = |attendees: Option<&Vec<_>>| attendees.is_some_and(|list| !list.is_empty());
+    has_attendees(payload.attendees.as_ref())
+        || has_attendees(current.and_then(|event| event.attendees.as_ref()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::google_calendar::EventAttendee;
+
+    #[test]
+    fn attendee_policy_only_triggers_with_attendees() {
+        let solo = EventPayload {
+            summary: Some("Focus".into()),
+            ..Default::default()
+        };
+        let invite = EventPayload {
+            attendees: Some(vec![EventAttendee {
+                email: "sam@example.com".into(),
+                ..Default::default()
+            }]),
+            ..solo.clone()
+        };
+
+        assert!(!requires_confirmation(
+            ConfirmationPolicy::Attendees,
+            None,
+            &solo
+        ));
+        assert!(requires_confirmation(
+            ConfirmationPolicy::Attendees,
+            None,
+            &invite
+        ));
+        assert!(requires_confirmation(
+            ConfirmationPolicy::Always,
+            None,
+            &solo
+        ));
+        assert!(!requires_confirmation(
+            ConfirmationPolicy::Never,
+            None,
+            &invite
+        ));
+    }
+
+    #[test]
+    fn quick_add_preview_shows_the_text() {
+        let write = PendingWrite {
+            user_id: "alice".into(),
+            operation: WriteOperation::Create,
+            event_id: None,
+            payload: EventPayload::default(),
+            idempotency_key: None,
+            if_match_etag: None,
+            destination_calendar_id: None,
+            quick_add_text: Some("Dentist Friday 3pm".into()),
+            created_at: Utc::now(),
+        };
+
+        let preview = WritePreview::new(&write, None);
+        assert_eq!(preview.changes.len(), 1);
+        assert_eq!(preview.changes[0].field, "text");
+        assert_eq!(preview.changes[0].after, "Dentist Friday 3pm");
+        assert!(preview.summary.starts_with("Quick-add calendar event:"));
+    }
+
+    #[test]
+    fn quick_add_preview_lists_the_parsed_event() {
+        let write = PendingWrite {
+            user_id: "alice".into(),
+            operation: WriteOperation::Create,
+            event_id: None,
+            payload: EventPayload {
+                calendar_id: Some("primary".into()),
+                ..Default::default()
+            },
+            idempotency_key: None,
+            if_match_etag: None,
+            destination_calendar_id: None,
+            quick_add_text: Some("Lunch with sam@example.com Friday 1pm".into()),
+            created_at: Utc::now(),
+        };
+        let parsed: CalendarEvent = serde_json::from_value(serde_json::json!({
+            "id": "evt",
+            "summary": "Lunch",
+            "start": {"dateTime": "2025-10-24T13:00:00Z"},
+            "end": {"dateTime": "2025-10-24T14:00:00Z"},
+            "attendees": [{"email": "sam@example.com"}]
+        }))
+        .unwrap();
+
+        let preview = WritePreview::new(&write, Some(&parsed));
+        let fields: Vec<_> = preview.changes.iter().map(|c| c.field.as_str()).collect();
+        assert_eq!(
+            fields,
+            [
+                "text",
+                "calendar_id",
+                "summary",
+                "start",
+                "end",
+                "attendees"
+            ]
+        );
+        assert!(preview.summary.contains("- start: 2025-10-24T13:00:00Z"));
+        assert!(preview.summary.contains("- attendees: sam@example.com"));
+        assert!(preview.notifies_attendees);
+    }
+
+    #[test]
+    fn staged_write_is_only_taken_by_its_user() {
+        let write = PendingWrite {
+            user_id: "alice".into(),
+            operation: WriteOperation::Create,
+            event_id: None,
+            payload: EventPayload::default(),
+            idempotency_key: None,
+            if_match_etag: None,
+            destination_calendar_id: None,
+            quick_add_text: None,
+            created_at: Utc::now(),
+        };
+        let mut pending = HashMap::from([("token".to_owned(), write.clone())]);
+
+        assert!(take_pending_write(&mut pending, "token", "mallory").is_none());
+        assert!(pending.contains_key("token"));
+        assert!(take_pending_write(&mut pending, "token", "alice").is_some());
+        assert!(pending.is_empty());
+
+        let expired = PendingWrite {
+            created_at: Utc::now() - Duration::minutes(PENDING_WRITE_TTL_MINUTES + 1),
+            ..write
+        };
+        pending.insert("old".into(), expired);
+        assert!(take_pending_write(&mut pending, "old", "alice").is_none());
+        assert!(pending.is_empty());
+    }
+}
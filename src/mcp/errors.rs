@@ -0,0 +1,153 @@
+use crate::google_calendar::conflicts::Conflict;
+use crate::google_calendar::GoogleCalendarError;
+use rmcp::model::ErrorCode;
+use rmcp::ErrorData;
+use serde_json::{json, Value};
+
+/// MCP error codes for Google Calendar failures, in the JSON-RPC server error range.
+///
+/// These values are part of the tool contract; `data.error` carries the matching string code.
+pub const UNAUTHORIZED: ErrorCode = ErrorCode(-32001);
+pub const NOT_FOUND: ErrorCode = ErrorCode::RESOURCE_NOT_FOUND;
+pub const FORBIDDEN: ErrorCode = ErrorCode(-32003);
+pub const CONFLICT: ErrorCode = ErrorCode(-32009);
+pub const GONE: ErrorCode = ErrorCode(-32010);
+pub const PRECONDITION_FAILED: ErrorCode = ErrorCode(-32012);
+pub const RATE_LIMITED: ErrorCode = ErrorCode(-32029);
+pub const UPSTREAM_UNAVAILABLE: ErrorCode = ErrorCode(-32050);
+pub const UPSTREAM_ERROR: ErrorCode = ErrorCode(-32060);
+
+/// Converts a classified Google error into an MCP error.
+///
+/// `data` always holds `error` (see [`GoogleCalendarError::code`]), `operation` and `retryable`,
+/// plus `reason` when Google supplied one and variant-specific fields.
+pub fn calendar_error(
+    operation: &str,
+    required_scope: &str,
+    err: &GoogleCalendarError,
+) -> ErrorData {
+    let mut data = json!({
+        "error": err.code(),
+        "operation": operation,
+        "retryable": err.is_retryable(),
+    });
+    if let Some(reason) = err.reason() {
+        data["reason"] = Value::String(reason.to_owned());
+    }
+
+    let code = match err {
+        GoogleCalendarError::InvalidRequest { .. } => ErrorCode::INVALID_PARAMS,
+        GoogleCalendarError::Unauthorized { .. } => UNAUTHORIZED,
+        GoogleCalendarError::InsufficientScope { message, .. } => {
+            // Keys read by the HTTP handler to build the WWW-Authenticate challenge.
+            data["__mcp_oauth_error"] = json!("insufficient_scope");
+            data["required_scope"] = json!(required_scope);
+            data["description"] = json!(message);
+            FORBIDDEN
+        }
+        GoogleCalendarError::Forbidden { .. } => FORBIDDEN,
+        GoogleCalendarError::NotFound { .. } => NOT_FOUND,
+        GoogleCalendarError::Conflict { .. } => CONFLICT,
+        GoogleCalendarError::Gone { .. } => GONE,
+        GoogleCalendarError::PreconditionFailed { current, .. } => {
+            data["current_etag"] = json!(current.as_ref().and_then(|event| event.etag.clone()));
+            data["current_event"] = json!(current);
+            PRECONDITION_FAILED
+        }
+        GoogleCalendarError::RateLimited { retry_after, .. } => {
+            if let Some(delay) = retry_after {
+                data["retry_after_secs"] = json!(delay.as_secs_f64().ceil() as u64);
+            }
+            RATE_LIMITED
+        }
+        GoogleCalendarError::Transient { status_code, .. } => {
+            data["status_code"] = json!(status_code);
+            UPSTREAM_UNAVAILABLE
+        }
+        GoogleCalendarError::Other { status_code, .. } => {
+            data["status_code"] = json!(status_code);
+            UPSTREAM_ERROR
+        }
+    };
+
+    ErrorData::new(code, format!("{operation} failed: {err}"), Some(data))
+}
+
+/// Error for a write refused by the `refuse` conflict policy; `data.conflicts` lists the busy
+/// events it overlaps.
+pub fn scheduling_conflict(conflicts: &[Conflict]) -> ErrorData {
+    ErrorData::new(
+        CONFLICT,
+        format!(
+            "the event overlaps {} busy event(s); pick another time or pass allow_conflicts",
+            conflicts.len()
+        ),
+        Some(json!({
+            "error": "scheduling_conflict",
+            "retryable": false,
+            "conflicts": conflicts,
+        })),
+    )
+}
+
+/// HTTP status used by `/mcp/tool` for an MCP error.
+pub fn http_status(code: ErrorCode) -> u16 {
+    match code {
+        ErrorCode::INVALID_PARAMS | ErrorCode::INVALID_REQUEST | ErrorCode::PARSE_ERROR => 400,
+        UNAUTHORIZED => 401,
+        FORBIDDEN => 403,
+        NOT_FOUND => 404,
+        CONFLICT => 409,
+        GONE => 410,
+        PRECONDITION_FAILED => 412,
+        RATE_LIMITED => 429,
+        UPSTREAM_UNAVAILABLE => 503,
+        UPSTREAM_ERROR => 502,
+        _ => 500,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn rate_limits_map_to_stable_code_and_status() {
+        let err = GoogleCalendarError::RateLimited {
+            message: "slow down".into(),
+            reason: Some("userRateLimitExceeded".into()),
+            retry_after: Some(Duration::from_millis(1500)),
+        };
+
+        let error = calendar_error("list_events", "scope", &err);
+        assert_eq!(error.code, RATE_LIMITED);
+        assert_eq!(http_status(error.code), 429);
+
+        let data = error.data.unwrap();
+        assert_eq!(data["error"], "rate_limited");
+        assert_eq!(data["reason"], "userRateLimitExceeded");
+        assert_eq!(data["retry_after_secs"], 2);
+        assert_eq!(data["retryable"], true);
+    }
+
+    #[test]
+    fn failed_preconditions_carry_the_current_event() {
+        let current: crate::google_calendar::CalendarEvent =
+            serde_json::from_value(json!({"id": "evt", "etag": "\"v2\""})).unwrap();
+        let err = GoogleCalendarError::PreconditionFailed {
+            message: "Precondition Failed".into(),
+            current: Some(Box::new(current)),
+        };
+
+        let error = calendar_error("update_event", "scope", &err);
+        assert_eq!(error.code, PRECONDITION_FAILED);
+        assert_eq!(http_status(error.code), 412);
+
+        let data = error.data.unwrap();
+        assert_eq!(data["error"], "precondition_failed");
+        assert_eq!(data["current_etag"], "\"v2\"");
+        assert_eq!(data["current_event"]["id"], "evt");
+        assert_eq!(data["retryable"], false);
+    }
+}
@@ -0,0 +1,34 @@
+use crate::google_calendar::export::ExportFormat;
+use chrono::{DateTime, Duration, Utc};
+
+/// Finished exports can be read for this many minutes.
+pub const EXPORT_TTL_MINUTES: i64 = 60;
+
+/// Scheme of the resource URIs that `google_calendar_export_events` links to.
+const EXPORT_URI_PREFIX: &str = "calendar-export://";
+
+/// An export kept in memory until its resource link is read.
+#[derive(Debug, Clone)]
+pub struct StoredExport {
+    pub user_id: String,
+    pub file_name: String,
+    pub format: ExportFormat,
+    pub content: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl StoredExport {
+    pub fn is_expired(&self) -> bool {
+        Utc::now() - self.created_at > Duration::minutes(EXPORT_TTL_MINUTES)
+    }
+}
+
+pub fn export_uri(export_id: &str) -> String {
+    format!("{EXPORT_URI_PREFIX}{export_id}")
+}
+
+/// The export id of a `calendar-export://` URI.
+pub fn export_id(uri: &str) -> Option<&str> {
+    uri.strip_prefix(EXPORT_URI_PREFIX)
+        .filter(|id| !id.is_empty())
+}
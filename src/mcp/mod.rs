@@ -1,42 +1,142 @@
+pub mod confirmation;
+pub mod errors;
+pub mod exports;
+pub mod time_zone;
+
 use std::sync::Arc;
 
+use crate::config::{ConfirmationPolicy, ConflictPolicy};
+use crate::google_calendar::acl::{AclRole, AclRule, AclRules, AclScope, ACL_SCOPE};
+use crate::google_calendar::agenda::{self, Agenda, AgendaOptions, AgendaSpan};
+use crate::google_calendar::analytics::{self, GroupBy, ReportOptions, TimeReport, WorkingHours};
+use crate::google_calendar::batch::{BatchItemError, BatchItemResult, BatchOperation};
+use crate::google_calendar::calendars::{
+    Calendar, CALENDARS_SCOPE, CALENDAR_LIST_READONLY_SCOPE, CALENDAR_LIST_SCOPE,
+};
+use crate::google_calendar::colors::Colors;
+use crate::google_calendar::conflicts::{self, Conflict};
+use crate::google_calendar::diff;
+use crate::google_calendar::event_types::{
+    self, AutoDeclineMode, ChatStatus, FocusTimeProperties, OfficeLocation, OutOfOfficeProperties,
+    WorkingLocationProperties, WorkingLocationType,
+};
+use crate::google_calendar::export::{EventExport, ExportColumn, ExportFormat, ExportSpec};
+use crate::google_calendar::ics;
+use crate::google_calendar::time_expr::{ResolvedTimes, TimeExprError, TimeInput, TimeResolver};
 use crate::google_calendar::{
-    CalendarEvent, EventPayload, GetEventParams, ListEventsParams, ListEventsResponse,
+    idempotent_event_id, CalendarEvent, CalendarListEntry, CompactEvent, DryRunReport,
+    EventAttachment, EventDateTime, EventPayload, EventType, EventVisibility, GetEventParams,
+    GoogleCalendarError, ListEventsParams, ListEventsResponse, RsvpResponse, SendUpdates,
+    Transparency, MAX_EVENT_ATTACHMENTS,
+};
+use crate::oauth::UserToken;
+use crate::token_ingest::{
+    bearer_matches_stored_token, ingest_bearer_token_from_headers, BearerTokenError,
 };
-use crate::oauth::TokenInfo;
-use crate::token_ingest::{ingest_bearer_token_from_headers, BearerTokenError};
 use crate::AppState;
 use axum::http::request::Parts;
+use chrono::{DateTime, Duration, Utc};
+use chrono_tz::Tz;
+use confirmation::{
+    requires_confirmation, take_pending_write, ClientPeer, PendingWrite, WriteConfirmation,
+    WriteOperation, WritePreview, PENDING_WRITE_TTL_MINUTES,
+};
+use exports::StoredExport;
 use rmcp::{
     handler::server::{
         router::tool::ToolRouter,
         wrapper::{Json, Parameters},
     },
-    model::{Extensions, Implementation, ProtocolVersion, ServerCapabilities, ServerInfo},
-    schemars, tool, tool_handler, tool_router, ErrorData, ServerHandler,
+    model::{
+        CallToolResult, Content, Extensions, Implementation, ProtocolVersion, RawResource,
+        ReadResourceRequestParam, ReadResourceResult, ResourceContents, ServerCapabilities,
+        ServerInfo,
+    },
+    schemars,
+    service::{ElicitationError, RequestContext},
+    tool, tool_handler, tool_router, ErrorData, RoleServer, ServerHandler,
 };
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use time_zone::{
+    parse_time_zone, CachedTimeZone, TimeZoneSource, UserTimeZone, ZonedTime,
+    TIME_ZONE_OVERRIDE_KEY,
+};
+use uuid::Uuid;
+
+/// Sharing tools, only listed when `acl.enabled` is set.
+const ACL_TOOLS: &[&str] = &[
+    "google_calendar_list_acl",
+    "google_calendar_share_calendar",
+    "google_calendar_update_acl",
+    "google_calendar_remove_acl",
+];
+
+/// Calendar management tools, only listed when `calendars.enabled` is set.
+const CALENDAR_TOOLS: &[&str] = &[
+    "google_calendar_create_calendar",
+    "google_calendar_update_calendar",
+    "google_calendar_subscribe_calendar",
+    "google_calendar_unsubscribe_calendar",
+];
+
+/// Tools that delete events for good; they also need `calendars.allow_destructive`.
+const DESTRUCTIVE_CALENDAR_TOOLS: &[&str] = &["google_calendar_delete_calendar"];
 
 #[derive(Clone)]
 pub struct CalendarService {
     state: Arc<AppState>,
+    local_user_id: Option<String>,
     tool_router: ToolRouter<Self>,
 }
 
 impl CalendarService {
     pub fn new(state: Arc<AppState>) -> Self {
+        let mut tool_router = Self::tool_router();
+        let calendars = &state.config.calendars;
+        let disabled = [
+            (ACL_TOOLS, state.config.acl.enabled),
+            (CALENDAR_TOOLS, calendars.enabled),
+            (
+                DESTRUCTIVE_CALENDAR_TOOLS,
+                calendars.enabled && calendars.allow_destructive,
+            ),
+        ];
+        for (names, enabled) in disabled {
+            if !enabled {
+                for name in names {
+                    tool_router.remove_route(name);
+                }
+            }
+        }
         Self {
             state,
-            tool_router: Self::tool_router(),
+            local_user_id: None,
+            tool_router,
+        }
+    }
+
+    /// Serves a single local user (stdio mode); tools no longer need a `user_id` argument.
+    pub fn with_local_user(mut self, user_id: String) -> Self {
+        self.local_user_id = Some(user_id);
+        self
+    }
+
+    fn resolve_user_id(&self, user_id: Option<String>) -> Result<String, ErrorData> {
+        if let Some(local) = &self.local_user_id {
+            return Ok(local.clone());
         }
+
+        user_id
+            .filter(|id| !id.trim().is_empty())
+            .ok_or_else(|| ErrorData::invalid_params("user_id is required", None))
     }
 
     async fn ensure_token(
         &self,
         user_id: &str,
         extensions: &Extensions,
-    ) -> Result<TokenInfo, ErrorData> {
+    ) -> Result<UserToken, ErrorData> {
         if let Some(parts) = extensions.get::<Parts>() {
             if let Err(err) =
                 ingest_bearer_token_from_headers(&self.state, &parts.headers, user_id).await
@@ -63,6 +163,14 @@ impl CalendarService {
             .map_err(|err| internal_error("token fetch", err))?
         {
             Some(token) => token,
+            None if self.local_user_id.is_some() => {
+                return Err(ErrorData::invalid_request(
+                    format!(
+                        "user '{user_id}' is not authorized; open the authorization URL printed on the server's stderr"
+                    ),
+                    None,
+                ));
+            }
             None => {
                 return Err(ErrorData::invalid_request(
                     format!("user '{user_id}' is not authorized; complete OAuth flow"),
@@ -95,7 +203,10 @@ impl CalendarService {
                 .map_err(|err| internal_error("token persist", err))?;
         }
 
-        Ok(token)
+        Ok(UserToken {
+            user_id: user_id.to_owned(),
+            token,
+        })
     }
 
     /// 各操作に必要な最小スコープを返す
@@ -109,40 +220,591 @@ impl CalendarService {
                 // Write操作 - calendar.eventsスコープが必要
                 "https://www.googleapis.com/auth/calendar.events"
             }
+            "list_acl" | "share_calendar" | "update_acl" | "remove_acl" => {
+                // 共有設定の操作 - calendar.aclsスコープが必要
+                ACL_SCOPE
+            }
+            "create_calendar" | "update_calendar" | "delete_calendar" => {
+                // カレンダー自体の操作 - calendar.calendarsスコープが必要
+                CALENDARS_SCOPE
+            }
+            "calendar_list" => {
+                // 書き込み権限の確認 - calendarlist.readonlyスコープが必要
+                CALENDAR_LIST_READONLY_SCOPE
+            }
+            "subscribe_calendar" | "unsubscribe_calendar" => {
+                // カレンダーリストの操作 - calendar.calendarlistスコープが必要
+                CALENDAR_LIST_SCOPE
+            }
             _ => "https://www.googleapis.com/auth/calendar.events",
         }
     }
 
-    /// Google Calendar APIエラーをMCPエラーに変換（insufficient_scope検出付き）
+    /// Google Calendar APIエラーをMCPエラーに変換（分類済みエラーは安定したコードに変換）
     fn handle_calendar_error(operation: &str, err: anyhow::Error) -> ErrorData {
-        let err_str = err.to_string();
-        if err_str.starts_with("insufficient_scope:") {
-            let description = err_str
-                .strip_prefix("insufficient_scope:")
-                .unwrap_or("")
-                .trim();
-            let required_scope = Self::required_scope_for_operation(operation);
-
-            ErrorData::invalid_request(
-                format!("Insufficient OAuth scope: {}", description),
-                Some(serde_json::json!({
-                    "__mcp_oauth_error": "insufficient_scope",
-                    "required_scope": required_scope,
-                    "description": description,
-                    "operation": operation
-                })),
+        match err.downcast_ref::<GoogleCalendarError>() {
+            Some(calendar_err) => errors::calendar_error(
+                operation,
+                Self::required_scope_for_operation(operation),
+                calendar_err,
+            ),
+            None => internal_error(operation, err),
+        }
+    }
+
+    /// The user's effective time zone: their override, else the cached or freshly read Calendar
+    /// setting, else UTC.
+    async fn user_time_zone(&self, user_id: &str, token: &UserToken) -> (Tz, TimeZoneSource) {
+        if let Some(tz) = self.time_zone_override(user_id).await {
+            return (tz, TimeZoneSource::Override);
+        }
+        let cached = self.state.time_zones.read().get(user_id).copied();
+        if let Some(cached) = cached.filter(CachedTimeZone::is_fresh) {
+            return (cached.time_zone, TimeZoneSource::Calendar);
+        }
+
+        match self.state.google_calendar.user_time_zone(token).await {
+            Ok(tz) => {
+                self.state.time_zones.write().insert(
+                    user_id.to_owned(),
+                    CachedTimeZone {
+                        time_zone: tz,
+                        fetched_at: Utc::now(),
+                    },
+                );
+                (tz, TimeZoneSource::Calendar)
+            }
+            Err(err) => {
+                tracing::warn!(user_id, error = %err, "falling back to UTC for user time zone");
+                (Tz::UTC, TimeZoneSource::Fallback)
+            }
+        }
+    }
+
+    async fn time_zone_override(&self, user_id: &str) -> Option<Tz> {
+        let value = match self
+            .state
+            .user_data
+            .get(user_id, TIME_ZONE_OVERRIDE_KEY)
+            .await
+        {
+            Ok(value) => value?,
+            Err(err) => {
+                tracing::warn!(user_id, error = %err, "failed to read time zone override");
+                return None;
+            }
+        };
+        value.as_str()?.parse().ok()
+    }
+
+    /// Fills in server-side write defaults the caller left unset.
+    fn apply_write_defaults(&self, payload: &mut EventPayload) {
+        payload
+            .send_updates
+            .get_or_insert(self.state.config.writes.send_updates);
+    }
+
+    /// Creates an `outOfOffice` / `focusTime` / `workingLocation` event on the primary calendar,
+    /// checking its type-specific constraints before asking for confirmation.
+    async fn create_special_event(
+        &self,
+        peer: &ClientPeer,
+        extensions: &Extensions,
+        user_id: Option<String>,
+        mut payload: EventPayload,
+    ) -> Result<Json<EventWriteResult>, ErrorData> {
+        let user_id = self.resolve_user_id(user_id)?;
+        payload.calendar_id = Some("primary".to_owned());
+        event_types::validate(&payload, true)
+            .map_err(|err| Self::handle_calendar_error("create_event", err.into()))?;
+        let token = self.ensure_token(&user_id, extensions).await?;
+        let (tz, _) = self.user_time_zone(&user_id, &token).await;
+        let resolved_times = resolve_payload_times(tz, &mut payload)?;
+        self.apply_write_defaults(&mut payload);
+        let write = PendingWrite {
+            user_id,
+            operation: WriteOperation::Create,
+            event_id: None,
+            payload,
+            idempotency_key: None,
+            if_match_etag: None,
+            destination_calendar_id: None,
+            quick_add_text: None,
+            created_at: Utc::now(),
+        };
+        self.confirm_write(peer, &token, &write).await?;
+        let mut result = self.execute_write(&token, &write).await?;
+        result.localize(tz);
+        Ok(Json(EventWriteResult {
+            resolved_times,
+            ..result
+        }))
+    }
+
+    /// Applies the configured confirmation policy before a write reaches Google.
+    ///
+    /// Clients with elicitation support are asked directly. For the rest the write is staged and
+    /// an error carrying a confirmation token for `google_calendar_commit_write` is returned.
+    async fn confirm_write(
+        &self,
+        peer: &ClientPeer,
+        token: &UserToken,
+        write: &PendingWrite,
+    ) -> Result<(), ErrorData> {
+        let policy = self.state.config.writes.confirmation;
+        if policy == ConfirmationPolicy::Never {
+            return Ok(());
+        }
+
+        let current = self.current_event(token, write).await?;
+        if !requires_confirmation(policy, current.as_ref(), &write.payload) {
+            return Ok(());
+        }
+        self.ask_confirmation(peer, write, current.as_ref()).await
+    }
+
+    /// Refuses `operation`, which has no confirmation preview, while `writes.confirmation` is set.
+    fn refuse_unconfirmed(&self, operation: &str) -> Result<(), ErrorData> {
+        if self.state.config.writes.confirmation == ConfirmationPolicy::Never {
+            return Ok(());
+        }
+        Err(ErrorData::invalid_request(
+            format!("{operation} bypasses confirmation and is disabled while writes.confirmation is set"),
+            None,
+        ))
+    }
+
+    /// Asks the user to approve `write`, or stages it for `google_calendar_commit_write` when the
+    /// client cannot elicit. Returns `Ok` only once the user has accepted.
+    async fn ask_confirmation(
+        &self,
+        peer: &ClientPeer,
+        write: &PendingWrite,
+        current: Option<&CalendarEvent>,
+    ) -> Result<(), ErrorData> {
+        let preview = WritePreview::new(write, current);
+
+        if let Some(peer) = peer.0.as_ref().filter(|_| peer.supports_elicitation()) {
+            let message = format!("{}\n\nApply this change?", preview.summary);
+            return match peer.elicit::<WriteConfirmation>(message).await {
+                Ok(Some(WriteConfirmation { confirm: true })) => Ok(()),
+                Ok(_)
+                | Err(ElicitationError::UserDeclined)
+                | Err(ElicitationError::UserCancelled) => Err(ErrorData::invalid_request(
+                    "write was declined by the user",
+                    Some(serde_json::json!({ "preview": preview })),
+                )),
+                Err(err) => Err(internal_error("write confirmation", err.into())),
+            };
+        }
+
+        let preview = self.stage_write(write.clone(), preview);
+        Err(ErrorData::invalid_request(
+            "confirmation required: show the summary to the user, then call google_calendar_commit_write with the confirmation_token",
+            Some(serde_json::json!({
+                "confirmation_required": true,
+                "preview": preview,
+            })),
+        ))
+    }
+
+    /// Lets the user keep or discard `event`, which Google created from `write`'s quick-add text.
+    ///
+    /// Through elicitation the event is kept once approved and deleted otherwise. Clients that
+    /// cannot elicit get the parsed preview with a `confirmation_token`: the event is deleted
+    /// right away and `google_calendar_commit_write` adds the text again once approved.
+    async fn review_quick_add(
+        &self,
+        peer: &ClientPeer,
+        token: &UserToken,
+        write: &PendingWrite,
+        event: &CalendarEvent,
+    ) -> Result<(), ErrorData> {
+        let preview = WritePreview::new(write, Some(event));
+
+        if let Some(peer) = peer.0.as_ref().filter(|_| peer.supports_elicitation()) {
+            let message = format!("{}\n\nKeep this event?", preview.summary);
+            let answer = peer.elicit::<WriteConfirmation>(message).await;
+            if matches!(answer, Ok(Some(WriteConfirmation { confirm: true }))) {
+                return Ok(());
+            }
+            self.discard_quick_add(token, write, event).await?;
+            return Err(match answer {
+                Ok(_)
+                | Err(ElicitationError::UserDeclined)
+                | Err(ElicitationError::UserCancelled) => ErrorData::invalid_request(
+                    "quick-add event was discarded by the user",
+                    Some(serde_json::json!({ "preview": preview })),
+                ),
+                Err(err) => internal_error("quick-add confirmation", err.into()),
+            });
+        }
+
+        self.discard_quick_add(token, write, event).await?;
+        let preview = self.stage_write(write.clone(), preview);
+        Err(ErrorData::invalid_request(
+            "confirmation required: show the parsed event to the user, then call google_calendar_commit_write with the confirmation_token to add it",
+            Some(serde_json::json!({
+                "confirmation_required": true,
+                "preview": preview,
+            })),
+        ))
+    }
+
+    async fn discard_quick_add(
+        &self,
+        token: &UserToken,
+        write: &PendingWrite,
+        event: &CalendarEvent,
+    ) -> Result<(), ErrorData> {
+        let event_id = event
+            .id
+            .as_deref()
+            .ok_or_else(|| ErrorData::internal_error("quick-add event has no id", None))?;
+        self.state
+            .google_calendar
+            .delete_event(
+                token,
+                &write.payload.calendar_id,
+                event_id,
+                write.payload.send_updates,
             )
+            .await
+            .map_err(|err| Self::handle_calendar_error("delete_event", err))
+    }
+
+    async fn current_event(
+        &self,
+        token: &UserToken,
+        write: &PendingWrite,
+    ) -> Result<Option<CalendarEvent>, ErrorData> {
+        let (WriteOperation::Update | WriteOperation::Move, Some(event_id)) =
+            (write.operation, &write.event_id)
+        else {
+            return Ok(None);
+        };
+        let params = GetEventParams {
+            event_id: event_id.clone(),
+            calendar_id: write.payload.calendar_id.clone(),
+        };
+        self.state
+            .google_calendar
+            .get_event(token, &params)
+            .await
+            .map(Some)
+            .map_err(|err| Self::handle_calendar_error("get_event", err))
+    }
+
+    async fn dry_run_write(
+        &self,
+        token: &UserToken,
+        write: &PendingWrite,
+    ) -> Result<EventWriteResult, ErrorData> {
+        if write.quick_add_text.is_some() {
+            return Err(ErrorData::invalid_params(
+                "dry_run is not supported for quick_add",
+                None,
+            ));
+        }
+        let client = &self.state.google_calendar;
+        let calendars = self
+            .conflict_calendars(token, client.resolve_calendar(&write.payload.calendar_id))
+            .await?;
+        let result = match (write.operation, write.event_id.as_deref()) {
+            (WriteOperation::Create, _) => {
+                client
+                    .dry_run_create(token, &write.payload, &calendars)
+                    .await
+            }
+            (WriteOperation::Update, Some(event_id)) => {
+                client
+                    .dry_run_update(token, event_id, &write.payload, &calendars)
+                    .await
+            }
+            (WriteOperation::Update, None) => {
+                return Err(ErrorData::invalid_params(
+                    "event_id is required for updates",
+                    None,
+                ))
+            }
+            (WriteOperation::Move, _) => {
+                return Err(ErrorData::invalid_params(
+                    "dry_run is not supported for moves",
+                    None,
+                ))
+            }
+        };
+        let (event, report) = result.map_err(|err| Self::handle_calendar_error("dry_run", err))?;
+        Ok(EventWriteResult {
+            dry_run: Some(report),
+            ..EventWriteResult::from(event)
+        })
+    }
+
+    /// Calendars selected in the user's calendar list, or only the primary calendar when the
+    /// granted scopes cannot read the list.
+    async fn selected_calendars(&self, token: &UserToken) -> Result<Vec<String>, ErrorData> {
+        match self.state.google_calendar.selected_calendars(token).await {
+            Ok(calendars) => Ok(calendars),
+            Err(err)
+                if matches!(
+                    err.downcast_ref(),
+                    Some(GoogleCalendarError::InsufficientScope { .. })
+                ) =>
+            {
+                tracing::debug!("calendarList not readable; using the primary calendar only");
+                Ok(vec!["primary".to_owned()])
+            }
+            Err(err) => Err(Self::handle_calendar_error("calendar_list", err)),
+        }
+    }
+
+    /// Calendars checked for conflicts: the configured ones, or those selected in the user's
+    /// calendar list, plus the calendar written to.
+    async fn conflict_calendars(
+        &self,
+        token: &UserToken,
+        calendar_id: &str,
+    ) -> Result<Vec<String>, ErrorData> {
+        let configured = &self.state.config.conflicts.calendars;
+        let mut calendars = if configured.is_empty() {
+            self.selected_calendars(token).await?
         } else {
-            internal_error(operation, err)
+            configured.clone()
+        };
+        if !calendars.iter().any(|id| id == calendar_id) {
+            calendars.push(calendar_id.to_owned());
+        }
+        Ok(calendars)
+    }
+
+    /// Applies the configured conflict policy to a create or update before it is confirmed.
+    ///
+    /// Returns the conflicts to report with the saved event, or a `scheduling_conflict` error
+    /// when the policy is `refuse` and the call did not pass `allow_conflicts`.
+    async fn check_write_conflicts(
+        &self,
+        token: &UserToken,
+        write: &PendingWrite,
+        allow_conflicts: bool,
+        tz: Tz,
+    ) -> Result<Vec<Conflict>, ErrorData> {
+        let policy = self.state.config.conflicts.policy;
+        let payload = &write.payload;
+        let changes_time =
+            payload.start.is_some() || payload.end.is_some() || payload.transparency.is_some();
+        if policy == ConflictPolicy::Off
+            || (write.operation == WriteOperation::Update && !changes_time)
+        {
+            return Ok(Vec::new());
+        }
+
+        let lookup = async {
+            let current = self.current_event(token, write).await?;
+            let Some((start, end)) = conflicts::proposed_range(payload, current.as_ref()) else {
+                return Ok(Vec::new());
+            };
+            let client = &self.state.google_calendar;
+            let calendars = self
+                .conflict_calendars(token, client.resolve_calendar(&payload.calendar_id))
+                .await?;
+            client
+                .conflicts_in_range(token, &calendars, start, end, current.as_ref())
+                .await
+                .map_err(|err| Self::handle_calendar_error("check_conflicts", err))
+        };
+        let mut conflicts = match lookup.await {
+            Ok(conflicts) => conflicts,
+            Err(err) if policy == ConflictPolicy::Warn => {
+                tracing::warn!(error = %err.message, "conflict check failed; saving without it");
+                Vec::new()
+            }
+            Err(err) => return Err(err),
+        };
+        for conflict in &mut conflicts {
+            conflict.event.localize(tz);
+        }
+        if policy == ConflictPolicy::Refuse && !allow_conflicts && !conflicts.is_empty() {
+            return Err(errors::scheduling_conflict(&conflicts));
+        }
+        Ok(conflicts)
+    }
+
+    fn stage_write(&self, write: PendingWrite, mut preview: WritePreview) -> WritePreview {
+        let confirmation_token = Uuid::new_v4().simple().to_string();
+        preview.confirmation_token = Some(confirmation_token.clone());
+        preview.expires_at = Some(write.created_at + Duration::minutes(PENDING_WRITE_TTL_MINUTES));
+
+        let mut pending = self.state.pending_writes.write();
+        pending.retain(|_, staged| !staged.is_expired());
+        pending.insert(confirmation_token, write);
+        preview
+    }
+
+    async fn execute_write(
+        &self,
+        token: &UserToken,
+        write: &PendingWrite,
+    ) -> Result<EventWriteResult, ErrorData> {
+        if let Some(text) = write.quick_add_text.as_deref() {
+            return self
+                .state
+                .google_calendar
+                .quick_add(
+                    token,
+                    &write.payload.calendar_id,
+                    text,
+                    write.payload.send_updates,
+                )
+                .await
+                .map(EventWriteResult::from)
+                .map_err(|err| Self::handle_calendar_error("quick_add", err));
+        }
+        match write.operation {
+            WriteOperation::Create => match write.idempotency_key.as_deref() {
+                Some(key) => self.create_idempotent(token, write, key).await,
+                None => self
+                    .state
+                    .google_calendar
+                    .create_event(token, &write.payload)
+                    .await
+                    .map(EventWriteResult::from)
+                    .map_err(|err| Self::handle_calendar_error("create_event", err)),
+            },
+            WriteOperation::Update => {
+                let event_id = write.event_id.as_deref().ok_or_else(|| {
+                    ErrorData::invalid_params("event_id is required for updates", None)
+                })?;
+                self.state
+                    .google_calendar
+                    .update_event(
+                        token,
+                        event_id,
+                        &write.payload,
+                        write.if_match_etag.as_deref(),
+                    )
+                    .await
+                    .map(EventWriteResult::from)
+                    .map_err(|err| Self::handle_calendar_error("update_event", err))
+            }
+            WriteOperation::Move => {
+                let (Some(event_id), Some(destination)) = (
+                    write.event_id.as_deref(),
+                    write.destination_calendar_id.as_deref(),
+                ) else {
+                    return Err(ErrorData::invalid_params(
+                        "event_id and destination_calendar_id are required for moves",
+                        None,
+                    ));
+                };
+                self.state
+                    .google_calendar
+                    .move_event(
+                        token,
+                        &write.payload.calendar_id,
+                        event_id,
+                        destination,
+                        write.payload.send_updates,
+                    )
+                    .await
+                    .map(EventWriteResult::from)
+                    .map_err(|err| Self::handle_calendar_error("move_event", err))
+            }
+        }
+    }
+
+    /// Rejects writes to `calendar_id` unless the user's calendar list grants writer access.
+    async fn ensure_writable(&self, token: &UserToken, calendar_id: &str) -> Result<(), ErrorData> {
+        let entry = self
+            .state
+            .google_calendar
+            .calendar_list_entry(token, calendar_id)
+            .await;
+        check_write_access(calendar_id, entry)
+    }
+
+    /// Creates an event at most once per `idempotency_key`.
+    ///
+    /// The key maps to a deterministic Google event id and is recorded in user data, so a retried
+    /// create returns the original event instead of a duplicate.
+    async fn create_idempotent(
+        &self,
+        token: &UserToken,
+        write: &PendingWrite,
+        idempotency_key: &str,
+    ) -> Result<EventWriteResult, ErrorData> {
+        if idempotency_key.trim().is_empty() {
+            return Err(ErrorData::invalid_params(
+                "idempotency_key must not be empty",
+                None,
+            ));
+        }
+
+        let client = &self.state.google_calendar;
+        let record_key = format!("idempotency:{idempotency_key}");
+        let record = self
+            .state
+            .user_data
+            .get(&write.user_id, &record_key)
+            .await
+            .map_err(|err| internal_error("idempotency lookup", err))?
+            .and_then(|value| serde_json::from_value::<IdempotencyRecord>(value).ok());
+
+        if let Some(record) = record {
+            let params = GetEventParams {
+                event_id: record.event_id,
+                calendar_id: Some(record.calendar_id),
+            };
+            let event = client
+                .get_event(token, &params)
+                .await
+                .map_err(|err| Self::handle_calendar_error("get_event", err))?;
+            return Ok(EventWriteResult {
+                already_created: true,
+                ..EventWriteResult::from(event)
+            });
         }
+
+        let event_id = idempotent_event_id(&write.user_id, idempotency_key);
+        let (event, created) = client
+            .create_event_with_id(token, &write.payload, &event_id)
+            .await
+            .map_err(|err| Self::handle_calendar_error("create_event", err))?;
+
+        let record = IdempotencyRecord {
+            event_id,
+            calendar_id: client
+                .resolve_calendar(&write.payload.calendar_id)
+                .to_owned(),
+            created_at: Utc::now(),
+        };
+        let value = serde_json::to_value(&record)
+            .map_err(|err| internal_error("idempotency record", err.into()))?;
+        self.state
+            .user_data
+            .put(&write.user_id, &record_key, value)
+            .await
+            .map_err(|err| internal_error("idempotency record", err))?;
+
+        Ok(EventWriteResult {
+            already_created: !created,
+            ..EventWriteResult::from(event)
+        })
     }
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+struct IdempotencyRecord {
+    event_id: String,
+    calendar_id: String,
+    created_at: DateTime<Utc>,
+}
+
 #[tool_router]
 impl CalendarService {
     #[tool(
         name = "google_calendar_list_events",
-        description = "List calendar events for the authorized user",
+        description = "List calendar events for the authorized user. Set auto_paginate to follow page tokens up to max_items, fields to limit returned event fields, and compact for id/summary/start/end/attendee_count only",
         annotations(
             title = "List Calendar Events",
             read_only_hint = true,
@@ -152,16 +814,44 @@ impl CalendarService {
     pub async fn list_events(
         &self,
         extensions: Extensions,
-        Parameters(ListEventsInput { user_id, params }): Parameters<ListEventsInput>,
-    ) -> Result<Json<ListEventsResponse>, ErrorData> {
+        Parameters(ListEventsInput {
+            user_id,
+            mut params,
+            options,
+        }): Parameters<ListEventsInput>,
+    ) -> Result<Json<ListEventsResult>, ErrorData> {
+        let user_id = self.resolve_user_id(user_id)?;
         let token = self.ensure_token(&user_id, &extensions).await?;
-        let data = self
-            .state
-            .google_calendar
-            .list_events(&token, &params)
-            .await
-            .map_err(|err| Self::handle_calendar_error("list_events", err))?;
-        Ok(Json(data))
+        if options.compact && params.fields.is_none() {
+            params.fields = Some(CompactEvent::FIELDS.to_owned());
+        }
+
+        let (tz, _) = self.user_time_zone(&user_id, &token).await;
+        let mut resolver = TimeResolver::new(Utc::now(), tz);
+        resolver
+            .time_input("time_min", &mut params.time_min)
+            .and_then(|_| resolver.time_input("time_max", &mut params.time_max))
+            .map_err(time_expr_error)?;
+
+        let client = &self.state.google_calendar;
+        let mut data = if options.auto_paginate {
+            let max_items = options
+                .max_items
+                .unwrap_or(DEFAULT_AUTO_PAGINATE_ITEMS)
+                .clamp(1, MAX_AUTO_PAGINATE_ITEMS) as usize;
+            client.list_all_events(&token, &params, max_items).await
+        } else {
+            client.list_events(&token, &params).await
+        }
+        .map_err(|err| Self::handle_calendar_error("list_events", err))?;
+        for event in &mut data.items {
+            event.localize(tz);
+        }
+
+        Ok(Json(ListEventsResult {
+            resolved_times: resolver.into_resolved(),
+            ..ListEventsResult::new(data, options)
+        }))
     }
 
     #[tool(
@@ -182,23 +872,25 @@ impl CalendarService {
             calendar_id,
         }): Parameters<GetEventInput>,
     ) -> Result<Json<CalendarEvent>, ErrorData> {
+        let user_id = self.resolve_user_id(user_id)?;
         let token = self.ensure_token(&user_id, &extensions).await?;
         let params = GetEventParams {
             event_id,
             calendar_id,
         };
-        let event = self
+        let mut event = self
             .state
             .google_calendar
             .get_event(&token, &params)
             .await
             .map_err(|err| Self::handle_calendar_error("get_event", err))?;
+        event.localize(self.user_time_zone(&user_id, &token).await.0);
         Ok(Json(event))
     }
 
     #[tool(
         name = "google_calendar_create_event",
-        description = "Create a new calendar event",
+        description = "Create a new calendar event. Set dry_run to preview the request body and conflicts without creating it. Pass an idempotency_key so retries return the original event instead of a duplicate",
         annotations(
             title = "Create Calendar Event",
             read_only_hint = false,
@@ -208,22 +900,54 @@ impl CalendarService {
     )]
     pub async fn create_event(
         &self,
+        peer: ClientPeer,
         extensions: Extensions,
-        Parameters(CreateEventInput { user_id, payload }): Parameters<CreateEventInput>,
-    ) -> Result<Json<CalendarEvent>, ErrorData> {
+        Parameters(CreateEventInput {
+            user_id,
+            dry_run,
+            idempotency_key,
+            allow_conflicts,
+            mut payload,
+        }): Parameters<CreateEventInput>,
+    ) -> Result<Json<EventWriteResult>, ErrorData> {
+        let user_id = self.resolve_user_id(user_id)?;
         let token = self.ensure_token(&user_id, &extensions).await?;
-        let event = self
-            .state
-            .google_calendar
-            .create_event(&token, &payload)
-            .await
-            .map_err(|err| Self::handle_calendar_error("create_event", err))?;
-        Ok(Json(event))
+        let (tz, _) = self.user_time_zone(&user_id, &token).await;
+        let resolved_times = resolve_payload_times(tz, &mut payload)?;
+        self.apply_write_defaults(&mut payload);
+        let write = PendingWrite {
+            user_id,
+            operation: WriteOperation::Create,
+            event_id: None,
+            payload,
+            idempotency_key,
+            if_match_etag: None,
+            destination_calendar_id: None,
+            quick_add_text: None,
+            created_at: Utc::now(),
+        };
+        let mut result = if dry_run {
+            self.dry_run_write(&token, &write).await?
+        } else {
+            let conflicts = self
+                .check_write_conflicts(&token, &write, allow_conflicts, tz)
+                .await?;
+            self.confirm_write(&peer, &token, &write).await?;
+            EventWriteResult {
+                conflicts,
+                ..self.execute_write(&token, &write).await?
+            }
+        };
+        result.localize(tz);
+        Ok(Json(EventWriteResult {
+            resolved_times,
+            ..result
+        }))
     }
 
     #[tool(
         name = "google_calendar_update_event",
-        description = "Update an existing calendar event (no deletion)",
+        description = "Update an existing calendar event (no deletion). Set dry_run to preview the merged result, diff and conflicts without saving",
         annotations(
             title = "Update Calendar Event",
             read_only_hint = false,
@@ -233,80 +957,2411 @@ impl CalendarService {
     )]
     pub async fn update_event(
         &self,
+        peer: ClientPeer,
         extensions: Extensions,
         Parameters(UpdateEventInput {
             user_id,
             event_id,
-            payload,
+            dry_run,
+            if_match_etag,
+            allow_conflicts,
+            mut payload,
         }): Parameters<UpdateEventInput>,
-    ) -> Result<Json<CalendarEvent>, ErrorData> {
+    ) -> Result<Json<EventWriteResult>, ErrorData> {
+        let user_id = self.resolve_user_id(user_id)?;
+        let token = self.ensure_token(&user_id, &extensions).await?;
+        let (tz, _) = self.user_time_zone(&user_id, &token).await;
+        let resolved_times = resolve_payload_times(tz, &mut payload)?;
+        self.apply_write_defaults(&mut payload);
+        let write = PendingWrite {
+            user_id,
+            operation: WriteOperation::Update,
+            event_id: Some(event_id),
+            payload,
+            idempotency_key: None,
+            if_match_etag,
+            destination_calendar_id: None,
+            quick_add_text: None,
+            created_at: Utc::now(),
+        };
+        let mut result = if dry_run {
+            self.dry_run_write(&token, &write).await?
+        } else {
+            let conflicts = self
+                .check_write_conflicts(&token, &write, allow_conflicts, tz)
+                .await?;
+            self.confirm_write(&peer, &token, &write).await?;
+            EventWriteResult {
+                conflicts,
+                ..self.execute_write(&token, &write).await?
+            }
+        };
+        result.localize(tz);
+        Ok(Json(EventWriteResult {
+            resolved_times,
+            ..result
+        }))
+    }
+
+    #[tool(
+        name = "google_calendar_move_event",
+        description = "Move an event to another calendar, e.g. from a personal to a team calendar. Requires writer access on the destination",
+        annotations(
+            title = "Move Calendar Event",
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = false
+        )
+    )]
+    pub async fn move_event(
+        &self,
+        peer: ClientPeer,
+        extensions: Extensions,
+        Parameters(MoveEventInput {
+            user_id,
+            event_id,
+            calendar_id,
+            destination_calendar_id,
+            send_updates,
+        }): Parameters<MoveEventInput>,
+    ) -> Result<Json<EventWriteResult>, ErrorData> {
+        let user_id = self.resolve_user_id(user_id)?;
+        let token = self.ensure_token(&user_id, &extensions).await?;
+        let client = &self.state.google_calendar;
+        let source = client.resolve_calendar(&calendar_id).to_owned();
+        if source == destination_calendar_id {
+            return Err(ErrorData::invalid_params(
+                "destination_calendar_id must differ from the event's calendar",
+                None,
+            ));
+        }
+        self.ensure_writable(&token, &destination_calendar_id)
+            .await?;
+
+        let mut payload = EventPayload {
+            calendar_id: Some(source),
+            send_updates,
+            ..Default::default()
+        };
+        self.apply_write_defaults(&mut payload);
+        let write = PendingWrite {
+            user_id,
+            operation: WriteOperation::Move,
+            event_id: Some(event_id),
+            payload,
+            idempotency_key: None,
+            if_match_etag: None,
+            destination_calendar_id: Some(destination_calendar_id),
+            quick_add_text: None,
+            created_at: Utc::now(),
+        };
+        self.confirm_write(&peer, &token, &write).await?;
+        let mut result = self.execute_write(&token, &write).await?;
+        result.localize(self.user_time_zone(&write.user_id, &token).await.0);
+        Ok(Json(result))
+    }
+
+    #[tool(
+        name = "google_calendar_respond_to_event",
+        description = "RSVP to an invitation as accepted, declined or tentative, with an optional comment. Only the authorized user's attendee entry changes",
+        annotations(
+            title = "Respond to Invitation",
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = true
+        )
+    )]
+    pub async fn respond_to_event(
+        &self,
+        peer: ClientPeer,
+        extensions: Extensions,
+        Parameters(RespondToEventInput {
+            user_id,
+            event_id,
+            calendar_id,
+            response,
+            comment,
+            send_updates,
+        }): Parameters<RespondToEventInput>,
+    ) -> Result<Json<EventWriteResult>, ErrorData> {
+        let user_id = self.resolve_user_id(user_id)?;
         let token = self.ensure_token(&user_id, &extensions).await?;
+        let params = GetEventParams {
+            event_id: event_id.clone(),
+            calendar_id: calendar_id.clone(),
+        };
         let event = self
             .state
             .google_calendar
-            .update_event(&token, &event_id, &payload)
+            .get_event(&token, &params)
             .await
-            .map_err(|err| Self::handle_calendar_error("update_event", err))?;
-        Ok(Json(event))
+            .map_err(|err| Self::handle_calendar_error("get_event", err))?;
+        let attendees = event.with_response(response, comment).ok_or_else(|| {
+            ErrorData::invalid_request("the user is not on this event's guest list", None)
+        })?;
+
+        let mut payload = EventPayload {
+            calendar_id,
+            attendees: Some(attendees),
+            send_updates,
+            ..Default::default()
+        };
+        self.apply_write_defaults(&mut payload);
+        let write = PendingWrite {
+            user_id,
+            operation: WriteOperation::Update,
+            event_id: Some(event_id),
+            payload,
+            idempotency_key: None,
+            // The whole guest list is rewritten, so guests added meanwhile must not be dropped.
+            if_match_etag: event.etag,
+            destination_calendar_id: None,
+            quick_add_text: None,
+            created_at: Utc::now(),
+        };
+        self.confirm_write(&peer, &token, &write).await?;
+        let mut result = self.execute_write(&token, &write).await?;
+        result.localize(self.user_time_zone(&write.user_id, &token).await.0);
+        Ok(Json(result))
     }
-}
 
-#[tool_handler]
-impl ServerHandler for CalendarService {
-    fn get_info(&self) -> ServerInfo {
-        ServerInfo {
-            protocol_version: ProtocolVersion::default(),
-            capabilities: ServerCapabilities::builder().enable_tools().build(),
-            server_info: Implementation {
+    #[tool(
+        name = "google_calendar_attach_file",
+        description = "Attach a Google Drive file (by its link) to an event, keeping the existing attachments. Attaching a file that is already there changes nothing",
+        annotations(
+            title = "Attach File to Event",
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = true
+        )
+    )]
+    pub async fn attach_file(
+        &self,
+        peer: ClientPeer,
+        extensions: Extensions,
+        Parameters(AttachFileInput {
+            user_id,
+            event_id,
+            calendar_id,
+            file_url,
+            title,
+            mime_type,
+            icon_link,
+        }): Parameters<AttachFileInput>,
+    ) -> Result<Json<EventWriteResult>, ErrorData> {
+        let user_id = self.resolve_user_id(user_id)?;
+        if !matches!(url::Url::parse(&file_url), Ok(url) if url.scheme() == "https") {
+            return Err(ErrorData::invalid_params(
+                "file_url must be an https link to the file, e.g. a Google Drive share link",
+                None,
+            ));
+        }
+        let token = self.ensure_token(&user_id, &extensions).await?;
+        let (tz, _) = self.user_time_zone(&user_id, &token).await;
+        let params = GetEventParams {
+            event_id: event_id.clone(),
+            calendar_id: calendar_id.clone(),
+        };
+        let mut event = self
+            .state
+            .google_calendar
+            .get_event(&token, &params)
+            .await
+            .map_err(|err| Self::handle_calendar_error("get_event", err))?;
+        let attachment = EventAttachment {
+            file_url,
+            title,
+            mime_type,
+            icon_link,
+            file_id: None,
+        };
+        let Some(attachments) = event.with_attachment(attachment) else {
+            event.localize(tz);
+            return Ok(Json(EventWriteResult::from(event)));
+        };
+        if attachments.len() > MAX_EVENT_ATTACHMENTS {
+            return Err(ErrorData::invalid_request(
+                format!("events can have at most {MAX_EVENT_ATTACHMENTS} attachments"),
+                None,
+            ));
+        }
+
+        let mut payload = EventPayload {
+            calendar_id,
+            attachments: Some(attachments),
+            ..Default::default()
+        };
+        self.apply_write_defaults(&mut payload);
+        let write = PendingWrite {
+            user_id,
+            operation: WriteOperation::Update,
+            event_id: Some(event_id),
+            payload,
+            idempotency_key: None,
+            // The whole list is rewritten, so files attached meanwhile must not be dropped.
+            if_match_etag: event.etag,
+            destination_calendar_id: None,
+            quick_add_text: None,
+            created_at: Utc::now(),
+        };
+        self.confirm_write(&peer, &token, &write).await?;
+        let mut result = self.execute_write(&token, &write).await?;
+        result.localize(tz);
+        Ok(Json(result))
+    }
+
+    #[tool(
+        name = "google_calendar_prepare_write",
+        description = "Stage a create or update for user confirmation; returns a readable diff and a confirmation token for google_calendar_commit_write",
+        annotations(
+            title = "Prepare Calendar Write",
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = false
+        )
+    )]
+    pub async fn prepare_write(
+        &self,
+        extensions: Extensions,
+        Parameters(PrepareWriteInput {
+            user_id,
+            operation,
+            event_id,
+            destination_calendar_id,
+            mut payload,
+        }): Parameters<PrepareWriteInput>,
+    ) -> Result<Json<WritePreview>, ErrorData> {
+        let user_id = self.resolve_user_id(user_id)?;
+        if operation != WriteOperation::Create && event_id.is_none() {
+            return Err(ErrorData::invalid_params(
+                "event_id is required for updates and moves",
+                None,
+            ));
+        }
+        let token = self.ensure_token(&user_id, &extensions).await?;
+        if operation == WriteOperation::Move {
+            let destination = destination_calendar_id.as_deref().ok_or_else(|| {
+                ErrorData::invalid_params("destination_calendar_id is required for moves", None)
+            })?;
+            self.ensure_writable(&token, destination).await?;
+            let source = self
+                .state
+                .google_calendar
+                .resolve_calendar(&payload.calendar_id);
+            payload.calendar_id = Some(source.to_owned());
+        }
+        let (tz, _) = self.user_time_zone(&user_id, &token).await;
+        resolve_payload_times(tz, &mut payload)?;
+        self.apply_write_defaults(&mut payload);
+        let write = PendingWrite {
+            user_id,
+            operation,
+            event_id,
+            payload,
+            idempotency_key: None,
+            if_match_etag: None,
+            destination_calendar_id,
+            quick_add_text: None,
+            created_at: Utc::now(),
+        };
+        let current = self.current_event(&token, &write).await?;
+        let preview = WritePreview::new(&write, current.as_ref());
+        Ok(Json(self.stage_write(write, preview)))
+    }
+
+    #[tool(
+        name = "google_calendar_commit_write",
+        description = "Apply a write staged by google_calendar_prepare_write once the user has confirmed it",
+        annotations(
+            title = "Commit Calendar Write",
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = false
+        )
+    )]
+    pub async fn commit_write(
+        &self,
+        extensions: Extensions,
+        Parameters(CommitWriteInput {
+            user_id,
+            confirmation_token,
+        }): Parameters<CommitWriteInput>,
+    ) -> Result<Json<EventWriteResult>, ErrorData> {
+        let user_id = self.resolve_user_id(user_id)?;
+        let write = take_pending_write(
+            &mut self.state.pending_writes.write(),
+            &confirmation_token,
+            &user_id,
+        )
+        .ok_or_else(|| ErrorData::invalid_params("unknown or expired confirmation_token", None))?;
+        let token = self.ensure_token(&user_id, &extensions).await?;
+        let mut result = self.execute_write(&token, &write).await?;
+        result.localize(self.user_time_zone(&user_id, &token).await.0);
+        Ok(Json(result))
+    }
+
+    #[tool(
+        name = "google_calendar_quick_add",
+        description = "Create an event from free text such as \"Lunch with Sam tomorrow 1pm at Cafe Rio\" using Google's parser. Set confirm to have the user review the parsed event and keep or discard it",
+        annotations(
+            title = "Quick Add Calendar Event",
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = false
+        )
+    )]
+    pub async fn quick_add(
+        &self,
+        peer: ClientPeer,
+        extensions: Extensions,
+        Parameters(QuickAddInput {
+            user_id,
+            text,
+            calendar_id,
+            confirm,
+        }): Parameters<QuickAddInput>,
+    ) -> Result<Json<EventWriteResult>, ErrorData> {
+        let user_id = self.resolve_user_id(user_id)?;
+        if text.trim().is_empty() {
+            return Err(ErrorData::invalid_params("text must not be empty", None));
+        }
+        let token = self.ensure_token(&user_id, &extensions).await?;
+        let (tz, _) = self.user_time_zone(&user_id, &token).await;
+        let client = &self.state.google_calendar;
+        let mut payload = EventPayload {
+            calendar_id: Some(client.resolve_calendar(&calendar_id).to_owned()),
+            ..Default::default()
+        };
+        // Guests named in the text are invited, so the server's notification default applies.
+        self.apply_write_defaults(&mut payload);
+
+        let write = PendingWrite {
+            user_id,
+            operation: WriteOperation::Create,
+            event_id: None,
+            payload,
+            idempotency_key: None,
+            if_match_etag: None,
+            destination_calendar_id: None,
+            quick_add_text: Some(text.clone()),
+            created_at: Utc::now(),
+        };
+        let event = client
+            .quick_add(
+                &token,
+                &write.payload.calendar_id,
+                &text,
+                write.payload.send_updates,
+            )
+            .await
+            .map_err(|err| Self::handle_calendar_error("quick_add", err))?;
+        let policy = self.state.config.writes.confirmation;
+        if confirm || requires_confirmation(policy, Some(&event), &write.payload) {
+            self.review_quick_add(&peer, &token, &write, &event).await?;
+        }
+        let mut result = EventWriteResult::from(event);
+        result.localize(tz);
+        Ok(Json(result))
+    }
+
+    #[tool(
+        name = "google_calendar_batch",
+        description = "Run up to 50 get/create/update operations in one round trip; each item succeeds or fails independently",
+        annotations(
+            title = "Batch Calendar Operations",
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = false
+        )
+    )]
+    pub async fn batch(
+        &self,
+        extensions: Extensions,
+        Parameters(BatchInput {
+            user_id,
+            mut operations,
+        }): Parameters<BatchInput>,
+    ) -> Result<Json<BatchResult>, ErrorData> {
+        let user_id = self.resolve_user_id(user_id)?;
+        let has_writes = operations
+            .iter()
+            .any(|operation| !matches!(operation, BatchOperation::Get { .. }));
+        if has_writes && self.state.config.writes.confirmation != ConfirmationPolicy::Never {
+            return Err(ErrorData::invalid_request(
+                "batched writes bypass confirmation and are disabled while writes.confirmation is set; use google_calendar_create_event / google_calendar_update_event",
+                None,
+            ));
+        }
+
+        let token = self.ensure_token(&user_id, &extensions).await?;
+        let (tz, _) = self.user_time_zone(&user_id, &token).await;
+        let mut resolver = TimeResolver::new(Utc::now(), tz);
+        for (index, operation) in operations.iter_mut().enumerate() {
+            if let BatchOperation::Create { payload } | BatchOperation::Update { payload, .. } =
+                operation
+            {
+                self.apply_write_defaults(payload);
+                resolver
+                    .event_time(&format!("operations[{index}].start"), &mut payload.start)
+                    .and_then(|_| {
+                        resolver.event_time(&format!("operations[{index}].end"), &mut payload.end)
+                    })
+                    .map_err(time_expr_error)?;
+            }
+        }
+
+        let mut results = self
+            .state
+            .google_calendar
+            .batch(&token, &operations)
+            .await
+            .map_err(|err| Self::handle_calendar_error("batch", err))?;
+
+        for event in results.iter_mut().filter_map(|item| item.event.as_mut()) {
+            event.localize(tz);
+        }
+
+        let failed = results.iter().filter(|item| item.error.is_some()).count();
+        Ok(Json(BatchResult {
+            succeeded: results.len() - failed,
+            failed,
+            results,
+            resolved_times: resolver.into_resolved(),
+        }))
+    }
+
+    #[tool(
+        name = "google_calendar_set_time_zone",
+        description = "Show or override the time zone used to display event times and interpret relative times. Pass time_zone (IANA name) to override, clear to follow the Google Calendar setting again, or neither to read the current zone",
+        annotations(
+            title = "Set Time Zone",
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = true
+        )
+    )]
+    pub async fn set_time_zone(
+        &self,
+        extensions: Extensions,
+        Parameters(SetTimeZoneInput {
+            user_id,
+            time_zone,
+            clear,
+        }): Parameters<SetTimeZoneInput>,
+    ) -> Result<Json<UserTimeZone>, ErrorData> {
+        let user_id = self.resolve_user_id(user_id)?;
+        let time_zone = time_zone.as_deref().map(parse_time_zone).transpose()?;
+        let token = self.ensure_token(&user_id, &extensions).await?;
+        if let Some(tz) = time_zone {
+            self.state
+                .user_data
+                .put(
+                    &user_id,
+                    TIME_ZONE_OVERRIDE_KEY,
+                    serde_json::Value::String(tz.name().to_owned()),
+                )
+                .await
+                .map_err(|err| internal_error("time zone override", err))?;
+        } else if clear {
+            self.state
+                .user_data
+                .remove(&user_id, TIME_ZONE_OVERRIDE_KEY)
+                .await
+                .map_err(|err| internal_error("time zone override", err))?;
+        }
+
+        let (tz, source) = self.user_time_zone(&user_id, &token).await;
+        Ok(Json(UserTimeZone {
+            time_zone: tz.name().to_owned(),
+            source,
+        }))
+    }
+
+    #[tool(
+        name = "google_calendar_convert_time",
+        description = "Convert a time (RFC3339 or an expression like \"tomorrow 3pm\") into the user's time zone and other IANA zones, e.g. to find a meeting slot across offices",
+        annotations(
+            title = "Convert Time Between Zones",
+            read_only_hint = true,
+            destructive_hint = false
+        )
+    )]
+    pub async fn convert_time(
+        &self,
+        extensions: Extensions,
+        Parameters(ConvertTimeInput {
+            user_id,
+            time,
+            from_time_zone,
+            to_time_zones,
+        }): Parameters<ConvertTimeInput>,
+    ) -> Result<Json<ConvertTimeResult>, ErrorData> {
+        let user_id = self.resolve_user_id(user_id)?;
+        let targets = to_time_zones
+            .iter()
+            .map(|name| parse_time_zone(name))
+            .collect::<Result<Vec<_>, _>>()?;
+        let token = self.ensure_token(&user_id, &extensions).await?;
+        let (user_tz, _) = self.user_time_zone(&user_id, &token).await;
+        let from_tz = match from_time_zone {
+            Some(name) => parse_time_zone(&name)?,
+            None => user_tz,
+        };
+
+        let mut resolver = TimeResolver::new(Utc::now(), from_tz);
+        let mut input = Some(TimeInput::Expression(time));
+        resolver
+            .time_input("time", &mut input)
+            .map_err(time_expr_error)?;
+        let at = input
+            .and_then(|input| input.absolute())
+            .expect("time was resolved above");
+
+        let mut zones = vec![user_tz, from_tz];
+        zones.extend(targets);
+        let mut conversions: Vec<ZonedTime> = Vec::with_capacity(zones.len());
+        for tz in zones {
+            if conversions.iter().all(|seen| seen.time_zone != tz.name()) {
+                conversions.push(ZonedTime::new(at, tz));
+            }
+        }
+
+        Ok(Json(ConvertTimeResult {
+            utc: at,
+            conversions,
+        }))
+    }
+
+    #[tool(
+        name = "google_calendar_list_colors",
+        description = "List the calendar and event colors; use an event color's id as color_id when creating or updating events",
+        annotations(
+            title = "List Calendar Colors",
+            read_only_hint = true,
+            destructive_hint = false
+        )
+    )]
+    pub async fn list_colors(
+        &self,
+        extensions: Extensions,
+        Parameters(ListColorsInput { user_id }): Parameters<ListColorsInput>,
+    ) -> Result<Json<Colors>, ErrorData> {
+        let user_id = self.resolve_user_id(user_id)?;
+        let token = self.ensure_token(&user_id, &extensions).await?;
+        let colors = self
+            .state
+            .google_calendar
+            .colors(&token)
+            .await
+            .map_err(|err| Self::handle_calendar_error("list_colors", err))?;
+        Ok(Json(colors))
+    }
+
+    #[tool(
+        name = "google_calendar_set_out_of_office",
+        description = "Block time as out of office on the primary calendar, optionally declining conflicting invitations with a message",
+        annotations(
+            title = "Set Out of Office",
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = false
+        )
+    )]
+    pub async fn set_out_of_office(
+        &self,
+        peer: ClientPeer,
+        extensions: Extensions,
+        Parameters(SetOutOfOfficeInput {
+            user_id,
+            start,
+            end,
+            summary,
+            auto_decline_mode,
+            decline_message,
+        }): Parameters<SetOutOfOfficeInput>,
+    ) -> Result<Json<EventWriteResult>, ErrorData> {
+        let payload = EventPayload {
+            summary: Some(summary.unwrap_or_else(|| "Out of office".to_owned())),
+            start: Some(start),
+            end: Some(end),
+            event_type: Some(EventType::OutOfOffice),
+            out_of_office_properties: Some(OutOfOfficeProperties {
+                auto_decline_mode,
+                decline_message,
+            }),
+            ..Default::default()
+        };
+        self.create_special_event(&peer, &extensions, user_id, payload)
+            .await
+    }
+
+    #[tool(
+        name = "google_calendar_block_focus_time",
+        description = "Block focus time on the primary calendar, optionally declining conflicting invitations and setting Chat to do not disturb",
+        annotations(
+            title = "Block Focus Time",
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = false
+        )
+    )]
+    pub async fn block_focus_time(
+        &self,
+        peer: ClientPeer,
+        extensions: Extensions,
+        Parameters(BlockFocusTimeInput {
+            user_id,
+            start,
+            end,
+            summary,
+            auto_decline_mode,
+            decline_message,
+            chat_status,
+        }): Parameters<BlockFocusTimeInput>,
+    ) -> Result<Json<EventWriteResult>, ErrorData> {
+        let payload = EventPayload {
+            summary: Some(summary.unwrap_or_else(|| "Focus time".to_owned())),
+            start: Some(start),
+            end: Some(end),
+            event_type: Some(EventType::FocusTime),
+            focus_time_properties: Some(FocusTimeProperties {
+                auto_decline_mode,
+                decline_message,
+                chat_status,
+            }),
+            ..Default::default()
+        };
+        self.create_special_event(&peer, &extensions, user_id, payload)
+            .await
+    }
+
+    #[tool(
+        name = "google_calendar_set_working_location",
+        description = "Record where the user works (home, an office or a custom place) for a time range on the primary calendar",
+        annotations(
+            title = "Set Working Location",
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = false
+        )
+    )]
+    pub async fn set_working_location(
+        &self,
+        peer: ClientPeer,
+        extensions: Extensions,
+        Parameters(SetWorkingLocationInput {
+            user_id,
+            start,
+            end,
+            location,
+            label,
+            building_id,
+            desk_id,
+        }): Parameters<SetWorkingLocationInput>,
+    ) -> Result<Json<EventWriteResult>, ErrorData> {
+        let (summary, properties) = match location {
+            WorkingLocationType::HomeOffice => {
+                ("Home".to_owned(), WorkingLocationProperties::home())
+            }
+            WorkingLocationType::OfficeLocation => (
+                label.clone().unwrap_or_else(|| "Office".to_owned()),
+                WorkingLocationProperties::office(OfficeLocation {
+                    building_id,
+                    desk_id,
+                    label,
+                    ..Default::default()
+                }),
+            ),
+            WorkingLocationType::CustomLocation => {
+                let label = label.unwrap_or_default();
+                (label.clone(), WorkingLocationProperties::custom(label))
+            }
+        };
+        let payload = EventPayload {
+            summary: Some(summary),
+            start: Some(start),
+            end: Some(end),
+            visibility: Some(EventVisibility::Public),
+            transparency: Some(Transparency::Transparent),
+            event_type: Some(EventType::WorkingLocation),
+            working_location_properties: Some(properties),
+            ..Default::default()
+        };
+        self.create_special_event(&peer, &extensions, user_id, payload)
+            .await
+    }
+
+    #[tool(
+        name = "google_calendar_list_acl",
+        description = "List who a calendar is shared with and their roles. Requires acl.enabled and the calendar.acls scope",
+        annotations(
+            title = "List Calendar Sharing",
+            read_only_hint = true,
+            destructive_hint = false
+        )
+    )]
+    pub async fn list_acl(
+        &self,
+        extensions: Extensions,
+        Parameters(ListAclInput {
+            user_id,
+            calendar_id,
+        }): Parameters<ListAclInput>,
+    ) -> Result<Json<AclRules>, ErrorData> {
+        let user_id = self.resolve_user_id(user_id)?;
+        let token = self.ensure_token(&user_id, &extensions).await?;
+        let rules = self
+            .state
+            .google_calendar
+            .list_acl(&token, &calendar_id)
+            .await
+            .map_err(|err| Self::handle_calendar_error("list_acl", err))?;
+        Ok(Json(rules))
+    }
+
+    #[tool(
+        name = "google_calendar_share_calendar",
+        description = "Share a calendar with a user, group, domain or everyone (scope type \"default\"). An existing rule for the same scope is replaced",
+        annotations(
+            title = "Share Calendar",
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = false
+        )
+    )]
+    pub async fn share_calendar(
+        &self,
+        extensions: Extensions,
+        Parameters(ShareCalendarInput {
+            user_id,
+            calendar_id,
+            scope,
+            role,
+            send_notifications,
+        }): Parameters<ShareCalendarInput>,
+    ) -> Result<Json<AclRule>, ErrorData> {
+        let user_id = self.resolve_user_id(user_id)?;
+        self.refuse_unconfirmed("google_calendar_share_calendar")?;
+        let token = self.ensure_token(&user_id, &extensions).await?;
+        let rule = self
+            .state
+            .google_calendar
+            .insert_acl(&token, &calendar_id, scope, role, send_notifications)
+            .await
+            .map_err(|err| Self::handle_calendar_error("share_calendar", err))?;
+        Ok(Json(rule))
+    }
+
+    #[tool(
+        name = "google_calendar_update_acl",
+        description = "Change the role of an existing sharing rule, identified by the rule id from google_calendar_list_acl",
+        annotations(
+            title = "Update Calendar Sharing",
+            read_only_hint = false,
+            destructive_hint = true,
+            idempotent_hint = true
+        )
+    )]
+    pub async fn update_acl(
+        &self,
+        extensions: Extensions,
+        Parameters(UpdateAclInput {
+            user_id,
+            calendar_id,
+            rule_id,
+            role,
+        }): Parameters<UpdateAclInput>,
+    ) -> Result<Json<AclRule>, ErrorData> {
+        let user_id = self.resolve_user_id(user_id)?;
+        self.refuse_unconfirmed("google_calendar_update_acl")?;
+        let token = self.ensure_token(&user_id, &extensions).await?;
+        let rule = self
+            .state
+            .google_calendar
+            .patch_acl(&token, &calendar_id, &rule_id, role)
+            .await
+            .map_err(|err| Self::handle_calendar_error("update_acl", err))?;
+        Ok(Json(rule))
+    }
+
+    #[tool(
+        name = "google_calendar_remove_acl",
+        description = "Stop sharing a calendar with the scope of a rule, identified by the rule id from google_calendar_list_acl",
+        annotations(
+            title = "Remove Calendar Sharing",
+            read_only_hint = false,
+            destructive_hint = true,
+            idempotent_hint = true
+        )
+    )]
+    pub async fn remove_acl(
+        &self,
+        extensions: Extensions,
+        Parameters(RemoveAclInput {
+            user_id,
+            calendar_id,
+            rule_id,
+        }): Parameters<RemoveAclInput>,
+    ) -> Result<Json<AclRemoved>, ErrorData> {
+        let user_id = self.resolve_user_id(user_id)?;
+        self.refuse_unconfirmed("google_calendar_remove_acl")?;
+        let token = self.ensure_token(&user_id, &extensions).await?;
+        let client = &self.state.google_calendar;
+        client
+            .delete_acl(&token, &calendar_id, &rule_id)
+            .await
+            .map_err(|err| Self::handle_calendar_error("remove_acl", err))?;
+        Ok(Json(AclRemoved {
+            calendar_id: client.resolve_calendar(&calendar_id).to_owned(),
+            rule_id,
+        }))
+    }
+
+    #[tool(
+        name = "google_calendar_create_calendar",
+        description = "Create a secondary calendar, e.g. for a project. The time zone defaults to the user's",
+        annotations(
+            title = "Create Calendar",
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = false
+        )
+    )]
+    pub async fn create_calendar(
+        &self,
+        extensions: Extensions,
+        Parameters(CreateCalendarInput {
+            user_id,
+            summary,
+            description,
+            location,
+            time_zone,
+        }): Parameters<CreateCalendarInput>,
+    ) -> Result<Json<Calendar>, ErrorData> {
+        let user_id = self.resolve_user_id(user_id)?;
+        let time_zone = time_zone.as_deref().map(parse_time_zone).transpose()?;
+        let token = self.ensure_token(&user_id, &extensions).await?;
+        let time_zone = match time_zone {
+            Some(tz) => tz,
+            None => self.user_time_zone(&user_id, &token).await.0,
+        };
+        let calendar = Calendar {
+            id: None,
+            summary: Some(summary),
+            description,
+            location,
+            time_zone: Some(time_zone.name().to_owned()),
+        };
+        let created = self
+            .state
+            .google_calendar
+            .create_calendar(&token, &calendar)
+            .await
+            .map_err(|err| Self::handle_calendar_error("create_calendar", err))?;
+        Ok(Json(created))
+    }
+
+    #[tool(
+        name = "google_calendar_update_calendar",
+        description = "Rename a calendar or change its description, location or time zone; unset fields are kept",
+        annotations(
+            title = "Update Calendar",
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = true
+        )
+    )]
+    pub async fn update_calendar(
+        &self,
+        extensions: Extensions,
+        Parameters(UpdateCalendarInput {
+            user_id,
+            calendar_id,
+            summary,
+            description,
+            location,
+            time_zone,
+        }): Parameters<UpdateCalendarInput>,
+    ) -> Result<Json<Calendar>, ErrorData> {
+        let user_id = self.resolve_user_id(user_id)?;
+        let time_zone = time_zone.as_deref().map(parse_time_zone).transpose()?;
+        let token = self.ensure_token(&user_id, &extensions).await?;
+        let patch = Calendar {
+            id: None,
+            summary,
+            description,
+            location,
+            time_zone: time_zone.map(|tz| tz.name().to_owned()),
+        };
+        let updated = self
+            .state
+            .google_calendar
+            .patch_calendar(&token, &calendar_id, &patch)
+            .await
+            .map_err(|err| Self::handle_calendar_error("update_calendar", err))?;
+        Ok(Json(updated))
+    }
+
+    #[tool(
+        name = "google_calendar_delete_calendar",
+        description = "Delete a secondary calendar and all of its events. Cannot be undone; only available when enabled in the server configuration",
+        annotations(
+            title = "Delete Calendar",
+            read_only_hint = false,
+            destructive_hint = true,
+            idempotent_hint = true
+        )
+    )]
+    pub async fn delete_calendar(
+        &self,
+        extensions: Extensions,
+        Parameters(CalendarIdInput {
+            user_id,
+            calendar_id,
+        }): Parameters<CalendarIdInput>,
+    ) -> Result<Json<CalendarActionResult>, ErrorData> {
+        let user_id = self.resolve_user_id(user_id)?;
+        self.refuse_unconfirmed("google_calendar_delete_calendar")?;
+        let token = self.ensure_token(&user_id, &extensions).await?;
+        self.state
+            .google_calendar
+            .delete_calendar(&token, &calendar_id)
+            .await
+            .map_err(|err| Self::handle_calendar_error("delete_calendar", err))?;
+        Ok(Json(CalendarActionResult {
+            calendar_id,
+            action: CalendarAction::Deleted,
+        }))
+    }
+
+    #[tool(
+        name = "google_calendar_subscribe_calendar",
+        description = "Add an existing calendar (e.g. one shared with the user) to their calendar list",
+        annotations(
+            title = "Subscribe to Calendar",
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = true
+        )
+    )]
+    pub async fn subscribe_calendar(
+        &self,
+        extensions: Extensions,
+        Parameters(CalendarIdInput {
+            user_id,
+            calendar_id,
+        }): Parameters<CalendarIdInput>,
+    ) -> Result<Json<CalendarListEntry>, ErrorData> {
+        let user_id = self.resolve_user_id(user_id)?;
+        let token = self.ensure_token(&user_id, &extensions).await?;
+        let entry = self
+            .state
+            .google_calendar
+            .subscribe_calendar(&token, &calendar_id)
+            .await
+            .map_err(|err| Self::handle_calendar_error("subscribe_calendar", err))?;
+        Ok(Json(entry))
+    }
+
+    #[tool(
+        name = "google_calendar_unsubscribe_calendar",
+        description = "Remove a calendar from the user's calendar list without deleting it",
+        annotations(
+            title = "Unsubscribe from Calendar",
+            read_only_hint = false,
+            destructive_hint = true,
+            idempotent_hint = true
+        )
+    )]
+    pub async fn unsubscribe_calendar(
+        &self,
+        extensions: Extensions,
+        Parameters(CalendarIdInput {
+            user_id,
+            calendar_id,
+        }): Parameters<CalendarIdInput>,
+    ) -> Result<Json<CalendarActionResult>, ErrorData> {
+        let user_id = self.resolve_user_id(user_id)?;
+        let token = self.ensure_token(&user_id, &extensions).await?;
+        self.state
+            .google_calendar
+            .unsubscribe_calendar(&token, &calendar_id)
+            .await
+            .map_err(|err| Self::handle_calendar_error("unsubscribe_calendar", err))?;
+        Ok(Json(CalendarActionResult {
+            calendar_id,
+            action: CalendarAction::Unsubscribed,
+        }))
+    }
+
+    #[tool(
+        name = "google_calendar_export_ics",
+        description = "Export the events of a calendar in a time range as an iCalendar (.ics) document, including recurrence rules and attendees",
+        annotations(
+            title = "Export Calendar as ICS",
+            read_only_hint = true,
+            destructive_hint = false
+        )
+    )]
+    pub async fn export_ics(
+        &self,
+        extensions: Extensions,
+        Parameters(ExportIcsInput {
+            user_id,
+            calendar_id,
+            mut time_min,
+            mut time_max,
+            query,
+        }): Parameters<ExportIcsInput>,
+    ) -> Result<Json<ExportIcsResult>, ErrorData> {
+        let user_id = self.resolve_user_id(user_id)?;
+        let token = self.ensure_token(&user_id, &extensions).await?;
+        let (tz, _) = self.user_time_zone(&user_id, &token).await;
+        let mut resolver = TimeResolver::new(Utc::now(), tz);
+        resolver
+            .time_input("time_min", &mut time_min)
+            .and_then(|_| resolver.time_input("time_max", &mut time_max))
+            .map_err(time_expr_error)?;
+
+        // Recurring events are exported once with their rule rather than as instances.
+        let params = ListEventsParams {
+            calendar_id: calendar_id.clone(),
+            time_min,
+            time_max,
+            query,
+            ..Default::default()
+        };
+        let data = self
+            .state
+            .google_calendar
+            .list_all_events(&token, &params, MAX_AUTO_PAGINATE_ITEMS as usize)
+            .await
+            .map_err(|err| Self::handle_calendar_error("list_events", err))?;
+
+        Ok(Json(ExportIcsResult {
+            calendar_id: calendar_id.unwrap_or_else(|| "primary".to_owned()),
+            event_count: data.items.len(),
+            truncated: data.next_page_token.is_some(),
+            ics: ics::write_calendar(data.summary.as_deref(), &data.items),
+            resolved_times: resolver.into_resolved(),
+        }))
+    }
+
+    #[tool(
+        name = "google_calendar_import_ics",
+        description = "Import the events of an iCalendar (.ics) document into a calendar. Events are matched by UID, so importing the same file again updates the earlier copies instead of duplicating them",
+        annotations(
+            title = "Import ICS into Calendar",
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = true
+        )
+    )]
+    pub async fn import_ics(
+        &self,
+        extensions: Extensions,
+        Parameters(ImportIcsInput {
+            user_id,
+            calendar_id,
+            ics,
+        }): Parameters<ImportIcsInput>,
+    ) -> Result<Json<ImportIcsResult>, ErrorData> {
+        let user_id = self.resolve_user_id(user_id)?;
+        if self.state.config.writes.confirmation != ConfirmationPolicy::Never {
+            return Err(ErrorData::invalid_request(
+                "ICS imports write many events without confirmation and are disabled while writes.confirmation is set; use google_calendar_create_event",
+                None,
+            ));
+        }
+
+        let token = self.ensure_token(&user_id, &extensions).await?;
+        let (tz, _) = self.user_time_zone(&user_id, &token).await;
+        let parsed = ics::parse_calendar(&ics, tz)
+            .map_err(|err| ErrorData::invalid_params(format!("invalid ICS: {err}"), None))?;
+        if parsed.events.len() > MAX_IMPORT_EVENTS {
+            return Err(ErrorData::invalid_params(
+                format!(
+                    "the ICS document has {} events; import at most {MAX_IMPORT_EVENTS} at a time",
+                    parsed.events.len()
+                ),
+                None,
+            ));
+        }
+        let client = &self.state.google_calendar;
+        self.ensure_writable(&token, client.resolve_calendar(&calendar_id))
+            .await?;
+
+        let mut results = Vec::with_capacity(parsed.events.len());
+        for mut event in parsed.events {
+            event.payload.calendar_id = calendar_id.clone();
+            let ical_uid = event.ical_uid.clone();
+            let outcome = match client
+                .find_by_ical_uid(&token, &calendar_id, &ical_uid)
+                .await
+            {
+                Ok(Some(existing)) => {
+                    let event_id = existing.id.unwrap_or_default();
+                    let mut patch = event.into_patch();
+                    self.apply_write_defaults(&mut patch);
+                    client
+                        .update_event(&token, &event_id, &patch, None)
+                        .await
+                        .map(|event| (ImportAction::Updated, event))
+                }
+                Ok(None) => client
+                    .import_event(&token, &event.payload)
+                    .await
+                    .map(|event| (ImportAction::Created, event)),
+                Err(err) => Err(err),
+            };
+            results.push(match outcome {
+                Ok((action, event)) => ImportIcsItem {
+                    ical_uid,
+                    action: Some(action),
+                    event_id: event.id,
+                    error: None,
+                },
+                Err(err) => ImportIcsItem {
+                    ical_uid,
+                    action: None,
+                    event_id: None,
+                    error: Some(import_error(err)),
+                },
+            });
+        }
+
+        let count = |action| {
+            results
+                .iter()
+                .filter(|item| item.action == Some(action))
+                .count()
+        };
+        Ok(Json(ImportIcsResult {
+            created: count(ImportAction::Created),
+            updated: count(ImportAction::Updated),
+            failed: results.iter().filter(|item| item.error.is_some()).count(),
+            skipped: parsed.skipped.iter().map(ToString::to_string).collect(),
+            results,
+        }))
+    }
+
+    #[tool(
+        name = "google_calendar_check_conflicts",
+        description = "Check a proposed event or a time range for double-booking: lists busy events that overlap it across the user's selected calendars. Free (transparent), cancelled and declined events are ignored",
+        annotations(
+            title = "Check Calendar Conflicts",
+            read_only_hint = true,
+            destructive_hint = false
+        )
+    )]
+    pub async fn check_conflicts(
+        &self,
+        extensions: Extensions,
+        Parameters(CheckConflictsInput {
+            user_id,
+            event,
+            time_min,
+            time_max,
+            calendar_ids,
+        }): Parameters<CheckConflictsInput>,
+    ) -> Result<Json<CheckConflictsResult>, ErrorData> {
+        let user_id = self.resolve_user_id(user_id)?;
+        let token = self.ensure_token(&user_id, &extensions).await?;
+        let (tz, _) = self.user_time_zone(&user_id, &token).await;
+        let client = &self.state.google_calendar;
+
+        let (proposed, range, target, resolved_times) = match (event, time_min, time_max) {
+            (Some(mut payload), None, None) => {
+                let resolved_times = resolve_payload_times(tz, &mut payload)?;
+                let target = client.resolve_calendar(&payload.calendar_id).to_owned();
+                let event: CalendarEvent = serde_json::from_value(serde_json::Value::Object(
+                    diff::payload_fields(&payload),
+                ))
+                .map_err(|err| ErrorData::invalid_params(err.to_string(), None))?;
+                let range = event
+                    .start
+                    .as_ref()
+                    .and_then(|start| start.date_time)
+                    .zip(event.end.as_ref().and_then(|end| end.date_time));
+                (Some(event), range, target, resolved_times)
+            }
+            (None, Some(time_min), Some(time_max)) => {
+                let (mut time_min, mut time_max) = (Some(time_min), Some(time_max));
+                let mut resolver = TimeResolver::new(Utc::now(), tz);
+                resolver
+                    .time_input("time_min", &mut time_min)
+                    .and_then(|_| resolver.time_input("time_max", &mut time_max))
+                    .map_err(time_expr_error)?;
+                let range = time_min
+                    .and_then(|at| at.absolute())
+                    .zip(time_max.and_then(|at| at.absolute()));
+                (None, range, "primary".to_owned(), resolver.into_resolved())
+            }
+            _ => {
+                return Err(ErrorData::invalid_params(
+                    "pass either event or both time_min and time_max",
+                    None,
+                ))
+            }
+        };
+        let Some((start, end)) = range else {
+            return Err(ErrorData::invalid_params(
+                "conflicts can only be checked for a timed start and end",
+                None,
+            ));
+        };
+        if start >= end {
+            return Err(ErrorData::invalid_params(
+                "the end must be after the start",
+                None,
+            ));
+        }
+
+        let calendar_ids = if calendar_ids.is_empty() {
+            self.conflict_calendars(&token, &target).await?
+        } else {
+            calendar_ids
+        };
+        // A free (transparent) event cannot double-book anyone.
+        let busy = proposed
+            .as_ref()
+            .is_none_or(|event| conflicts::busy_range(event).is_some());
+        let mut conflicts = if busy {
+            client
+                .conflicts_in_range(&token, &calendar_ids, start, end, proposed.as_ref())
+                .await
+                .map_err(|err| Self::handle_calendar_error("list_events", err))?
+        } else {
+            Vec::new()
+        };
+        for conflict in &mut conflicts {
+            conflict.event.localize(tz);
+        }
+
+        Ok(Json(CheckConflictsResult {
+            calendar_ids,
+            conflicts,
+            resolved_times,
+        }))
+    }
+
+    #[tool(
+        name = "google_calendar_agenda",
+        description = "Compact agenda for a day or week in the user's time zone, merged across their selected calendars: events grouped by day with meeting links, RSVP status, travel hints between places and free blocks within working hours. Prefer this over list_events to brief the user on their schedule",
+        annotations(
+            title = "Calendar Agenda",
+            read_only_hint = true,
+            destructive_hint = false
+        )
+    )]
+    pub async fn agenda(
+        &self,
+        extensions: Extensions,
+        Parameters(AgendaInput {
+            user_id,
+            mut date,
+            span,
+            calendar_ids,
+            working_hours,
+            min_free_minutes,
+        }): Parameters<AgendaInput>,
+    ) -> Result<Json<AgendaResult>, ErrorData> {
+        let user_id = self.resolve_user_id(user_id)?;
+        if calendar_ids.len() > MAX_AGENDA_CALENDARS {
+            return Err(ErrorData::invalid_params(
+                format!("an agenda covers at most {MAX_AGENDA_CALENDARS} calendars"),
+                None,
+            ));
+        }
+        let working_hours = working_hours.unwrap_or_default();
+        working_hours
+            .validate()
+            .map_err(|err| Self::handle_calendar_error("agenda", err.into()))?;
+        let token = self.ensure_token(&user_id, &extensions).await?;
+        let (tz, _) = self.user_time_zone(&user_id, &token).await;
+        let now = Utc::now();
+        let mut resolver = TimeResolver::new(now, tz);
+        resolver
+            .time_input("date", &mut date)
+            .map_err(time_expr_error)?;
+
+        let options = AgendaOptions {
+            tz,
+            span,
+            date: date
+                .and_then(|date| date.absolute())
+                .unwrap_or(now)
+                .with_timezone(&tz)
+                .date_naive(),
+            working_hours,
+            min_free: Duration::minutes(
+                min_free_minutes
+                    .map_or(agenda::DEFAULT_MIN_FREE_MINUTES, i64::from)
+                    .max(1),
+            ),
+        };
+        let (time_min, time_max) = options.range();
+        let calendar_ids = if calendar_ids.is_empty() {
+            self.selected_calendars(&token).await?
+        } else {
+            calendar_ids
+        };
+        let mut params = ListEventsParams {
+            time_min: Some(time_min.into()),
+            time_max: Some(time_max.into()),
+            single_events: true,
+            order_by_start_time: true,
+            fields: Some(agenda::AGENDA_FIELDS.to_owned()),
+            ..Default::default()
+        };
+        let mut events = Vec::new();
+        let mut truncated = false;
+        for calendar_id in calendar_ids {
+            params.calendar_id = Some(calendar_id.clone());
+            let data = self
+                .state
+                .google_calendar
+                .list_all_events(&token, &params, MAX_AUTO_PAGINATE_ITEMS as usize)
+                .await
+                .map_err(|err| Self::handle_calendar_error("list_events", err))?;
+            truncated |= data.next_page_token.is_some();
+            events.extend(
+                data.items
+                    .into_iter()
+                    .map(|event| (calendar_id.clone(), event)),
+            );
+        }
+
+        Ok(Json(AgendaResult {
+            agenda: agenda::build_agenda(events, &options),
+            truncated,
+            resolved_times: resolver.into_resolved(),
+        }))
+    }
+
+    #[tool(
+        name = "google_calendar_time_report",
+        description = "Summarize how time was spent in a range instead of listing events: meeting and focus-time hours, back-to-back streaks, meetings outside working hours, top collaborators, recurring-meeting load and declined/unanswered invitations, grouped by day, week or calendar",
+        annotations(
+            title = "Calendar Time Report",
+            read_only_hint = true,
+            destructive_hint = false
+        )
+    )]
+    pub async fn time_report(
+        &self,
+        extensions: Extensions,
+        Parameters(TimeReportInput {
+            user_id,
+            calendar_ids,
+            time_min,
+            time_max,
+            group_by,
+            working_hours,
+            top_collaborators,
+        }): Parameters<TimeReportInput>,
+    ) -> Result<Json<TimeReportResult>, ErrorData> {
+        let user_id = self.resolve_user_id(user_id)?;
+        if calendar_ids.len() > MAX_REPORT_CALENDARS {
+            return Err(ErrorData::invalid_params(
+                format!("a report covers at most {MAX_REPORT_CALENDARS} calendars"),
+                None,
+            ));
+        }
+        let working_hours = working_hours.unwrap_or_default();
+        working_hours
+            .validate()
+            .map_err(|err| Self::handle_calendar_error("time_report", err.into()))?;
+        let token = self.ensure_token(&user_id, &extensions).await?;
+        let (tz, _) = self.user_time_zone(&user_id, &token).await;
+        let mut params = ListEventsParams {
+            time_min: Some(time_min),
+            time_max: Some(time_max),
+            single_events: true,
+            order_by_start_time: true,
+            fields: Some(analytics::REPORT_FIELDS.to_owned()),
+            ..Default::default()
+        };
+        let mut resolver = TimeResolver::new(Utc::now(), tz);
+        resolver
+            .time_input("time_min", &mut params.time_min)
+            .and_then(|_| resolver.time_input("time_max", &mut params.time_max))
+            .map_err(time_expr_error)?;
+
+        let calendar_ids = if calendar_ids.is_empty() {
+            vec!["primary".to_owned()]
+        } else {
+            calendar_ids
+        };
+        let mut events = Vec::new();
+        let mut truncated = false;
+        for calendar_id in calendar_ids {
+            params.calendar_id = Some(calendar_id.clone());
+            let data = self
+                .state
+                .google_calendar
+                .list_all_events(&token, &params, MAX_AUTO_PAGINATE_ITEMS as usize)
+                .await
+                .map_err(|err| Self::handle_calendar_error("list_events", err))?;
+            truncated |= data.next_page_token.is_some();
+            events.extend(
+                data.items
+                    .into_iter()
+                    .map(|event| (calendar_id.clone(), event)),
+            );
+        }
+
+        let options = ReportOptions {
+            group_by,
+            tz,
+            working_hours,
+            top_collaborators: top_collaborators
+                .unwrap_or(DEFAULT_TOP_COLLABORATORS)
+                .min(MAX_TOP_COLLABORATORS),
+        };
+        Ok(Json(TimeReportResult {
+            report: analytics::time_report(&events, &options),
+            truncated,
+            resolved_times: resolver.into_resolved(),
+        }))
+    }
+
+    #[tool(
+        name = "google_calendar_export_events",
+        description = "Export the events in a date range as CSV or JSON Lines for reporting (e.g. meeting hours per week), following every page. Recurring events are expanded into instances. Returns a resource link to read the file from",
+        annotations(
+            title = "Export Events as CSV/JSONL",
+            read_only_hint = true,
+            destructive_hint = false
+        )
+    )]
+    pub async fn export_events(
+        &self,
+        extensions: Extensions,
+        Parameters(input): Parameters<ExportEventsInput>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let user_id = self.resolve_user_id(input.user_id.clone())?;
+        let mut open = self.open_export(&extensions, input).await?;
+        let mut content = open.first_chunk;
+        while let Some(chunk) = self
+            .state
+            .google_calendar
+            .next_export_chunk(&open.token, &mut open.export)
+            .await
+            .map_err(|err| Self::handle_calendar_error("list_events", err))?
+        {
+            content.push_str(&chunk);
+        }
+
+        let export_id = Uuid::new_v4().to_string();
+        let format = open.export.spec().format;
+        let result = ExportEventsResult {
+            uri: exports::export_uri(&export_id),
+            file_name: open.file_name.clone(),
+            format,
+            event_count: open.export.exported(),
+            truncated: open.export.truncated(),
+            size_bytes: content.len(),
+            expires_at: Utc::now() + Duration::minutes(exports::EXPORT_TTL_MINUTES),
+            resolved_times: open.resolved_times,
+        };
+        let mut link = RawResource::new(result.uri.clone(), open.file_name.clone());
+        link.mime_type = Some(format.mime_type().to_owned());
+        link.size = u32::try_from(content.len()).ok();
+        link.description = Some(format!("{} calendar events", result.event_count));
+
+        {
+            let mut stored = self.state.exports.write();
+            stored.retain(|_, export| !export.is_expired());
+            stored.insert(
+                export_id,
+                StoredExport {
+                    user_id,
+                    file_name: open.file_name,
+                    format,
+                    content,
+                    created_at: Utc::now(),
+                },
+            );
+        }
+
+        let value = serde_json::to_value(&result)
+            .map_err(|err| internal_error("serialize export", err.into()))?;
+        let mut output = CallToolResult::structured(value);
+        output.content.push(Content::resource_link(link));
+        Ok(output)
+    }
+}
+
+impl CalendarService {
+    /// Whether the caller owns `export`: the local user over stdio, otherwise the user whose
+    /// stored access token is presented as the bearer token.
+    async fn may_read_export(
+        &self,
+        export: &StoredExport,
+        extensions: &Extensions,
+    ) -> Result<bool, ErrorData> {
+        if let Some(local) = &self.local_user_id {
+            return Ok(*local == export.user_id);
+        }
+        let Some(parts) = extensions.get::<Parts>() else {
+            return Ok(false);
+        };
+        bearer_matches_stored_token(&self.state, &parts.headers, &export.user_id)
+            .await
+            .map_err(|err| internal_error("token fetch", err))
+    }
+
+    /// Authorizes the user, resolves the range and renders the first page of an event export,
+    /// so that errors surface before anything is handed out.
+    async fn open_export(
+        &self,
+        extensions: &Extensions,
+        input: ExportEventsInput,
+    ) -> Result<OpenExport, ErrorData> {
+        let user_id = self.resolve_user_id(input.user_id)?;
+        let token = self.ensure_token(&user_id, extensions).await?;
+        let (tz, _) = self.user_time_zone(&user_id, &token).await;
+        let mut params = ListEventsParams {
+            calendar_id: input.calendar_id,
+            time_min: input.time_min,
+            time_max: input.time_max,
+            query: input.query,
+            ..Default::default()
+        };
+        let mut resolver = TimeResolver::new(Utc::now(), tz);
+        resolver
+            .time_input("time_min", &mut params.time_min)
+            .and_then(|_| resolver.time_input("time_max", &mut params.time_max))
+            .map_err(time_expr_error)?;
+
+        let max_items = input
+            .max_items
+            .unwrap_or(DEFAULT_EXPORT_ITEMS)
+            .clamp(1, MAX_EXPORT_ITEMS) as usize;
+        let spec = ExportSpec::new(input.format, input.columns);
+        let file_name = format!(
+            "events-{}.{}",
+            Utc::now().with_timezone(&tz).format("%Y%m%d-%H%M%S"),
+            spec.format.extension()
+        );
+        let mut export = EventExport::new(spec, params, tz, max_items);
+        let first_chunk = self
+            .state
+            .google_calendar
+            .next_export_chunk(&token, &mut export)
+            .await
+            .map_err(|err| Self::handle_calendar_error("list_events", err))?
+            .unwrap_or_default();
+
+        Ok(OpenExport {
+            token,
+            export,
+            first_chunk,
+            file_name,
+            resolved_times: resolver.into_resolved(),
+        })
+    }
+
+    fn instructions(&self) -> String {
+        let mut instructions = "Complete OAuth authorization before calling tools. Event deletion is intentionally disabled.".to_owned();
+        let calendars = &self.state.config.calendars;
+        if calendars.enabled && calendars.allow_destructive {
+            instructions.push_str(
+                " Clearing and deleting whole calendars is enabled by the server configuration and cannot be undone.",
+            );
+        }
+        instructions
+    }
+}
+
+#[tool_handler]
+impl ServerHandler for CalendarService {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            protocol_version: ProtocolVersion::default(),
+            capabilities: ServerCapabilities::builder()
+                .enable_tools()
+                .enable_resources()
+                .build(),
+            server_info: Implementation {
                 name: "mcp-google-calendar".into(),
                 title: Some("Google Calendar MCP Bridge".into()),
                 version: env!("CARGO_PKG_VERSION").into(),
                 icons: None,
                 website_url: Some("https://modelcontextprotocol.io/".into()),
             },
-            instructions: Some(
-                "Complete OAuth authorization before calling tools. Event deletion is intentionally disabled.".into(),
-            ),
+            instructions: Some(self.instructions()),
+        }
+    }
+
+    /// Serves the files linked by `google_calendar_export_events`.
+    async fn read_resource(
+        &self,
+        request: ReadResourceRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, ErrorData> {
+        let not_found = || {
+            ErrorData::resource_not_found(
+                format!("{} does not exist or has expired", request.uri),
+                None,
+            )
+        };
+        let export = exports::export_id(&request.uri)
+            .and_then(|id| self.state.exports.read().get(id).cloned())
+            .filter(|export| !export.is_expired())
+            .ok_or_else(not_found)?;
+        if !self.may_read_export(&export, &context.extensions).await? {
+            return Err(not_found());
+        }
+        Ok(ReadResourceResult {
+            contents: vec![ResourceContents::TextResourceContents {
+                uri: request.uri,
+                mime_type: Some(export.format.mime_type().to_owned()),
+                text: export.content,
+                meta: None,
+            }],
+        })
+    }
+}
+
+fn time_expr_error(err: TimeExprError) -> ErrorData {
+    ErrorData::invalid_params(err.to_string(), None)
+}
+
+/// Resolves relative `start`/`end` in `payload` against the current time in `tz`.
+fn resolve_payload_times(
+    tz: Tz,
+    payload: &mut EventPayload,
+) -> Result<Option<ResolvedTimes>, ErrorData> {
+    let mut resolver = TimeResolver::new(Utc::now(), tz);
+    payload
+        .resolve_times(&mut resolver)
+        .map_err(time_expr_error)?;
+    Ok(resolver.into_resolved())
+}
+
+/// A per-event failure of an ICS import, classified like batch item errors.
+fn import_error(err: anyhow::Error) -> BatchItemError {
+    match err.downcast_ref::<GoogleCalendarError>() {
+        Some(calendar_err) => BatchItemError::from(calendar_err),
+        None => BatchItemError {
+            error: "internal_error".to_owned(),
+            reason: None,
+            message: err.to_string(),
+            retryable: false,
+        },
+    }
+}
+
+fn internal_error(operation: &str, err: anyhow::Error) -> ErrorData {
+    ErrorData::internal_error(
+        format!("{operation} failed: {err}"),
+        Some(serde_json::json!({ "cause": err.to_string() })),
+    )
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+struct ListEventsInput {
+    /// Stable user identifier on behalf of which the request is executed.
+    /// Ignored in stdio mode, where the local user is implied.
+    #[serde(default)]
+    pub user_id: Option<String>,
+    #[serde(flatten)]
+    pub params: ListEventsParams,
+    #[serde(flatten)]
+    pub options: ListEventsOptions,
+}
+
+/// Default and upper bound for events collected by `auto_paginate`.
+const DEFAULT_AUTO_PAGINATE_ITEMS: u32 = 250;
+const MAX_AUTO_PAGINATE_ITEMS: u32 = 2500;
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, JsonSchema)]
+pub struct ListEventsOptions {
+    /// Follow `nextPageToken` automatically until `max_items` events are collected.
+    #[serde(default)]
+    pub auto_paginate: bool,
+    /// Cap for `auto_paginate` (default 250, at most 2500).
+    #[serde(default)]
+    pub max_items: Option<u32>,
+    /// Return only id, summary, start, end and attendee_count per event.
+    #[serde(default)]
+    pub compact: bool,
+}
+
+/// Events in the requested representation.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(untagged)]
+pub enum EventItems {
+    Full(Vec<CalendarEvent>),
+    Compact(Vec<CompactEvent>),
+}
+
+/// Result of `google_calendar_list_events`.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ListEventsResult {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    pub items: EventItems,
+    /// Pass back as `page_token` to continue; with `auto_paginate` it is only set when
+    /// `max_items` was reached before the last page.
+    #[serde(rename = "nextPageToken", skip_serializing_if = "Option::is_none")]
+    pub next_page_token: Option<String>,
+    /// Absolute bounds used for relative `time_min`/`time_max` expressions.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolved_times: Option<ResolvedTimes>,
+}
+
+impl ListEventsResult {
+    fn new(response: ListEventsResponse, options: ListEventsOptions) -> Self {
+        let items = if options.compact {
+            EventItems::Compact(response.items.into_iter().map(CompactEvent::from).collect())
+        } else {
+            EventItems::Full(response.items)
+        };
+        Self {
+            kind: response.kind,
+            summary: response.summary,
+            items,
+            next_page_token: response.next_page_token,
+            resolved_times: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+struct GetEventInput {
+    #[serde(default)]
+    pub user_id: Option<String>,
+    pub event_id: String,
+    #[serde(default)]
+    pub calendar_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+struct CreateEventInput {
+    #[serde(default)]
+    pub user_id: Option<String>,
+    /// Validate and preview the write without calling the mutating API.
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Client-chosen key; retrying a create with the same key returns the original event.
+    #[serde(default)]
+    pub idempotency_key: Option<String>,
+    /// Create the event even if it overlaps busy events and the conflict policy is `refuse`.
+    #[serde(default)]
+    pub allow_conflicts: bool,
+    #[serde(flatten)]
+    pub payload: EventPayload,
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+struct UpdateEventInput {
+    #[serde(default)]
+    pub user_id: Option<String>,
+    pub event_id: String,
+    /// Validate and preview the write without calling the mutating API.
+    #[serde(default)]
+    pub dry_run: bool,
+    /// `etag` from a previous read; the update is rejected if the event changed since.
+    #[serde(default)]
+    pub if_match_etag: Option<String>,
+    /// Save the new time even if it overlaps busy events and the conflict policy is `refuse`.
+    #[serde(default)]
+    pub allow_conflicts: bool,
+    #[serde(flatten)]
+    pub payload: EventPayload,
+}
+
+/// Result of a create/update tool call.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct EventWriteResult {
+    /// The saved event, or the event as it would look after a dry run.
+    #[serde(flatten)]
+    pub event: CalendarEvent,
+    /// Present only for dry runs; nothing was written.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dry_run: Option<DryRunReport>,
+    /// True when an earlier create with the same idempotency key already made this event.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub already_created: bool,
+    /// Link to join the event's video conference, e.g. after `add_meet_link`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub join_url: Option<String>,
+    /// Busy events the saved time overlaps, under the `warn` conflict policy.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub conflicts: Vec<Conflict>,
+    /// Absolute times used for relative `start`/`end` expressions.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolved_times: Option<ResolvedTimes>,
+}
+
+impl EventWriteResult {
+    fn localize(&mut self, tz: Tz) {
+        self.event.localize(tz);
+        let reported = self
+            .dry_run
+            .iter_mut()
+            .flat_map(|report| &mut report.conflicts);
+        for conflict in self.conflicts.iter_mut().chain(reported) {
+            conflict.event.localize(tz);
         }
     }
 }
 
-fn internal_error(operation: &str, err: anyhow::Error) -> ErrorData {
-    ErrorData::internal_error(
-        format!("{operation} failed: {err}"),
-        Some(serde_json::json!({ "cause": err.to_string() })),
-    )
+impl From<CalendarEvent> for EventWriteResult {
+    fn from(event: CalendarEvent) -> Self {
+        let join_url = event
+            .conference_data
+            .as_ref()
+            .and_then(|conference| conference.join_url())
+            .map(str::to_owned);
+        Self {
+            event,
+            dry_run: None,
+            already_created: false,
+            join_url,
+            conflicts: Vec::new(),
+            resolved_times: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+struct PrepareWriteInput {
+    #[serde(default)]
+    pub user_id: Option<String>,
+    pub operation: WriteOperation,
+    /// Required when `operation` is `update` or `move`.
+    #[serde(default)]
+    pub event_id: Option<String>,
+    /// Required when `operation` is `move`; the source is `calendar_id`.
+    #[serde(default)]
+    pub destination_calendar_id: Option<String>,
+    #[serde(flatten)]
+    pub payload: EventPayload,
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+struct MoveEventInput {
+    #[serde(default)]
+    pub user_id: Option<String>,
+    pub event_id: String,
+    /// Calendar the event is on now (defaults to the configured calendar).
+    #[serde(default)]
+    pub calendar_id: Option<String>,
+    pub destination_calendar_id: String,
+    /// Who is emailed about the move (server default if unset).
+    #[serde(default)]
+    pub send_updates: Option<SendUpdates>,
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+struct RespondToEventInput {
+    #[serde(default)]
+    pub user_id: Option<String>,
+    pub event_id: String,
+    #[serde(default)]
+    pub calendar_id: Option<String>,
+    pub response: RsvpResponse,
+    /// Note to the organizer.
+    #[serde(default)]
+    pub comment: Option<String>,
+    /// Who is emailed about the response (server default if unset).
+    #[serde(default)]
+    pub send_updates: Option<SendUpdates>,
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+struct AttachFileInput {
+    #[serde(default)]
+    pub user_id: Option<String>,
+    pub event_id: String,
+    #[serde(default)]
+    pub calendar_id: Option<String>,
+    /// Link to the file, in the format of a Drive file's `alternateLink`.
+    pub file_url: String,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub mime_type: Option<String>,
+    #[serde(default)]
+    pub icon_link: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+struct QuickAddInput {
+    #[serde(default)]
+    pub user_id: Option<String>,
+    /// Free-text description, e.g. "Dentist Friday 3pm".
+    pub text: String,
+    #[serde(default)]
+    pub calendar_id: Option<String>,
+    /// Ask the user to keep or discard the event Google parsed from the text.
+    #[serde(default)]
+    pub confirm: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+struct BatchInput {
+    #[serde(default)]
+    pub user_id: Option<String>,
+    /// At most 50 operations, executed in a single batch request.
+    pub operations: Vec<BatchOperation>,
+}
+
+/// Per-item outcome of a batch, in input order.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct BatchResult {
+    pub succeeded: usize,
+    pub failed: usize,
+    pub results: Vec<BatchItemResult>,
+    /// Absolute times used for relative `start`/`end` expressions.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolved_times: Option<ResolvedTimes>,
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+struct SetTimeZoneInput {
+    #[serde(default)]
+    pub user_id: Option<String>,
+    /// IANA zone to use instead of the Google Calendar setting, e.g. "Asia/Tokyo".
+    #[serde(default)]
+    pub time_zone: Option<String>,
+    /// Remove the override.
+    #[serde(default)]
+    pub clear: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+struct ConvertTimeInput {
+    #[serde(default)]
+    pub user_id: Option<String>,
+    /// RFC3339, or an expression such as "friday 9am" or "2025-10-20 15:00".
+    pub time: String,
+    /// Zone in which a local expression is meant (defaults to the user's zone).
+    #[serde(default)]
+    pub from_time_zone: Option<String>,
+    /// Additional IANA zones to show the time in.
+    #[serde(default)]
+    pub to_time_zones: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+struct ListColorsInput {
+    #[serde(default)]
+    pub user_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+struct SetOutOfOfficeInput {
+    #[serde(default)]
+    pub user_id: Option<String>,
+    /// RFC3339 or an expression such as "monday 9am".
+    pub start: EventDateTime,
+    pub end: EventDateTime,
+    /// Title shown on the calendar (default "Out of office").
+    #[serde(default)]
+    pub summary: Option<String>,
+    #[serde(default)]
+    pub auto_decline_mode: Option<AutoDeclineMode>,
+    /// Sent when an invitation is declined; requires a declining auto_decline_mode.
+    #[serde(default)]
+    pub decline_message: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+struct BlockFocusTimeInput {
+    #[serde(default)]
+    pub user_id: Option<String>,
+    pub start: EventDateTime,
+    pub end: EventDateTime,
+    /// Title shown on the calendar (default "Focus time").
+    #[serde(default)]
+    pub summary: Option<String>,
+    #[serde(default)]
+    pub auto_decline_mode: Option<AutoDeclineMode>,
+    /// Sent when an invitation is declined; requires a declining auto_decline_mode.
+    #[serde(default)]
+    pub decline_message: Option<String>,
+    #[serde(default)]
+    pub chat_status: Option<ChatStatus>,
 }
 
 #[derive(Debug, Clone, Deserialize, JsonSchema)]
-struct ListEventsInput {
-    /// Stable user identifier on behalf of which the request is executed.
-    pub user_id: String,
-    #[serde(flatten)]
-    pub params: ListEventsParams,
+struct SetWorkingLocationInput {
+    #[serde(default)]
+    pub user_id: Option<String>,
+    pub start: EventDateTime,
+    pub end: EventDateTime,
+    pub location: WorkingLocationType,
+    /// Name of the place; required for "customLocation".
+    #[serde(default)]
+    pub label: Option<String>,
+    /// Office building, for "officeLocation".
+    #[serde(default)]
+    pub building_id: Option<String>,
+    /// Desk, for "officeLocation".
+    #[serde(default)]
+    pub desk_id: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize, JsonSchema)]
-struct GetEventInput {
-    pub user_id: String,
-    pub event_id: String,
+struct ListAclInput {
+    #[serde(default)]
+    pub user_id: Option<String>,
     #[serde(default)]
     pub calendar_id: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize, JsonSchema)]
-struct CreateEventInput {
-    pub user_id: String,
+struct ShareCalendarInput {
+    #[serde(default)]
+    pub user_id: Option<String>,
+    #[serde(default)]
+    pub calendar_id: Option<String>,
+    /// e.g. {"type": "group", "value": "oncall@example.com"} or {"type": "default"}.
+    pub scope: AclScope,
+    pub role: AclRole,
+    /// Email the grantee about the new access.
+    #[serde(default)]
+    pub send_notifications: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+struct UpdateAclInput {
+    #[serde(default)]
+    pub user_id: Option<String>,
+    #[serde(default)]
+    pub calendar_id: Option<String>,
+    /// Rule id such as "user:alice@example.com".
+    pub rule_id: String,
+    pub role: AclRole,
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+struct RemoveAclInput {
+    #[serde(default)]
+    pub user_id: Option<String>,
+    #[serde(default)]
+    pub calendar_id: Option<String>,
+    /// Rule id such as "user:alice@example.com".
+    pub rule_id: String,
+}
+
+/// A sharing rule that was removed.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct AclRemoved {
+    pub calendar_id: String,
+    pub rule_id: String,
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+struct CreateCalendarInput {
+    #[serde(default)]
+    pub user_id: Option<String>,
+    pub summary: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub location: Option<String>,
+    /// IANA zone (defaults to the user's time zone).
+    #[serde(default)]
+    pub time_zone: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+struct UpdateCalendarInput {
+    #[serde(default)]
+    pub user_id: Option<String>,
+    pub calendar_id: String,
+    #[serde(default)]
+    pub summary: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub location: Option<String>,
+    #[serde(default)]
+    pub time_zone: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+struct CalendarIdInput {
+    #[serde(default)]
+    pub user_id: Option<String>,
+    pub calendar_id: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CalendarAction {
+    Deleted,
+    Unsubscribed,
+}
+
+/// A calendar operation without a response body.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct CalendarActionResult {
+    pub calendar_id: String,
+    pub action: CalendarAction,
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+struct ExportIcsInput {
+    #[serde(default)]
+    pub user_id: Option<String>,
+    #[serde(default)]
+    pub calendar_id: Option<String>,
+    /// Start of the range (RFC3339 or a relative expression such as "today").
+    #[serde(default)]
+    pub time_min: Option<TimeInput>,
+    /// End of the range (RFC3339 or a relative expression such as "in 30 days").
+    #[serde(default)]
+    pub time_max: Option<TimeInput>,
+    /// Only export events matching this free-text search.
+    #[serde(default)]
+    pub query: Option<String>,
+}
+
+/// Result of `google_calendar_export_ics`.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ExportIcsResult {
+    pub calendar_id: String,
+    pub event_count: usize,
+    /// Set when the range held more than 2500 events and the rest were left out.
+    pub truncated: bool,
+    /// The `VCALENDAR` document, with CRLF line endings.
+    pub ics: String,
+    /// Absolute bounds used for relative `time_min`/`time_max` expressions.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolved_times: Option<ResolvedTimes>,
+}
+
+/// Upper bound for events in one `google_calendar_import_ics` call.
+const MAX_IMPORT_EVENTS: usize = 500;
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+struct ImportIcsInput {
+    #[serde(default)]
+    pub user_id: Option<String>,
+    /// Calendar to import into (defaults to "primary").
+    #[serde(default)]
+    pub calendar_id: Option<String>,
+    /// Contents of the .ics file.
+    pub ics: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportAction {
+    Created,
+    Updated,
+}
+
+/// Outcome for one `VEVENT`, in document order.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ImportIcsItem {
+    pub ical_uid: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub action: Option<ImportAction>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<BatchItemError>,
+}
+
+/// Result of `google_calendar_import_ics`.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ImportIcsResult {
+    pub created: usize,
+    pub updated: usize,
+    pub failed: usize,
+    pub results: Vec<ImportIcsItem>,
+    /// `VEVENT`s that were not imported, e.g. recurrence exceptions, with the reason.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub skipped: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+struct CheckConflictsInput {
+    #[serde(default)]
+    pub user_id: Option<String>,
+    /// Proposed event to check; its `start`, `end`, `transparency` and `calendar_id` are used.
+    #[serde(default)]
+    pub event: Option<EventPayload>,
+    /// Start of a time range to check instead of an event.
+    #[serde(default)]
+    pub time_min: Option<TimeInput>,
+    /// End of the time range.
+    #[serde(default)]
+    pub time_max: Option<TimeInput>,
+    /// Calendars to look at. Defaults to the configured conflict calendars, or those selected in
+    /// the user's calendar list.
+    #[serde(default)]
+    pub calendar_ids: Vec<String>,
+}
+
+/// Result of `google_calendar_check_conflicts`.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct CheckConflictsResult {
+    /// Calendars that were checked.
+    pub calendar_ids: Vec<String>,
+    /// Busy events overlapping the event or range, in start order per calendar.
+    pub conflicts: Vec<Conflict>,
+    /// Absolute times used for relative expressions.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolved_times: Option<ResolvedTimes>,
+}
+
+/// Upper bound for calendars in one `google_calendar_agenda`.
+const MAX_AGENDA_CALENDARS: usize = 20;
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+struct AgendaInput {
+    #[serde(default)]
+    pub user_id: Option<String>,
+    /// Day to show, or any day of the week to show: RFC3339 or an expression such as
+    /// "tomorrow" or "next monday". Defaults to today.
+    #[serde(default)]
+    pub date: Option<TimeInput>,
+    /// `day` (default) or `week`.
+    #[serde(default)]
+    pub span: AgendaSpan,
+    /// Calendars to merge. Defaults to those selected in the user's calendar list.
+    #[serde(default)]
+    pub calendar_ids: Vec<String>,
+    /// Working hours that free blocks are looked for in; 09:00-18:00, Monday to Friday by
+    /// default.
+    #[serde(default)]
+    pub working_hours: Option<WorkingHours>,
+    /// Shortest free block to list, in minutes (default 30).
+    #[serde(default)]
+    pub min_free_minutes: Option<u32>,
+}
+
+/// Result of `google_calendar_agenda`.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct AgendaResult {
     #[serde(flatten)]
-    pub payload: EventPayload,
+    pub agenda: Agenda,
+    /// Set when a calendar held more than 2500 events in the range and the rest were left out.
+    pub truncated: bool,
+    /// Absolute time used for a relative `date`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolved_times: Option<ResolvedTimes>,
 }
 
+/// Upper bound for calendars in one `google_calendar_time_report`.
+const MAX_REPORT_CALENDARS: usize = 10;
+const DEFAULT_TOP_COLLABORATORS: usize = 5;
+const MAX_TOP_COLLABORATORS: usize = 50;
+
 #[derive(Debug, Clone, Deserialize, JsonSchema)]
-struct UpdateEventInput {
-    pub user_id: String,
-    pub event_id: String,
+struct TimeReportInput {
+    #[serde(default)]
+    pub user_id: Option<String>,
+    /// Calendars to include (defaults to ["primary"], at most 10).
+    #[serde(default)]
+    pub calendar_ids: Vec<String>,
+    /// Start of the range, e.g. "3 months ago" or RFC3339.
+    pub time_min: TimeInput,
+    /// End of the range, e.g. "today" or RFC3339.
+    pub time_max: TimeInput,
+    /// "day", "week" (default) or "calendar".
+    #[serde(default)]
+    pub group_by: GroupBy,
+    /// Defaults to 09:00-18:00, Monday to Friday, in the user's time zone.
+    #[serde(default)]
+    pub working_hours: Option<WorkingHours>,
+    /// Collaborators listed per period (default 5).
+    #[serde(default)]
+    pub top_collaborators: Option<usize>,
+}
+
+/// Result of `google_calendar_time_report`.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct TimeReportResult {
     #[serde(flatten)]
-    pub payload: EventPayload,
+    pub report: TimeReport,
+    /// Set when a calendar held more than 2500 events in the range and the rest were left out.
+    pub truncated: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolved_times: Option<ResolvedTimes>,
+}
+
+/// Default and upper bound for events in one export.
+const DEFAULT_EXPORT_ITEMS: u32 = 10_000;
+const MAX_EXPORT_ITEMS: u32 = 50_000;
+
+/// Parameters of an event export, shared by `google_calendar_export_events` and the
+/// `/export/events` download route.
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct ExportEventsInput {
+    #[serde(default)]
+    pub user_id: Option<String>,
+    #[serde(default)]
+    pub calendar_id: Option<String>,
+    /// Start of the range (RFC3339 or a relative expression such as "3 months ago").
+    #[serde(default)]
+    pub time_min: Option<TimeInput>,
+    #[serde(default)]
+    pub time_max: Option<TimeInput>,
+    /// Only export events matching this free-text search.
+    #[serde(default)]
+    pub query: Option<String>,
+    /// "csv" (default) or "jsonl".
+    #[serde(default)]
+    pub format: ExportFormat,
+    /// Columns in order; defaults to summary, start, end, duration_minutes, attendee_count,
+    /// organizer and recurring.
+    #[serde(default)]
+    pub columns: Vec<ExportColumn>,
+    /// Cap on exported events (default 10000, at most 50000).
+    #[serde(default)]
+    pub max_items: Option<u32>,
+}
+
+/// An export whose first page has been fetched.
+pub struct OpenExport {
+    pub token: UserToken,
+    pub export: EventExport,
+    /// Header and first page of rows.
+    pub first_chunk: String,
+    pub file_name: String,
+    pub resolved_times: Option<ResolvedTimes>,
+}
+
+/// Result of `google_calendar_export_events`; the file itself is behind `uri`.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ExportEventsResult {
+    /// Read with `resources/read` until `expires_at`.
+    pub uri: String,
+    pub file_name: String,
+    pub format: ExportFormat,
+    pub event_count: usize,
+    /// Set when the range held more than `max_items` events.
+    pub truncated: bool,
+    pub size_bytes: usize,
+    pub expires_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolved_times: Option<ResolvedTimes>,
+}
+
+/// The same instant in the user's zone, the source zone and each requested zone.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ConvertTimeResult {
+    pub utc: DateTime<Utc>,
+    pub conversions: Vec<ZonedTime>,
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+struct CommitWriteInput {
+    #[serde(default)]
+    pub user_id: Option<String>,
+    pub confirmation_token: String,
 }
 
 pub fn service_factory(state: Arc<AppState>) -> impl Fn() -> CalendarService + Clone {
@@ -324,6 +3379,13 @@ impl HttpMcpServer {
         }
     }
 
+    /// Starts an event export for the `/export/events` download route.
+    pub async fn open_export(&self, input: ExportEventsInput) -> Result<OpenExport, ErrorData> {
+        self.service
+            .open_export(&Extensions::default(), input)
+            .await
+    }
+
     pub async fn handle_request(&self, request: ToolRequest) -> ToolResponse {
         match self.try_handle(request).await {
             Ok(value) => value,
@@ -331,6 +3393,7 @@ impl HttpMcpServer {
                 let message = err.message.to_string();
                 let data = err.data;
                 let mut response = ToolResponse::error(message);
+                response.error_code = Some(err.code.0);
                 if let Some(data) = data {
                     response.data = Some(data);
                 }
@@ -341,12 +3404,20 @@ impl HttpMcpServer {
 
     async fn try_handle(&self, request: ToolRequest) -> Result<ToolResponse, ErrorData> {
         match request {
-            ToolRequest::List { user_id, params } => {
+            ToolRequest::List {
+                user_id,
+                params,
+                options,
+            } => {
                 let Json(payload) = self
                     .service
                     .list_events(
                         Extensions::default(),
-                        Parameters(ListEventsInput { user_id, params }),
+                        Parameters(ListEventsInput {
+                            user_id: Some(user_id),
+                            params,
+                            options,
+                        }),
                     )
                     .await?;
                 let value = serde_json::to_value(payload)
@@ -363,7 +3434,7 @@ impl HttpMcpServer {
                     .get_event(
                         Extensions::default(),
                         Parameters(GetEventInput {
-                            user_id,
+                            user_id: Some(user_id),
                             event_id,
                             calendar_id,
                         }),
@@ -373,12 +3444,25 @@ impl HttpMcpServer {
                     .map_err(|err| internal_error("serialize get event", err.into()))?;
                 Ok(ToolResponse::success(value))
             }
-            ToolRequest::Create { user_id, payload } => {
+            ToolRequest::Create {
+                user_id,
+                dry_run,
+                idempotency_key,
+                allow_conflicts,
+                payload,
+            } => {
                 let Json(payload) = self
                     .service
                     .create_event(
+                        ClientPeer::default(),
                         Extensions::default(),
-                        Parameters(CreateEventInput { user_id, payload }),
+                        Parameters(CreateEventInput {
+                            user_id: Some(user_id),
+                            dry_run,
+                            idempotency_key,
+                            allow_conflicts,
+                            payload,
+                        }),
                     )
                     .await?;
                 let value = serde_json::to_value(payload)
@@ -388,15 +3472,22 @@ impl HttpMcpServer {
             ToolRequest::Update {
                 user_id,
                 event_id,
+                dry_run,
+                if_match_etag,
+                allow_conflicts,
                 payload,
             } => {
                 let Json(payload) = self
                     .service
                     .update_event(
+                        ClientPeer::default(),
                         Extensions::default(),
                         Parameters(UpdateEventInput {
-                            user_id,
+                            user_id: Some(user_id),
                             event_id,
+                            dry_run,
+                            if_match_etag,
+                            allow_conflicts,
                             payload,
                         }),
                     )
@@ -405,6 +3496,67 @@ impl HttpMcpServer {
                     .map_err(|err| internal_error("serialize update event", err.into()))?;
                 Ok(ToolResponse::success(value))
             }
+            ToolRequest::Commit {
+                user_id,
+                confirmation_token,
+            } => {
+                let Json(payload) = self
+                    .service
+                    .commit_write(
+                        Extensions::default(),
+                        Parameters(CommitWriteInput {
+                            user_id: Some(user_id),
+                            confirmation_token,
+                        }),
+                    )
+                    .await?;
+                let value = serde_json::to_value(payload)
+                    .map_err(|err| internal_error("serialize commit write", err.into()))?;
+                Ok(ToolResponse::success(value))
+            }
+            ToolRequest::Batch {
+                user_id,
+                operations,
+            } => {
+                let Json(payload) = self
+                    .service
+                    .batch(
+                        Extensions::default(),
+                        Parameters(BatchInput {
+                            user_id: Some(user_id),
+                            operations,
+                        }),
+                    )
+                    .await?;
+                let value = serde_json::to_value(payload)
+                    .map_err(|err| internal_error("serialize batch", err.into()))?;
+                Ok(ToolResponse::success(value))
+            }
+            ToolRequest::Move {
+                user_id,
+                event_id,
+                calendar_id,
+                destination_calendar_id,
+                send_updates,
+            } => {
+                let Json(payload) = self
+                    .service
+                    .move_event(
+                        ClientPeer::default(),
+                        Extensions::default(),
+                        Parameters(MoveEventInput {
+                            user_id: Some(user_id),
+                            event_id,
+                            calendar_id,
+                            destination_calendar_id,
+                            send_updates,
+                        }),
+                    )
+                    .await?;
+                let value = serde_json::to_value(payload)
+                    .map_err(|err| internal_error("serialize move event", err.into()))?;
+                Ok(ToolResponse::success(value))
+            }
         }
     }
 }
@@ -416,6 +3568,8 @@ pub enum ToolRequest {
         user_id: String,
         #[serde(default)]
         params: ListEventsParams,
+        #[serde(flatten)]
+        options: ListEventsOptions,
     },
     Get {
         user_id: String,
@@ -425,13 +3579,42 @@ pub enum ToolRequest {
     },
     Create {
         user_id: String,
+        #[serde(default)]
+        dry_run: bool,
+        #[serde(default)]
+        idempotency_key: Option<String>,
+        #[serde(default)]
+        allow_conflicts: bool,
         payload: EventPayload,
     },
     Update {
         user_id: String,
         event_id: String,
+        #[serde(default)]
+        dry_run: bool,
+        #[serde(default)]
+        if_match_etag: Option<String>,
+        #[serde(default)]
+        allow_conflicts: bool,
         payload: EventPayload,
     },
+    Commit {
+        user_id: String,
+        confirmation_token: String,
+    },
+    Batch {
+        user_id: String,
+        operations: Vec<BatchOperation>,
+    },
+    Move {
+        user_id: String,
+        event_id: String,
+        #[serde(default)]
+        calendar_id: Option<String>,
+        destination_calendar_id: String,
+        #[serde(default)]
+        send_updates: Option<SendUpdates>,
+    },
 }
 
 #[derive(Debug, Serialize)]
@@ -441,6 +3624,9 @@ pub struct ToolResponse {
     pub data: Option<serde_json::Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
+    /// MCP error code; see [`errors`] for the Google Calendar specific values.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<i32>,
 }
 
 impl ToolResponse {
@@ -449,6 +3635,7 @@ impl ToolResponse {
             status: ResponseStatus::Success,
             data: Some(data),
             error: None,
+            error_code: None,
         }
     }
 
@@ -457,13 +3644,200 @@ impl ToolResponse {
             status: ResponseStatus::Error,
             data: None,
             error: Some(message),
+            error_code: None,
         }
     }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, PartialEq, Eq)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum ResponseStatus {
     Success,
     Error,
 }
+
+/// Checks the user's `calendarList` entry for `calendar_id` before a write.
+///
+/// Reading the calendar list needs the `calendar.calendarlist.readonly` scope beyond
+/// `calendar.events`; without it the write is refused rather than left unchecked.
+fn check_write_access(
+    calendar_id: &str,
+    entry: anyhow::Result<CalendarListEntry>,
+) -> Result<(), ErrorData> {
+    let operation = "calendar_list";
+    let required_scope = CalendarService::required_scope_for_operation(operation);
+    let entry = match entry {
+        Ok(entry) => entry,
+        Err(err)
+            if matches!(
+                err.downcast_ref(),
+                Some(GoogleCalendarError::InsufficientScope { .. })
+            ) =>
+        {
+            return Err(errors::calendar_error(
+                operation,
+                required_scope,
+                &GoogleCalendarError::InsufficientScope {
+                    message: format!(
+                        "checking write access to calendar {calendar_id} needs the {required_scope} scope; re-authorize to grant it"
+                    ),
+                    reason: None,
+                },
+            ));
+        }
+        Err(err) => return Err(CalendarService::handle_calendar_error(operation, err)),
+    };
+    if entry.can_write() {
+        return Ok(());
+    }
+
+    let role = entry.access_role.as_deref().unwrap_or("no");
+    Err(errors::calendar_error(
+        operation,
+        required_scope,
+        &GoogleCalendarError::Forbidden {
+            message: format!(
+                "you have {role} access to calendar {calendar_id}; writer or owner is required"
+            ),
+            reason: Some("requiredAccessLevel".to_owned()),
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tool_schemas_are_objects() {
+        for tool in CalendarService::tool_router().list_all() {
+            assert_eq!(
+                tool.input_schema.get("type").and_then(|v| v.as_str()),
+                Some("object"),
+                "input schema of {}",
+                tool.name
+            );
+            if let Some(output) = &tool.output_schema {
+                assert_eq!(
+                    output.get("type").and_then(|v| v.as_str()),
+                    Some("object"),
+                    "output schema of {}",
+                    tool.name
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn write_access_check_fails_closed_without_calendar_list_scope() {
+        let scope_error = GoogleCalendarError::InsufficientScope {
+            message: "Request had insufficient authentication scopes.".into(),
+            reason: Some("insufficientPermissions".into()),
+        };
+        let err = check_write_access("team", Err(scope_error.into())).unwrap_err();
+        assert_eq!(err.code, errors::FORBIDDEN);
+        let data = err.data.unwrap();
+        assert_eq!(data["error"], "insufficient_scope");
+        assert_eq!(data["required_scope"], CALENDAR_LIST_READONLY_SCOPE);
+
+        let reader: CalendarListEntry =
+            serde_json::from_value(serde_json::json!({"id": "team", "accessRole": "reader"}))
+                .unwrap();
+        let err = check_write_access("team", Ok(reader)).unwrap_err();
+        assert_eq!(err.data.unwrap()["reason"], "requiredAccessLevel");
+
+        let writer: CalendarListEntry =
+            serde_json::from_value(serde_json::json!({"id": "team", "accessRole": "writer"}))
+                .unwrap();
+        assert!(check_write_access("team", Ok(writer)).is_ok());
+    }
+
+    #[test]
+    fn http_list_accepts_top_level_options() {
+        let request: ToolRequest = serde_json::from_value(serde_json::json!({
+            "operation": "list",
+            "user_id": "user",
+            "params": { "fields": "id,summary" },
+            "auto_paginate": true,
+            "compact": true
+        }))
+        .unwrap();
+
+        let ToolRequest::List {
+            params, options, ..
+        } = request
+        else {
+            panic!("expected list request");
+        };
+        assert_eq!(params.fields.as_deref(), Some("id,summary"));
+        assert!(options.auto_paginate && options.compact);
+        assert!(options.max_items.is_none());
+    }
+
+    #[tokio::test]
+    async fn unconfirmed_quick_add_is_discarded_until_committed() {
+        let requests = Arc::new(parking_lot::Mutex::new(Vec::new()));
+        let seen = requests.clone();
+        let app = axum::Router::new().fallback(move |request: axum::extract::Request| {
+            seen.lock()
+                .push(format!("{} {}", request.method(), request.uri()));
+            async { axum::http::StatusCode::NO_CONTENT }
+        });
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let config = serde_json::from_value(serde_json::json!({
+            "oauth": {"client_id": "client", "client_secret": "secret"},
+            "google": {"api_base": format!("http://{addr}/calendar/v3")},
+            "security": {"use_in_memory": true}
+        }))
+        .unwrap();
+        let storage = Arc::new(crate::oauth::storage::InMemoryTokenStorage::new());
+        let service = CalendarService::new(Arc::new(AppState::new(config, storage).unwrap()));
+        let token = UserToken {
+            user_id: "alice".into(),
+            token: crate::oauth::TokenInfo {
+                access_token: "token".into(),
+                refresh_token: None,
+                expires_at: None,
+                scope: None,
+                token_type: "Bearer".into(),
+            },
+        };
+        let write = PendingWrite {
+            user_id: "alice".into(),
+            operation: WriteOperation::Create,
+            event_id: None,
+            payload: EventPayload {
+                calendar_id: Some("primary".into()),
+                send_updates: Some(SendUpdates::None),
+                ..Default::default()
+            },
+            idempotency_key: None,
+            if_match_etag: None,
+            destination_calendar_id: None,
+            quick_add_text: Some("Lunch with sam@example.com Friday 1pm".into()),
+            created_at: Utc::now(),
+        };
+        let parsed: CalendarEvent =
+            serde_json::from_value(serde_json::json!({"id": "evt", "summary": "Lunch"})).unwrap();
+
+        let err = service
+            .review_quick_add(&ClientPeer::default(), &token, &write, &parsed)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            *requests.lock(),
+            ["DELETE /calendar/v3/calendars/primary/events/evt?sendUpdates=none"]
+        );
+        let data = err.data.unwrap();
+        assert_eq!(data["confirmation_required"], true);
+        assert_eq!(data["preview"]["changes"][2]["after"], "Lunch");
+        let staged = service.state.pending_writes.read();
+        assert_eq!(
+            staged.values().next().unwrap().quick_add_text.as_deref(),
+            Some("Lunch with sam@example.com Friday 1pm")
+        );
+    }
+}
@@ -0,0 +1,96 @@
+use chrono::{DateTime, FixedOffset, Offset, Utc};
+use chrono_tz::{OffsetName, Tz};
+use rmcp::ErrorData;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+/// `user_data` key of a user's time zone override.
+pub const TIME_ZONE_OVERRIDE_KEY: &str = "time_zone";
+
+/// How long a time zone read from Google is reused before it is fetched again.
+pub const TIME_ZONE_CACHE_TTL_MINUTES: i64 = 60;
+
+#[derive(Debug, Clone, Copy)]
+pub struct CachedTimeZone {
+    pub time_zone: Tz,
+    pub fetched_at: DateTime<Utc>,
+}
+
+impl CachedTimeZone {
+    pub fn is_fresh(&self) -> bool {
+        Utc::now() - self.fetched_at < chrono::Duration::minutes(TIME_ZONE_CACHE_TTL_MINUTES)
+    }
+}
+
+/// Where the effective time zone of a user came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeZoneSource {
+    /// Set with `google_calendar_set_time_zone`.
+    Override,
+    /// The user's Google Calendar setting.
+    Calendar,
+    /// Google could not be asked; times are shown in UTC.
+    Fallback,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct UserTimeZone {
+    pub time_zone: String,
+    pub source: TimeZoneSource,
+}
+
+/// Parses an IANA zone name such as `Europe/Berlin`.
+pub fn parse_time_zone(name: &str) -> Result<Tz, ErrorData> {
+    name.trim().parse::<Tz>().map_err(|_| {
+        ErrorData::invalid_params(
+            format!("unknown time zone '{name}'; use an IANA name such as \"Europe/Berlin\""),
+            None,
+        )
+    })
+}
+
+/// One instant as seen in one zone.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ZonedTime {
+    pub time_zone: String,
+    pub local_time: DateTime<FixedOffset>,
+    /// Abbreviation in effect at that instant, e.g. "CEST".
+    pub abbreviation: String,
+    /// Offset from UTC such as "+02:00".
+    pub utc_offset: String,
+    pub weekday: String,
+}
+
+impl ZonedTime {
+    pub fn new(at: DateTime<Utc>, tz: Tz) -> Self {
+        let local = at.with_timezone(&tz);
+        Self {
+            time_zone: tz.name().to_owned(),
+            abbreviation: local.offset().abbreviation().unwrap_or_default().to_owned(),
+            utc_offset: local.offset().fix().to_string(),
+            weekday: local.format("%A").to_string(),
+            local_time: local.fixed_offset(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zoned_time_reflects_daylight_saving() {
+        let at = DateTime::parse_from_rfc3339("2025-07-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        let berlin = ZonedTime::new(at, parse_time_zone("Europe/Berlin").unwrap());
+        assert_eq!(berlin.local_time.to_rfc3339(), "2025-07-01T14:00:00+02:00");
+        assert_eq!(berlin.abbreviation, "CEST");
+        assert_eq!(berlin.utc_offset, "+02:00");
+        assert_eq!(berlin.weekday, "Tuesday");
+
+        assert!(parse_time_zone("Mars/Olympus").is_err());
+    }
+}
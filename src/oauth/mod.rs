@@ -38,6 +38,20 @@ impl TokenInfo {
     }
 }
 
+/// A user's access token together with the user id, so that per-user limits do not depend on
+/// which access token is current.
+#[derive(Debug, Clone)]
+pub struct UserToken {
+    pub user_id: String,
+    pub token: TokenInfo,
+}
+
+impl UserToken {
+    pub fn access_token(&self) -> &str {
+        &self.token.access_token
+    }
+}
+
 pub struct OAuthClient {
     client: BasicClient,
     scopes: Vec<Scope>,
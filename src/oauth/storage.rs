@@ -13,6 +13,11 @@ pub trait TokenStorage: Send + Sync + 'static {
     async fn fetch(&self, user_id: &str) -> Result<Option<TokenInfo>>;
     async fn persist(&self, user_id: &str, token: &TokenInfo) -> Result<()>;
     async fn revoke(&self, user_id: &str) -> Result<()>;
+    /// Ids of the users with a stored token. Stores that cannot enumerate their users return
+    /// none, which leaves stdio sessions on the default local user.
+    async fn list_users(&self) -> Result<Vec<String>> {
+        Ok(Vec::new())
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -125,6 +130,10 @@ impl TokenStorage for FileTokenStorage {
 
         Ok(())
     }
+
+    async fn list_users(&self) -> Result<Vec<String>> {
+        Ok(self.cache.read().keys().cloned().collect())
+    }
 }
 
 #[async_trait]
@@ -142,6 +151,10 @@ impl TokenStorage for InMemoryTokenStorage {
         self.cache.write().remove(user_id);
         Ok(())
     }
+
+    async fn list_users(&self) -> Result<Vec<String>> {
+        Ok(self.cache.read().keys().cloned().collect())
+    }
 }
 
 #[cfg(test)]
@@ -169,6 +182,10 @@ mod tests {
 
         assert!(loaded.is_some());
         assert_eq!(loaded.unwrap().access_token, "access");
+        assert_eq!(
+            storage.list_users().await.unwrap(),
+            vec!["user".to_string()]
+        );
 
         storage.revoke("user").await.unwrap();
         assert!(storage.fetch("user").await.unwrap().is_none());
@@ -284,11 +284,11 @@ impl ProxyState {
         };
 
         // redirect_uri検証（登録済みURIリストと照合）
+        let fallback_scope = "https://www.googleapis.com/auth/calendar.events".to_string();
         let (allowed_uris, default_scope) = match &validated_client {
             ValidatedClient::Registered(c) => (&c.redirect_uris, &c.scope),
             ValidatedClient::Metadata(m) => {
-                let scope = m.scope.as_ref()
-                    .unwrap_or(&"https://www.googleapis.com/auth/calendar.events".to_string());
+                let scope = m.scope.as_ref().unwrap_or(&fallback_scope);
                 (&m.redirect_uris, scope)
             }
         };
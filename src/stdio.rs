@@ -0,0 +1,161 @@
+use crate::oauth::TokenInfo;
+use crate::AppState;
+use anyhow::{anyhow, bail, Context, Result};
+use axum::extract::Query;
+use axum::routing::get;
+use axum::Router;
+use parking_lot::Mutex;
+use serde::Deserialize;
+use std::sync::Arc;
+use tokio::sync::oneshot;
+use tokio_util::sync::CancellationToken;
+use url::Url;
+
+const DEFAULT_LOCAL_USER: &str = "local";
+
+/// Picks the user id served by a `--stdio` process.
+///
+/// Order: `stdio.user_id` from config, the only user in the token store, then `"local"` when
+/// the store is empty. A store holding several users needs `stdio.user_id` to pick one.
+pub async fn resolve_local_user(state: &AppState) -> Result<String> {
+    if let Some(user_id) = state.config.stdio.user_id.as_deref() {
+        if !user_id.trim().is_empty() {
+            return Ok(user_id.trim().to_owned());
+        }
+    }
+
+    let mut users = state
+        .token_storage
+        .list_users()
+        .await
+        .context("failed to list stored users")?;
+    if users.len() == 1 {
+        return Ok(users.remove(0));
+    }
+    if users.len() > 1 {
+        users.sort();
+        bail!(
+            "token store holds several users ({}); set stdio.user_id to pick one",
+            users.join(", ")
+        );
+    }
+
+    Ok(DEFAULT_LOCAL_USER.to_owned())
+}
+
+#[derive(Debug, Deserialize)]
+struct LoopbackCallback {
+    state: String,
+    #[serde(default)]
+    code: Option<String>,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+/// Runs the OAuth flow for a local stdio session.
+///
+/// A temporary listener is bound to the loopback address of `oauth.redirect_uri`, the authorize
+/// URL is printed to stderr (stdout carries the MCP protocol), and the listener is shut down as
+/// soon as Google redirects back.
+pub async fn authorize_via_loopback(state: Arc<AppState>, user_id: &str) -> Result<TokenInfo> {
+    let redirect_uri = state.config.oauth.redirect_uri.clone();
+    let redirect = Url::parse(&redirect_uri).context("invalid oauth.redirect_uri")?;
+    let host = redirect
+        .host_str()
+        .ok_or_else(|| anyhow!("oauth.redirect_uri has no host"))?
+        .to_owned();
+    if !is_loopback_host(&host) {
+        bail!("oauth.redirect_uri must point to a loopback address in stdio mode, got '{host}'");
+    }
+    let port = redirect
+        .port_or_known_default()
+        .ok_or_else(|| anyhow!("oauth.redirect_uri has no port"))?;
+
+    let context = state
+        .oauth_client
+        .authorize_url(&redirect_uri, None)
+        .context("failed to build authorization url")?;
+
+    let (sender, receiver) = oneshot::channel::<LoopbackCallback>();
+    let sender = Arc::new(Mutex::new(Some(sender)));
+    let router = Router::new().route(
+        redirect.path(),
+        get(move |Query(callback): Query<LoopbackCallback>| {
+            let sender = sender.clone();
+            async move {
+                if let Some(sender) = sender.lock().take() {
+                    let _ = sender.send(callback);
+                }
+                "Authorization received. You can close this window and return to your MCP client."
+            }
+        }),
+    );
+
+    let listener = tokio::net::TcpListener::bind((host.as_str(), port))
+        .await
+        .with_context(|| format!("failed to bind loopback listener on {host}:{port}"))?;
+    let shutdown = CancellationToken::new();
+    let server_shutdown = shutdown.clone();
+    let server = tokio::spawn(async move {
+        axum::serve(listener, router)
+            .with_graceful_shutdown(async move { server_shutdown.cancelled().await })
+            .await
+    });
+
+    eprintln!(
+        "Google Calendar authorization required for user '{user_id}'.\nOpen this URL in a browser:\n\n  {}\n",
+        context.authorize_url
+    );
+
+    let callback = receiver.await;
+    shutdown.cancel();
+    let _ = server.await;
+    let callback = callback.context("loopback listener closed before the OAuth callback")?;
+
+    if callback.state != context.csrf_state {
+        bail!("OAuth callback state mismatch");
+    }
+    if let Some(error) = callback.error {
+        bail!("authorization was not granted: {error}");
+    }
+    let code = callback
+        .code
+        .ok_or_else(|| anyhow!("OAuth callback is missing the authorization code"))?;
+
+    let token = state
+        .oauth_client
+        .exchange_code(&redirect_uri, &code, &context.pkce_verifier, None)
+        .await
+        .context("failed to exchange authorization code")?;
+    state
+        .token_storage
+        .persist(user_id, &token)
+        .await
+        .context("failed to persist token")?;
+
+    tracing::info!(user_id = %user_id, "stored token from loopback authorization");
+    Ok(token)
+}
+
+fn is_loopback_host(host: &str) -> bool {
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    host.eq_ignore_ascii_case("localhost")
+        || host
+            .parse::<std::net::IpAddr>()
+            .map(|ip| ip.is_loopback())
+            .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loopback_hosts_are_detected() {
+        assert!(is_loopback_host("localhost"));
+        assert!(is_loopback_host("127.0.0.1"));
+        assert!(is_loopback_host("[::1]"));
+        assert!(!is_loopback_host("example.com"));
+        assert!(!is_loopback_host("10.0.0.1"));
+    }
+}
@@ -116,6 +116,29 @@ pub async fn ingest_bearer_token_from_headers(
     Ok(Some(token_info))
 }
 
+/// Whether the `Authorization: Bearer` header carries the access token stored for `user_id`.
+///
+/// Unlike [`ingest_bearer_token_from_headers`] nothing is stored, so a caller cannot claim a user
+/// by presenting a token of their own.
+pub async fn bearer_matches_stored_token(
+    state: &Arc<AppState>,
+    headers: &HeaderMap,
+    user_id: &str,
+) -> anyhow::Result<bool> {
+    let Some(presented) = bearer_token(headers) else {
+        return Ok(false);
+    };
+    let stored = state.token_storage.fetch(user_id).await?;
+    Ok(stored.is_some_and(|token| token.access_token == presented))
+}
+
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    let value = headers.get(AUTHORIZATION)?.to_str().ok()?;
+    let (scheme, token) = value.split_once(' ')?;
+    let token = token.trim();
+    (scheme.eq_ignore_ascii_case("Bearer") && !token.is_empty()).then_some(token)
+}
+
 fn header_with_presence(headers: &HeaderMap, names: &[&'static str]) -> (Option<String>, bool) {
     for name in names {
         if let Some(value) = headers.get(*name) {
@@ -0,0 +1,152 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use parking_lot::RwLock;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tokio::task;
+
+type UserRecords = HashMap<String, HashMap<String, Value>>;
+
+/// Small per-user key/value records kept next to the token store
+/// (idempotency keys, user preferences).
+#[async_trait]
+pub trait UserDataStorage: Send + Sync + 'static {
+    async fn get(&self, user_id: &str, key: &str) -> Result<Option<Value>>;
+    async fn put(&self, user_id: &str, key: &str, value: Value) -> Result<()>;
+    async fn remove(&self, user_id: &str, key: &str) -> Result<()>;
+}
+
+#[derive(Debug)]
+pub struct FileUserDataStorage {
+    path: PathBuf,
+    cache: RwLock<UserRecords>,
+}
+
+impl FileUserDataStorage {
+    pub fn new(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let cache = if path.exists() {
+            let data = fs::read_to_string(&path)?;
+            if data.trim().is_empty() {
+                HashMap::new()
+            } else {
+                serde_json::from_str::<UserRecords>(&data)?
+            }
+        } else {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            HashMap::new()
+        };
+
+        Ok(Self {
+            path,
+            cache: RwLock::new(cache),
+        })
+    }
+
+    async fn flush(&self) -> Result<()> {
+        let cache_snapshot = { self.cache.read().clone() };
+        let path = self.path.clone();
+
+        task::spawn_blocking(move || {
+            let serialized = serde_json::to_string_pretty(&cache_snapshot)?;
+            fs::write(path, serialized)?;
+            Ok(())
+        })
+        .await?
+    }
+}
+
+#[async_trait]
+impl UserDataStorage for FileUserDataStorage {
+    async fn get(&self, user_id: &str, key: &str) -> Result<Option<Value>> {
+        Ok(self
+            .cache
+            .read()
+            .get(user_id)
+            .and_then(|records| records.get(key))
+            .cloned())
+    }
+
+    async fn put(&self, user_id: &str, key: &str, value: Value) -> Result<()> {
+        self.cache
+            .write()
+            .entry(user_id.to_owned())
+            .or_default()
+            .insert(key.to_owned(), value);
+        self.flush().await
+    }
+
+    async fn remove(&self, user_id: &str, key: &str) -> Result<()> {
+        if let Some(records) = self.cache.write().get_mut(user_id) {
+            records.remove(key);
+        }
+        self.flush().await
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct InMemoryUserDataStorage {
+    cache: RwLock<UserRecords>,
+}
+
+impl InMemoryUserDataStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl UserDataStorage for InMemoryUserDataStorage {
+    async fn get(&self, user_id: &str, key: &str) -> Result<Option<Value>> {
+        Ok(self
+            .cache
+            .read()
+            .get(user_id)
+            .and_then(|records| records.get(key))
+            .cloned())
+    }
+
+    async fn put(&self, user_id: &str, key: &str, value: Value) -> Result<()> {
+        self.cache
+            .write()
+            .entry(user_id.to_owned())
+            .or_default()
+            .insert(key.to_owned(), value);
+        Ok(())
+    }
+
+    async fn remove(&self, user_id: &str, key: &str) -> Result<()> {
+        if let Some(records) = self.cache.write().get_mut(user_id) {
+            records.remove(key);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn file_storage_survives_reload() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("user_data.json");
+
+        let storage = FileUserDataStorage::new(&path).unwrap();
+        storage
+            .put("user", "greeting", Value::String("hello".into()))
+            .await
+            .unwrap();
+
+        let reloaded = FileUserDataStorage::new(&path).unwrap();
+        assert_eq!(
+            reloaded.get("user", "greeting").await.unwrap(),
+            Some(Value::String("hello".into()))
+        );
+        assert!(reloaded.get("other", "greeting").await.unwrap().is_none());
+    }
+}